@@ -1,5 +1,8 @@
 import { PublicKey, TransactionSignature } from '@solana/web3.js';
 import { Program, Wallet } from '@coral-xyz/anchor';
+import { getAssociatedTokenAddressSync, NATIVE_MINT } from '@solana/spl-token';
+
+const PUMPFUN_PROGRAM_ID = new PublicKey("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
 
 export class Executor {
     constructor(
@@ -20,17 +23,55 @@ export class Executor {
             
             const remainingAccounts = await this.getDexAccounts(position.preferredDex, position.inputMint, position.outputMint);
 
+            // Pump.fun positions need the curve so the program can detect graduation,
+            // plus the registered migration route once the curve has completed
+            const pumpFun = position.preferredDex.pumpFun !== undefined;
+            const tokenMint = position.inputMint.equals(NATIVE_MINT) ? position.outputMint : position.inputMint;
+            const bondingCurve = pumpFun ? this.findBondingCurveAddress(tokenMint) : null;
+            const migrationRoute = pumpFun ? await this.findExistingMigrationRoute(tokenMint) : null;
+
+            // Either side may be an SPL Token or a Token-2022 mint
+            const inputTokenProgram = await this.findTokenProgram(position.inputMint);
+            const outputTokenProgram = await this.findTokenProgram(position.outputMint);
+
+            // The protocol fee must land in the configured fee wallet's ATA for the output mint
+            const config = await this.program.account.globalConfig.fetch(this.findConfigAddress());
+            const protocolFeeDestination = getAssociatedTokenAddressSync(
+                position.outputMint,
+                config.feeDestination,
+                true,
+                outputTokenProgram
+            );
+
             // 3. Send Transaction
             const tx = await this.program.methods
                 .executePosition(
-                    new (require('bn.js').BN)(0) // Price ignored due to oracle
+                    new (require('bn.js').BN)(0), // Price ignored due to oracle
+                    null, // Single hop on the position's venue; pass legs here to route through an intermediate mint
+                    null, // Or split the input across venues by share
+                    null, // Or let the program quote candidate venues and take the best
+                    null // Or pass a Jupiter route/shared_accounts_route payload built for the vault
                 )
                 .accounts({
                     position: positionPubkey,
                     vault: position.vault,
+                    outputVault: position.outputVault,
+                    escrow: this.findEscrowAddress(positionPubkey),
+                    owner: position.owner,
+                    // Deliver straight to the owner when their ATAs exist, otherwise they settle later
+                    ownerInputToken: await this.findExistingAta(position.inputMint, position.owner, inputTokenProgram),
+                    ownerOutputToken: await this.findExistingAta(position.outputMint, position.owner, outputTokenProgram),
                     executor: this.wallet.publicKey,
                     config: this.findConfigAddress(),
                     oraclePriceFeed: position.oraclePriceFeed,
+                    pausedPair: this.findPausedPairAddress(position.inputMint, position.outputMint),
+                    protocolFeeDestination,
+                    bondingCurve,
+                    migrationRoute,
+                    inputMint: position.inputMint,
+                    outputMint: position.outputMint,
+                    tokenProgram: inputTokenProgram,
+                    outputTokenProgram,
                     // ... other accounts inferred by Anchor
                 })
                 .remainingAccounts(remainingAccounts)
@@ -45,9 +86,95 @@ export class Executor {
         }
     }
 
+    async expire(positionPubkey: PublicKey): Promise<TransactionSignature | null> {
+        console.log(`⌛ Attempting to expire position: ${positionPubkey.toString()}`);
+
+        try {
+            const position = await this.program.account.position.fetch(positionPubkey);
+            const inputTokenProgram = await this.findTokenProgram(position.inputMint);
+            const outputTokenProgram = await this.findTokenProgram(position.outputMint);
+
+            const tx = await this.program.methods
+                .expirePosition()
+                .accounts({
+                    position: positionPubkey,
+                    vault: position.vault,
+                    outputVault: position.outputVault,
+                    escrow: this.findEscrowAddress(positionPubkey),
+                    owner: position.owner,
+                    // Wrapped SOL is refunded as lamports, no ATA needed
+                    ownerTokenAccount: position.inputMint.equals(NATIVE_MINT)
+                        ? null
+                        : getAssociatedTokenAddressSync(position.inputMint, position.owner, false, inputTokenProgram),
+                    ownerOutputToken: await this.findExistingAta(position.outputMint, position.owner, outputTokenProgram),
+                    keeper: this.wallet.publicKey,
+                    config: this.findConfigAddress(),
+                    inputMint: position.inputMint,
+                    outputMint: position.outputMint,
+                    tokenProgram: inputTokenProgram,
+                    outputTokenProgram,
+                })
+                .rpc();
+
+            console.log(`✅ Expiry Successful! Tx: ${tx}`);
+            return tx;
+
+        } catch (err) {
+            console.error(`❌ Expiry Failed:`, err);
+            return null;
+        }
+    }
+
+    private async findExistingAta(mint: PublicKey, owner: PublicKey, tokenProgram: PublicKey): Promise<PublicKey | null> {
+        const ata = getAssociatedTokenAddressSync(mint, owner, false, tokenProgram);
+        const info = await this.program.provider.connection.getAccountInfo(ata);
+        return info ? ata : null;
+    }
+
+    private async findTokenProgram(mint: PublicKey): Promise<PublicKey> {
+        const info = await this.program.provider.connection.getAccountInfo(mint);
+        if (!info) throw new Error(`Mint ${mint.toString()} not found`);
+        return info.owner;
+    }
+
+    private findEscrowAddress(position: PublicKey): PublicKey {
+        const [pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("escrow"), position.toBuffer()],
+            this.program.programId
+        );
+        return pda;
+    }
+
+    // One address per pair whichever way it trades, mints in byte order
+    private findPausedPairAddress(mintX: PublicKey, mintY: PublicKey): PublicKey {
+        const [mintA, mintB] = [mintX, mintY].sort((a, b) => a.toBuffer().compare(b.toBuffer()));
+        const [pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("paused_pair"), mintA.toBuffer(), mintB.toBuffer()],
+            this.program.programId
+        );
+        return pda;
+    }
+
+    private findBondingCurveAddress(mint: PublicKey): PublicKey {
+        const [pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("bonding-curve"), mint.toBuffer()],
+            PUMPFUN_PROGRAM_ID
+        );
+        return pda;
+    }
+
+    private async findExistingMigrationRoute(mint: PublicKey): Promise<PublicKey | null> {
+        const [pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("migration_route"), mint.toBuffer()],
+            this.program.programId
+        );
+        const info = await this.program.provider.connection.getAccountInfo(pda);
+        return info ? pda : null;
+    }
+
     private findConfigAddress(): PublicKey {
         const [pda] = PublicKey.findProgramAddressSync(
-            [Buffer.from("global_config")],
+            [Buffer.from("config")],
             this.program.programId
         );
         return pda;
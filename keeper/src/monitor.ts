@@ -74,6 +74,12 @@ export class PositionMonitor {
         try {
             const account = position.account;
             const oracleAddress = account.oraclePriceFeed;
+
+            // Stale orders past their expiry are refunded to the owner
+            if (account.expiresAt && Date.now() / 1000 >= account.expiresAt.toNumber()) {
+                await this.executor.expire(position.publicKey);
+                return;
+            }
             
             // Get price from Pyth
             // In production, we'd use the streaming connection
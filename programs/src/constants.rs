@@ -1,5 +1,14 @@
 // Constants for the PyroSwap program
 
+// Config layout
+pub const GLOBAL_CONFIG_VERSION: u8 = 1;
+
+// Pause flags on `GlobalConfig::paused`
+pub const PAUSE_OPENS: u8 = 1 << 0;
+pub const PAUSE_SWAPS: u8 = 1 << 1;
+pub const PAUSE_EXECUTIONS: u8 = 1 << 2;
+pub const PAUSE_ALL: u8 = PAUSE_OPENS | PAUSE_SWAPS | PAUSE_EXECUTIONS;
+
 // Fee constraints
 pub const MAX_PROTOCOL_FEE_BPS: u16 = 500; // 5% max
 pub const MAX_REFERRAL_SHARE_BPS: u16 = 5000; // 50% of protocol fee max
@@ -10,15 +19,30 @@ pub const MAX_STOP_LOSS_BPS: u16 = 5000; // 50% max stop loss
 pub const MIN_TAKE_PROFIT_BPS: u16 = 100; // 1% min take profit
 pub const MAX_TAKE_PROFIT_BPS: u16 = 10000; // 100% max take profit
 
+// Expiry
+pub const MAX_EXPIRY_KEEPER_FEE_BPS: u16 = 2000; // 20% of the execution fee escrow max
+
 // Oracle constraints
 pub const MAX_PRICE_AGE_SLOTS: u64 = 25; // ~10 seconds on Solana
 pub const MIN_PRICE_CONFIDENCE_BPS: u64 = 100; // 1% max confidence interval
 
+// Routing
+pub const MAX_ROUTE_LEGS: usize = 4;
+pub const MAX_SPLIT_LEGS: usize = 4;
+pub const MAX_VENUE_CANDIDATES: usize = 4;
+
 // Slippage
 pub const DEFAULT_SLIPPAGE_BPS: u16 = 50; // 0.5% default slippage tolerance
 pub const MAX_SLIPPAGE_BPS: u16 = 1000; // 10% max slippage
 
 // Seeds
-pub const GLOBAL_CONFIG_SEED: &[u8] = b"global_config";
+pub const GLOBAL_CONFIG_SEED: &[u8] = b"config";
 pub const POSITION_SEED: &[u8] = b"position";
 pub const VAULT_SEED: &[u8] = b"vault";
+pub const OUTPUT_VAULT_SEED: &[u8] = b"output_vault";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const MIGRATION_ROUTE_SEED: &[u8] = b"migration_route";
+pub const WHITELISTED_PROGRAM_SEED: &[u8] = b"whitelisted_program";
+pub const PAUSED_PAIR_SEED: &[u8] = b"paused_pair";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const UNWRAP_SEED: &[u8] = b"unwrap";
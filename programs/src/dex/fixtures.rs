@@ -0,0 +1,215 @@
+//! Account fixtures for adapter quote tests.
+//!
+//! Pool snapshots are rebuilt byte-for-byte at the offsets each adapter parses,
+//! so a quote test exercises the same decoding path as on-chain execution.
+//!
+//! Swap tests run CPIs against mock programs: plain functions registered with
+//! `mock_program` and dispatched from a `SyscallStubs` in place of `invoke`.
+//! This is deliberate rather than a stand-in for a deployed mock program: the
+//! crate's unit tests have no validator, and the mocks only need to move
+//! balances the way the real program would so the adapter's measuring and
+//! checks run end to end.
+
+use std::sync::{Mutex, Once};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+/// An owned account that can hand out `AccountInfo`s
+pub struct FixtureAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+impl FixtureAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self { key, owner, lamports: 1_000_000_000, data }
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            false,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+/// A placeholder account for slice positions the quote does not read
+pub fn unused_account() -> FixtureAccount {
+    FixtureAccount::new(Pubkey::new_unique(), Pubkey::default(), vec![])
+}
+
+/// An SPL Token account holding `amount` of `mint`
+pub fn token_account(key: Pubkey, mint: Pubkey, amount: u64) -> FixtureAccount {
+    let mut data = vec![0u8; 165];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(Pubkey::new_unique().as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1; // AccountState::Initialized
+    FixtureAccount::new(key, anchor_spl::token::ID, data)
+}
+
+/// Rent-exempt reserve of a 165 byte token account
+pub const NATIVE_RENT_RESERVE: u64 = 2_039_280;
+
+/// A wrapped SOL account holding `amount` on top of its rent reserve
+pub fn native_token_account(key: Pubkey, amount: u64) -> FixtureAccount {
+    let mut account = token_account(key, anchor_spl::token::spl_token::native_mint::ID, amount);
+    account.data[109..113].copy_from_slice(&1u32.to_le_bytes()); // is_native: Some
+    write_u64(&mut account.data, 113, NATIVE_RENT_RESERVE);
+    account.lamports = NATIVE_RENT_RESERVE + amount;
+    account
+}
+
+/// A Token-2022 mint charging `basis_points` (capped at `maximum_fee`) on transfers
+pub fn transfer_fee_mint(key: Pubkey, basis_points: u16, maximum_fee: u64) -> FixtureAccount {
+    let mut data = vec![0u8; 166 + 4 + 108];
+    data[44] = 6; // decimals
+    data[45] = 1; // is_initialized
+    data[165] = 1; // AccountType::Mint
+    data[166..168].copy_from_slice(&1u16.to_le_bytes()); // ExtensionType::TransferFeeConfig
+    data[168..170].copy_from_slice(&108u16.to_le_bytes());
+    // Older and newer fees are identical, so the epoch does not matter
+    for fee in [170 + 72, 170 + 90] {
+        write_u64(&mut data, fee + 8, maximum_fee);
+        data[fee + 16..fee + 18].copy_from_slice(&basis_points.to_le_bytes());
+    }
+    FixtureAccount::new(key, anchor_spl::token_2022::ID, data)
+}
+
+/// A Token-2022 mint carrying a single extension of type `extension_type`
+pub fn extension_mint(key: Pubkey, extension_type: u16, value: &[u8]) -> FixtureAccount {
+    let mut data = vec![0u8; 166 + 4 + value.len()];
+    data[44] = 6; // decimals
+    data[45] = 1; // is_initialized
+    data[165] = 1; // AccountType::Mint
+    data[166..168].copy_from_slice(&extension_type.to_le_bytes());
+    data[168..170].copy_from_slice(&(value.len() as u16).to_le_bytes());
+    data[170..].copy_from_slice(value);
+    FixtureAccount::new(key, anchor_spl::token_2022::ID, data)
+}
+
+pub fn write_u64(data: &mut [u8], offset: usize, value: u64) {
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+pub fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) {
+    data[offset..offset + 32].copy_from_slice(value.as_ref());
+}
+
+/// Collect `AccountInfo`s for a fixture slice
+pub fn infos(accounts: &mut [FixtureAccount]) -> Vec<AccountInfo<'_>> {
+    accounts.iter_mut().map(|a| a.info()).collect()
+}
+
+/// A mock program run in place of a CPI into the program it is registered for
+pub type MockProgram = fn(&Instruction, &[AccountInfo], &[&[&[u8]]]) -> ProgramResult;
+
+static MOCK_PROGRAMS: Mutex<Vec<(Pubkey, MockProgram)>> = Mutex::new(Vec::new());
+
+/// Routes every CPI to the mock registered for its program
+struct MockRuntime;
+
+impl SyscallStubs for MockRuntime {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let program = MOCK_PROGRAMS
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(program_id, _)| *program_id == instruction.program_id)
+            .map(|(_, program)| *program)
+            .ok_or(ProgramError::IncorrectProgramId)?;
+        program(instruction, account_infos, signers_seeds)
+    }
+}
+
+/// Run `program` for every CPI into `program_id`. Syscall stubs are process
+/// wide, so all test modules share one runtime and register their mocks here.
+pub fn mock_program(program_id: Pubkey, program: MockProgram) {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(MockRuntime));
+    });
+    let mut programs = MOCK_PROGRAMS.lock().unwrap();
+    if !programs.iter().any(|(registered, _)| *registered == program_id) {
+        programs.push((program_id, program));
+    }
+}
+
+/// Whether the account at `meta` signed the CPI, directly or as a PyroSwap
+/// PDA through `signers_seeds`
+pub fn cpi_signed(meta: &AccountMeta, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> bool {
+    let signed = account_infos.iter().any(|a| *a.key == meta.pubkey && a.is_signer)
+        || signers_seeds.iter().any(|seeds| {
+            Pubkey::create_program_address(seeds, &crate::ID).is_ok_and(|pda| pda == meta.pubkey)
+        });
+    meta.is_signer && signed
+}
+
+/// The `AccountInfo` for an instruction account
+pub fn cpi_account<'a, 'info>(account_infos: &'a [AccountInfo<'info>], meta: &AccountMeta) -> std::result::Result<&'a AccountInfo<'info>, ProgramError> {
+    account_infos.iter().find(|a| *a.key == meta.pubkey).ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+/// Add `delta` to a token account's balance, failing on underflow like a transfer
+pub fn adjust_token_balance(account: &AccountInfo, delta: i128) -> ProgramResult {
+    let mut data = account.try_borrow_mut_data()?;
+    let balance = u64::from_le_bytes(data[64..72].try_into().unwrap()) as i128 + delta;
+    let balance = u64::try_from(balance).map_err(|_| ProgramError::InsufficientFunds)?;
+    data[64..72].copy_from_slice(&balance.to_le_bytes());
+    Ok(())
+}
+
+/// Mock the SPL Token instructions the program signs for itself: `approve`,
+/// `revoke` and `sync_native`
+pub fn mock_token_program() {
+    mock_program(anchor_spl::token::ID, token_program);
+}
+
+fn token_program(instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+    let source = cpi_account(account_infos, &instruction.accounts[0])?;
+    let mut data = source.try_borrow_mut_data()?;
+    match instruction.data[0] {
+        // Approve { amount }: source, delegate, owner. Revoke: source, owner
+        4 | 5 => {
+            let owner = &instruction.accounts[if instruction.data[0] == 4 { 2 } else { 1 }];
+            if data[32..64] != owner.pubkey.to_bytes() || !cpi_signed(owner, account_infos, signers_seeds) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            data[72..108].fill(0);
+            data[121..129].fill(0);
+            if instruction.data[0] == 4 {
+                data[72..76].copy_from_slice(&1u32.to_le_bytes());
+                data[76..108].copy_from_slice(instruction.accounts[1].pubkey.as_ref());
+                data[121..129].copy_from_slice(&instruction.data[1..9]);
+            }
+            Ok(())
+        }
+        // SyncNative: account
+        17 => {
+            if data[109..113] != 1u32.to_le_bytes() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let reserve = u64::from_le_bytes(data[113..121].try_into().unwrap());
+            let amount = source.lamports().checked_sub(reserve).ok_or(ProgramError::InsufficientFunds)?;
+            data[64..72].copy_from_slice(&amount.to_le_bytes());
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
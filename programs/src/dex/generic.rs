@@ -0,0 +1,392 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use crate::constants::WHITELISTED_PROGRAM_SEED;
+use crate::dex::{read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
+use crate::state::DexType;
+
+// Positions in the swap account slice (see `GenericAdapter::swap`)
+const WHITELIST_INDEX: usize = 0;
+const PROGRAM_INDEX: usize = 1;
+const SOURCE_INDEX: usize = 2;
+const DESTINATION_INDEX: usize = 3;
+const FORWARDED_ACCOUNTS_START: usize = 4;
+
+/// Offsets of the authority and balance in an SPL Token account
+const TOKEN_AUTHORITY_OFFSET: usize = 32;
+const TOKEN_AMOUNT_OFFSET: usize = 64;
+
+/// The whitelist entry that lets the generic adapter invoke `program`
+pub fn whitelisted_program_address(program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[WHITELISTED_PROGRAM_SEED, program.as_ref()], &crate::ID).0
+}
+
+/// Invokes a whitelisted program with caller-supplied instruction data.
+///
+/// Nothing about the target's layout is known, so the swap is judged only by
+/// its effect: the source may lose at most `amount_in`, the destination must
+/// gain at least `min_amount_out` and neither may change in any other way,
+/// and every other account this program owns or the source's authority
+/// controls must come back untouched.
+///
+/// `adapter_for(DexType::Generic)` has no instruction data and refuses to
+/// swap; callers that carry data build their own `GenericAdapter`.
+pub struct GenericAdapter<'a> {
+    pub instruction_data: &'a [u8],
+}
+
+impl GenericAdapter<'_> {
+    /// The token account in the swap slice that pays for the swap
+    pub fn source_account<'a, 'info>(accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() >= FORWARDED_ACCOUNTS_START, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[SOURCE_INDEX])
+    }
+}
+
+impl DexAdapter for GenericAdapter<'_> {
+    fn dex_type(&self) -> DexType {
+        DexType::Generic
+    }
+
+    /// Any whitelisted program; the slice names the one to call
+    fn program_id(&self) -> Pubkey {
+        Pubkey::default()
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() >= FORWARDED_ACCOUNTS_START, ErrorCode::InvalidAccountsLength);
+        require!(!self.instruction_data.is_empty(), ErrorCode::MissingInstructionData);
+
+        let program = &accounts[PROGRAM_INDEX];
+        require!(program.executable, PyroSwapError::InvalidDexProgram);
+        let whitelist = &accounts[WHITELIST_INDEX];
+        require!(
+            *whitelist.owner == crate::ID && *whitelist.key == whitelisted_program_address(program.key),
+            ErrorCode::ProgramNotWhitelisted
+        );
+
+        let (mint, _) = read_token_account(&accounts[SOURCE_INDEX])?;
+        require_keys_eq!(mint, *source_mint, ErrorCode::MintMismatch);
+        read_token_account(&accounts[DESTINATION_INDEX])?;
+        require_keys_neq!(*accounts[SOURCE_INDEX].key, *accounts[DESTINATION_INDEX].key, ErrorCode::MintMismatch);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
+        _amount_in: u64,
+        _source_mint: &Pubkey,
+        _accounts: &[AccountInfo<'info>],
+    ) -> Result<DexQuote> {
+        err!(ErrorCode::NotQuotable)
+    }
+
+    fn swap<'info>(
+        &self,
+        amount_in: u64,
+        min_amount_out: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        signer_seeds: Option<&[&[&[u8]]]>,
+    ) -> Result<u64> {
+        // Generic passthrough CPI
+        // accounts expected:
+        // 0. Whitelist entry for the target program
+        // 1. Target program
+        // 2. Source token account
+        // 3. Destination token account
+        // 4.. Accounts of the target instruction, in order (including 2 and 3)
+
+        self.validate_accounts(source_mint, accounts)?;
+
+        let source = &accounts[SOURCE_INDEX];
+        let destination = &accounts[DESTINATION_INDEX];
+        let forwarded = &accounts[FORWARDED_ACCOUNTS_START..];
+        let (_, source_before) = read_token_account(source)?;
+        let (_, destination_before) = read_token_account(destination)?;
+        let protected = protected_snapshot(source, destination, forwarded)?;
+
+        let instruction = anchor_lang::solana_program::instruction::Instruction {
+            program_id: *accounts[PROGRAM_INDEX].key,
+            accounts: forwarded.iter().map(|a| {
+                anchor_lang::solana_program::instruction::AccountMeta {
+                    pubkey: *a.key,
+                    is_signer: a.is_signer,
+                    is_writable: a.is_writable,
+                }
+            }).collect(),
+            data: self.instruction_data.to_vec(),
+        };
+
+        if let Some(seeds) = signer_seeds {
+            anchor_lang::solana_program::program::invoke_signed(&instruction, accounts, seeds)?;
+        } else {
+            invoke(&instruction, accounts)?;
+        }
+
+        let (_, source_after) = read_token_account(source)?;
+        let (_, destination_after) = read_token_account(destination)?;
+        check_balance_deltas(
+            (source_before, source_after),
+            (destination_before, destination_after),
+            amount_in,
+            min_amount_out,
+        )?;
+        check_unchanged(&protected, accounts)?;
+
+        Ok(destination_after - destination_before)
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        _source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() >= FORWARDED_ACCOUNTS_START, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[DESTINATION_INDEX])
+    }
+
+    fn pool_account<'a, 'info>(&self, _accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        // The whitelisted program's accounts follow no known layout
+        err!(PyroSwapError::InvalidDexType)
+    }
+}
+
+/// Owner, lamports and data of an account the target must not touch. The
+/// swap's own source and destination keep no lamports and a zeroed balance,
+/// so only their owner, delegate, delegated amount, close authority, state
+/// and extensions are compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSnapshot {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: Option<u64>,
+    pub data: Vec<u8>,
+}
+
+impl AccountSnapshot {
+    fn of(account: &AccountInfo) -> Result<Self> {
+        Ok(Self {
+            key: *account.key,
+            owner: *account.owner,
+            lamports: Some(account.lamports()),
+            data: account.try_borrow_data()?.to_vec(),
+        })
+    }
+
+    /// Everything but the balance, which the swap is there to move
+    fn of_swap_account(account: &AccountInfo) -> Result<Self> {
+        read_token_account(account)?;
+        let mut data = account.try_borrow_data()?.to_vec();
+        data[TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8].fill(0);
+        Ok(Self { key: *account.key, owner: *account.owner, lamports: None, data })
+    }
+
+    /// The same kind of snapshot of `account` now
+    fn retake(&self, account: &AccountInfo) -> Result<Self> {
+        match self.lamports {
+            Some(_) => Self::of(account),
+            None => Self::of_swap_account(account),
+        }
+    }
+}
+
+/// Snapshot the source and destination, then every other forwarded account
+/// that this program owns, or that is a token account under the source's
+/// authority
+pub fn protected_snapshot(
+    source: &AccountInfo,
+    destination: &AccountInfo,
+    forwarded: &[AccountInfo],
+) -> Result<Vec<AccountSnapshot>> {
+    let authority = token_authority(source);
+    let mut snapshots = vec![AccountSnapshot::of_swap_account(source)?, AccountSnapshot::of_swap_account(destination)?];
+    for account in forwarded {
+        if account.key == source.key || account.key == destination.key {
+            continue;
+        }
+        if snapshots.iter().any(|snapshot| snapshot.key == *account.key) {
+            continue;
+        }
+        let controlled = authority.is_some() && token_authority(account) == authority;
+        if *account.owner == crate::ID || controlled {
+            snapshots.push(AccountSnapshot::of(account)?);
+        }
+    }
+    Ok(snapshots)
+}
+
+/// Require every snapshotted account to be exactly as it was
+pub fn check_unchanged(snapshots: &[AccountSnapshot], accounts: &[AccountInfo]) -> Result<()> {
+    for snapshot in snapshots {
+        let account = accounts
+            .iter()
+            .find(|account| *account.key == snapshot.key)
+            .ok_or(ErrorCode::ProtectedAccountChanged)?;
+        require!(snapshot.retake(account)? == *snapshot, ErrorCode::ProtectedAccountChanged);
+    }
+    Ok(())
+}
+
+/// The source may lose at most `amount_in` and the destination must gain at
+/// least `min_amount_out`. Balances are `(before, after)`.
+pub fn check_balance_deltas(
+    source: (u64, u64),
+    destination: (u64, u64),
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    let spent = source.0.saturating_sub(source.1);
+    require!(source.1 <= source.0 && spent <= amount_in, ErrorCode::SourceOverspent);
+    let received = destination.1.checked_sub(destination.0).ok_or(PyroSwapError::SlippageExceeded)?;
+    require!(received >= min_amount_out, PyroSwapError::SlippageExceeded);
+    Ok(())
+}
+
+/// The authority of an SPL Token or Token-2022 account, if `account` is one
+fn token_authority(account: &AccountInfo) -> Option<Pubkey> {
+    read_token_account(account).ok()?;
+    let data = account.try_borrow_data().ok()?;
+    Some(Pubkey::new_from_array(
+        data[TOKEN_AUTHORITY_OFFSET..TOKEN_AUTHORITY_OFFSET + 32].try_into().unwrap(),
+    ))
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid number of accounts provided")]
+    InvalidAccountsLength,
+    #[msg("Program is not on the generic adapter whitelist")]
+    ProgramNotWhitelisted,
+    #[msg("Generic swaps need caller-supplied instruction data")]
+    MissingInstructionData,
+    #[msg("Generic venues cannot be quoted on-chain")]
+    NotQuotable,
+    #[msg("Source and destination accounts do not match the swap")]
+    MintMismatch,
+    #[msg("Source account lost more than the swap amount")]
+    SourceOverspent,
+    #[msg("An account outside the swap changed")]
+    ProtectedAccountChanged,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+
+    const TOKEN_DELEGATE_OFFSET: usize = 72;
+    const TOKEN_STATE_OFFSET: usize = 108;
+    const TOKEN_DELEGATED_AMOUNT_OFFSET: usize = 121;
+
+    /// A token account under `authority`
+    fn owned_token_account(mint: Pubkey, authority: &Pubkey, amount: u64) -> FixtureAccount {
+        let mut account = token_account(Pubkey::new_unique(), mint, amount);
+        write_pubkey(&mut account.data, TOKEN_AUTHORITY_OFFSET, authority);
+        account
+    }
+
+    fn swap_slice(program: Pubkey, mint: Pubkey, authority: &Pubkey) -> Vec<FixtureAccount> {
+        vec![
+            FixtureAccount::new(whitelisted_program_address(&program), crate::ID, vec![0u8; 41]),
+            FixtureAccount::new(program, Pubkey::default(), vec![]),
+            owned_token_account(mint, authority, 1_000),
+            owned_token_account(Pubkey::new_unique(), authority, 0),
+        ]
+    }
+
+    #[test]
+    fn bounds_the_balance_deltas() {
+        assert!(check_balance_deltas((1_000, 900), (0, 50), 100, 50).is_ok());
+        assert!(check_balance_deltas((1_000, 1_000), (0, 50), 100, 50).is_ok());
+        assert!(check_balance_deltas((1_000, 899), (0, 50), 100, 50).is_err());
+        assert!(check_balance_deltas((1_000, 900), (0, 49), 100, 50).is_err());
+        assert!(check_balance_deltas((1_000, 900), (60, 50), 100, 0).is_err());
+    }
+
+    #[test]
+    fn protects_program_accounts_and_the_authority_s_other_token_accounts() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut forwarded = vec![
+            owned_token_account(mint, &authority, 1_000),
+            owned_token_account(Pubkey::new_unique(), &authority, 0),
+            FixtureAccount::new(Pubkey::new_unique(), crate::ID, vec![7u8; 16]),
+            owned_token_account(mint, &authority, 5_000),
+            owned_token_account(mint, &Pubkey::new_unique(), 5_000),
+            FixtureAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), vec![1u8; 8]),
+        ];
+        let protected_keys = [forwarded[0].key, forwarded[1].key, forwarded[2].key, forwarded[3].key];
+
+        let infos = infos(&mut forwarded);
+        let snapshots = protected_snapshot(&infos[0], &infos[1], &infos).unwrap();
+        assert_eq!(snapshots.iter().map(|s| s.key).collect::<Vec<_>>(), protected_keys);
+        assert!(check_unchanged(&snapshots, &infos).is_ok());
+
+        // The pool's vault may move, the authority's other account may not
+        infos[4].try_borrow_mut_data().unwrap()[64] = 0;
+        assert!(check_unchanged(&snapshots, &infos).is_ok());
+        infos[3].try_borrow_mut_data().unwrap()[64] = 0;
+        assert!(check_unchanged(&snapshots, &infos).is_err());
+    }
+
+    #[test]
+    fn source_and_destination_may_only_change_balance() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut forwarded = vec![
+            owned_token_account(mint, &authority, 1_000),
+            owned_token_account(Pubkey::new_unique(), &authority, 0),
+        ];
+
+        let slice = infos(&mut forwarded);
+        let snapshots = protected_snapshot(&slice[0], &slice[1], &slice).unwrap();
+        write_u64(&mut slice[0].try_borrow_mut_data().unwrap(), TOKEN_AMOUNT_OFFSET, 900);
+        write_u64(&mut slice[1].try_borrow_mut_data().unwrap(), TOKEN_AMOUNT_OFFSET, 50);
+        assert!(check_unchanged(&snapshots, &slice).is_ok());
+
+        // Handing the destination to another owner, approving a delegate on
+        // the source, or freezing either is not a swap
+        let changes: [(usize, usize, u8); 4] = [
+            (1, TOKEN_AUTHORITY_OFFSET, 1),
+            (0, TOKEN_DELEGATE_OFFSET, 1),
+            (0, TOKEN_DELEGATED_AMOUNT_OFFSET, 1),
+            (1, TOKEN_STATE_OFFSET, 2),
+        ];
+        for (index, offset, value) in changes {
+            let mut accounts = vec![
+                owned_token_account(mint, &authority, 1_000),
+                owned_token_account(Pubkey::new_unique(), &authority, 0),
+            ];
+            let slice = infos(&mut accounts);
+            let snapshots = protected_snapshot(&slice[0], &slice[1], &slice).unwrap();
+            slice[index].try_borrow_mut_data().unwrap()[offset] = value;
+            assert!(check_unchanged(&snapshots, &slice).is_err());
+        }
+    }
+
+    #[test]
+    fn requires_a_whitelisted_program_and_instruction_data() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let adapter = GenericAdapter { instruction_data: &[1, 2, 3] };
+
+        let mut accounts = swap_slice(program, mint, &authority);
+        let mut infos = infos(&mut accounts);
+        infos[PROGRAM_INDEX].executable = true;
+        assert!(adapter.validate_accounts(&mint, &infos).is_ok());
+        assert!(adapter.validate_accounts(&Pubkey::new_unique(), &infos).is_err());
+        assert!(GenericAdapter { instruction_data: &[] }.validate_accounts(&mint, &infos).is_err());
+        assert!(adapter.get_quote(1_000, &mint, &infos).is_err());
+
+        // An entry for another program does not cover this one
+        let mut other = swap_slice(Pubkey::new_unique(), mint, &authority);
+        let mut mismatched = infos.clone();
+        mismatched[WHITELIST_INDEX] = other[WHITELIST_INDEX].info();
+        assert!(adapter.validate_accounts(&mint, &mismatched).is_err());
+
+        infos[PROGRAM_INDEX].executable = false;
+        assert!(adapter.validate_accounts(&mint, &infos).is_err());
+    }
+}
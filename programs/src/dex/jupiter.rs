@@ -0,0 +1,429 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use crate::dex::{check_balance_deltas, check_program_account, read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
+use crate::state::DexType;
+
+/// Jupiter Aggregator v6 Program ID
+pub const JUPITER_V6_PROGRAM_ID: Pubkey = solana_program::pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// `route` instruction discriminator
+pub const ROUTE_INSTRUCTION: [u8; 8] = [229, 23, 203, 151, 122, 227, 173, 42];
+/// `shared_accounts_route` instruction discriminator
+pub const SHARED_ACCOUNTS_ROUTE_INSTRUCTION: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
+
+/// The program sits first in the swap slice, ahead of the instruction's accounts
+const PROGRAM_INDEX: usize = 0;
+
+/// `in_amount: u64, quoted_out_amount: u64, slippage_bps: u16, platform_fee_bps: u8`
+/// close both instructions, after the variable-length route plan
+const ARGS_TAIL_LEN: usize = 19;
+
+/// The arguments PyroSwap checks in a `route` or `shared_accounts_route` payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JupiterRouteArgs {
+    pub shared_accounts: bool,
+    pub in_amount: u64,
+    pub quoted_out_amount: u64,
+    pub slippage_bps: u16,
+    pub platform_fee_bps: u8,
+}
+
+impl JupiterRouteArgs {
+    /// Read the fixed arguments from either instruction. The route plan itself
+    /// is Jupiter's business and is passed through unread.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(!data.is_empty(), ErrorCode::MissingRoute);
+        // Discriminator, route plan length, and for the shared variant its `id`
+        let shared_accounts = match data.get(..8) {
+            Some(tag) if tag == ROUTE_INSTRUCTION => false,
+            Some(tag) if tag == SHARED_ACCOUNTS_ROUTE_INSTRUCTION => true,
+            _ => return err!(ErrorCode::InvalidRouteInstruction),
+        };
+        let header_len = if shared_accounts { 8 + 1 + 4 } else { 8 + 4 };
+        require!(data.len() >= header_len + ARGS_TAIL_LEN, ErrorCode::InvalidRouteInstruction);
+
+        let tail = &data[data.len() - ARGS_TAIL_LEN..];
+        Ok(Self {
+            shared_accounts,
+            in_amount: u64::from_le_bytes(tail[0..8].try_into().unwrap()),
+            quoted_out_amount: u64::from_le_bytes(tail[8..16].try_into().unwrap()),
+            slippage_bps: u16::from_le_bytes(tail[16..18].try_into().unwrap()),
+            platform_fee_bps: tail[18],
+        })
+    }
+
+    /// Positions of the accounts PyroSwap reads, in the swap slice
+    fn layout(&self) -> RouteLayout {
+        if self.shared_accounts {
+            // 0. Token program
+            // 1. Program authority
+            // 2. User transfer authority (signer)
+            // 3. Source token account
+            // 4. Program source token account
+            // 5. Program destination token account
+            // 6. Destination token account
+            // 7. Source mint
+            // 8. Destination mint
+            // 9. Platform fee account
+            // 10. Token-2022 program
+            // 11. Event authority
+            // 12. Jupiter program
+            RouteLayout { authority: 1 + 2, source: 1 + 3, destination: 1 + 6, optional_destination: None, accounts_len: 1 + 13 }
+        } else {
+            // 0. Token program
+            // 1. User transfer authority (signer)
+            // 2. User source token account
+            // 3. User destination token account
+            // 4. Destination token account, optional: the Jupiter program
+            //    stands in for None, otherwise the output goes here instead
+            // 5. Destination mint
+            // 6. Platform fee account
+            // 7. Event authority
+            // 8. Jupiter program
+            RouteLayout { authority: 1 + 1, source: 1 + 2, destination: 1 + 3, optional_destination: Some(1 + 4), accounts_len: 1 + 9 }
+        }
+    }
+}
+
+struct RouteLayout {
+    authority: usize,
+    source: usize,
+    destination: usize,
+    /// `route`'s override for where the output lands
+    optional_destination: Option<usize>,
+    accounts_len: usize,
+}
+
+/// Runs a Jupiter v6 `route` or `shared_accounts_route` built off-chain.
+///
+/// The payload must spend exactly the swap amount, carry no platform fee, and
+/// name the source account's own authority as the transfer authority; when
+/// PyroSwap signs for a vault, that is the vault PDA. Jupiter's quoted output
+/// and slippage are not trusted: the output is measured and held to
+/// `min_amount_out` like every other venue.
+///
+/// The swap slice is the Jupiter program followed by the instruction's
+/// accounts exactly as Jupiter expects them, hop accounts included.
+pub struct JupiterAdapter<'a> {
+    pub instruction_data: &'a [u8],
+}
+
+impl DexAdapter for JupiterAdapter<'_> {
+    fn dex_type(&self) -> DexType {
+        DexType::Jupiter
+    }
+
+    fn program_id(&self) -> Pubkey {
+        JUPITER_V6_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        let args = JupiterRouteArgs::parse(self.instruction_data)?;
+        require!(args.platform_fee_bps == 0, ErrorCode::PlatformFeeNotAllowed);
+        let layout = args.layout();
+        require!(accounts.len() >= layout.accounts_len, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, PROGRAM_INDEX, &JUPITER_V6_PROGRAM_ID)?;
+
+        let source = &accounts[layout.source];
+        let (mint, _) = read_token_account(source)?;
+        require_keys_eq!(mint, *source_mint, ErrorCode::MintMismatch);
+        let authority = Pubkey::new_from_array(source.try_borrow_data()?[32..64].try_into().unwrap());
+        require_keys_eq!(*accounts[layout.authority].key, authority, ErrorCode::AuthorityMismatch);
+
+        let destination = accounts[layout.destination].key;
+        read_token_account(&accounts[layout.destination])?;
+        require_keys_neq!(*source.key, *destination, ErrorCode::MintMismatch);
+        // The output must land in the destination we measure, not one the payload redirects to
+        if let Some(index) = layout.optional_destination {
+            let redirect = accounts[index].key;
+            require!(
+                *redirect == JUPITER_V6_PROGRAM_ID || redirect == destination,
+                ErrorCode::DestinationMismatch
+            );
+        }
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
+        _amount_in: u64,
+        _source_mint: &Pubkey,
+        _accounts: &[AccountInfo<'info>],
+    ) -> Result<DexQuote> {
+        err!(ErrorCode::NotQuotable)
+    }
+
+    fn swap<'info>(
+        &self,
+        amount_in: u64,
+        min_amount_out: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        signer_seeds: Option<&[&[&[u8]]]>,
+    ) -> Result<u64> {
+        self.validate_accounts(source_mint, accounts)?;
+        let args = JupiterRouteArgs::parse(self.instruction_data)?;
+        require!(args.in_amount == amount_in, ErrorCode::InAmountMismatch);
+        let layout = args.layout();
+
+        let source = &accounts[layout.source];
+        let destination = &accounts[layout.destination];
+        let (_, source_before) = read_token_account(source)?;
+        let (_, destination_before) = read_token_account(destination)?;
+
+        // The transfer authority signs, either directly or through `signer_seeds`
+        let instruction = anchor_lang::solana_program::instruction::Instruction {
+            program_id: JUPITER_V6_PROGRAM_ID,
+            accounts: accounts[PROGRAM_INDEX + 1..].iter().enumerate().map(|(i, a)| {
+                anchor_lang::solana_program::instruction::AccountMeta {
+                    pubkey: *a.key,
+                    is_signer: a.is_signer || i + 1 == layout.authority,
+                    is_writable: a.is_writable,
+                }
+            }).collect(),
+            data: self.instruction_data.to_vec(),
+        };
+
+        if let Some(seeds) = signer_seeds {
+            anchor_lang::solana_program::program::invoke_signed(&instruction, accounts, seeds)?;
+        } else {
+            invoke(&instruction, accounts)?;
+        }
+
+        let (_, source_after) = read_token_account(source)?;
+        let (_, destination_after) = read_token_account(destination)?;
+        check_balance_deltas(
+            (source_before, source_after),
+            (destination_before, destination_after),
+            amount_in,
+            min_amount_out,
+        )?;
+
+        Ok(destination_after - destination_before)
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        _source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        let layout = JupiterRouteArgs::parse(self.instruction_data)?.layout();
+        require!(accounts.len() >= layout.accounts_len, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[layout.destination])
+    }
+
+    fn pool_account<'a, 'info>(&self, _accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        // A route may cross any number of pools
+        err!(PyroSwapError::InvalidDexType)
+    }
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid number of accounts provided")]
+    InvalidAccountsLength,
+    #[msg("Jupiter swaps need a route payload")]
+    MissingRoute,
+    #[msg("Payload is not a Jupiter route or shared_accounts_route instruction")]
+    InvalidRouteInstruction,
+    #[msg("Jupiter routes are quoted off-chain")]
+    NotQuotable,
+    #[msg("Route must not charge a platform fee")]
+    PlatformFeeNotAllowed,
+    #[msg("Route input does not match the swap amount")]
+    InAmountMismatch,
+    #[msg("Transfer authority does not own the source account")]
+    AuthorityMismatch,
+    #[msg("Source and destination accounts do not match the swap")]
+    MintMismatch,
+    #[msg("Route sends its output somewhere other than the destination account")]
+    DestinationMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+    use anchor_lang::solana_program::entrypoint::ProgramResult;
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    /// Stands in for Jupiter during CPI: checks the transfer authority signed,
+    /// debits `in_amount` from the source and credits `quoted_out_amount` to
+    /// the destination, at the positions Jupiter's account interface uses.
+    /// Like Jupiter, `route` pays a `destination_token_account` other than
+    /// the program id instead of the user's destination.
+    fn mock_jupiter(instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        let args = JupiterRouteArgs::parse(&instruction.data).map_err(|_| ProgramError::InvalidInstructionData)?;
+        // Instruction positions are the slice positions less the program
+        let layout = args.layout();
+        let meta = |index: usize| &instruction.accounts[index - 1];
+
+        if !cpi_signed(meta(layout.authority), account_infos, signers_seeds) {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        adjust_token_balance(cpi_account(account_infos, meta(layout.source))?, -(args.in_amount as i128))?;
+
+        let paid = match layout.optional_destination {
+            Some(index) if meta(index).pubkey != JUPITER_V6_PROGRAM_ID => index,
+            _ => layout.destination,
+        };
+        adjust_token_balance(cpi_account(account_infos, meta(paid))?, args.quoted_out_amount as i128)
+    }
+
+    fn install_mock() {
+        mock_program(JUPITER_V6_PROGRAM_ID, mock_jupiter);
+    }
+
+    fn payload(shared_accounts: bool, in_amount: u64, quoted_out_amount: u64, platform_fee_bps: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        if shared_accounts {
+            data.extend_from_slice(&SHARED_ACCOUNTS_ROUTE_INSTRUCTION);
+            data.push(3); // id
+        } else {
+            data.extend_from_slice(&ROUTE_INSTRUCTION);
+        }
+        // A one-step plan: `Swap::Whirlpool { a_to_b: true }`, 100%, 0 -> 1
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&[17, 1, 100, 0, 1]);
+        data.extend_from_slice(&in_amount.to_le_bytes());
+        data.extend_from_slice(&quoted_out_amount.to_le_bytes());
+        data.extend_from_slice(&50u16.to_le_bytes());
+        data.push(platform_fee_bps);
+        data
+    }
+
+    /// A swap slice for `payload` selling out of a vault owned by `authority`
+    fn swap_slice(shared_accounts: bool, mint: Pubkey, authority: Pubkey) -> Vec<FixtureAccount> {
+        let layout = JupiterRouteArgs { shared_accounts, in_amount: 0, quoted_out_amount: 0, slippage_bps: 0, platform_fee_bps: 0 }.layout();
+        let mut accounts: Vec<FixtureAccount> = (0..layout.accounts_len + 2).map(|_| unused_account()).collect();
+        accounts[PROGRAM_INDEX] = FixtureAccount::new(JUPITER_V6_PROGRAM_ID, Pubkey::default(), vec![]);
+        if let Some(index) = layout.optional_destination {
+            accounts[index] = FixtureAccount::new(JUPITER_V6_PROGRAM_ID, Pubkey::default(), vec![]);
+        }
+        accounts[layout.authority] = FixtureAccount::new(authority, Pubkey::default(), vec![]);
+        accounts[layout.source] = token_account(Pubkey::new_unique(), mint, 1_000_000);
+        write_pubkey(&mut accounts[layout.source].data, 32, &authority);
+        accounts[layout.destination] = token_account(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+        accounts
+    }
+
+    fn vault_signer(position: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"vault", position.as_ref()], &crate::ID)
+    }
+
+    #[test]
+    fn parses_both_route_instructions() {
+        let args = JupiterRouteArgs::parse(&payload(false, 1_000, 990, 0)).unwrap();
+        assert_eq!(
+            args,
+            JupiterRouteArgs { shared_accounts: false, in_amount: 1_000, quoted_out_amount: 990, slippage_bps: 50, platform_fee_bps: 0 }
+        );
+        assert!(JupiterRouteArgs::parse(&payload(true, 1_000, 990, 0)).unwrap().shared_accounts);
+
+        assert!(JupiterRouteArgs::parse(&[]).is_err());
+        assert!(JupiterRouteArgs::parse(&payload(false, 1_000, 990, 0)[..8 + 4 + ARGS_TAIL_LEN - 1]).is_err());
+        let mut unknown = payload(false, 1_000, 990, 0);
+        unknown[0] = 0;
+        assert!(JupiterRouteArgs::parse(&unknown).is_err());
+    }
+
+    #[test]
+    fn routes_from_a_vault_through_the_mock() {
+        install_mock();
+        let position = Pubkey::new_unique();
+        let (vault_authority, bump) = vault_signer(&position);
+        let mint = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault", position.as_ref(), &[bump]];
+
+        for shared_accounts in [false, true] {
+            let data = payload(shared_accounts, 400_000, 39_000, 0);
+            let adapter = JupiterAdapter { instruction_data: &data };
+            let mut accounts = swap_slice(shared_accounts, mint, vault_authority);
+            let slice = infos(&mut accounts);
+
+            let out = adapter.swap(400_000, 38_000, &mint, &slice, Some(&[seeds])).unwrap();
+            assert_eq!(out, 39_000);
+            assert_eq!(read_token_account(adapter.destination_account(&mint, &slice).unwrap()).unwrap().1, 39_000);
+            let layout = JupiterRouteArgs::parse(&data).unwrap().layout();
+            assert_eq!(read_token_account(&slice[layout.source]).unwrap().1, 600_000);
+        }
+    }
+
+    #[test]
+    fn measures_the_output_against_the_minimum() {
+        install_mock();
+        let position = Pubkey::new_unique();
+        let (vault_authority, bump) = vault_signer(&position);
+        let mint = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault", position.as_ref(), &[bump]];
+
+        let data = payload(false, 400_000, 39_000, 0);
+        let mut accounts = swap_slice(false, mint, vault_authority);
+        let slice = infos(&mut accounts);
+        assert!(JupiterAdapter { instruction_data: &data }.swap(400_000, 39_001, &mint, &slice, Some(&[seeds])).is_err());
+
+        // Without the vault's seeds the mock refuses, as Jupiter would
+        let mut accounts = swap_slice(false, mint, vault_authority);
+        let slice = infos(&mut accounts);
+        assert!(JupiterAdapter { instruction_data: &data }.swap(400_000, 0, &mint, &slice, None).is_err());
+    }
+
+    #[test]
+    fn rejects_a_redirected_destination() {
+        install_mock();
+        let position = Pubkey::new_unique();
+        let (vault_authority, bump) = vault_signer(&position);
+        let mint = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault", position.as_ref(), &[bump]];
+        let data = payload(false, 400_000, 39_000, 0);
+        let adapter = JupiterAdapter { instruction_data: &data };
+        let layout = JupiterRouteArgs::parse(&data).unwrap().layout();
+        let redirect = layout.optional_destination.unwrap();
+
+        // Naming the measured destination itself is the same as None
+        let mut accounts = swap_slice(false, mint, vault_authority);
+        let destination = &accounts[layout.destination];
+        accounts[redirect] = FixtureAccount::new(destination.key, destination.owner, destination.data.clone());
+        let slice = infos(&mut accounts);
+        assert_eq!(adapter.swap(400_000, 38_000, &mint, &slice, Some(&[seeds])).unwrap(), 39_000);
+
+        // Another account would take the output while the vault is still debited
+        let mut accounts = swap_slice(false, mint, vault_authority);
+        accounts[redirect] = token_account(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+        let slice = infos(&mut accounts);
+        assert!(adapter.validate_accounts(&mint, &slice).is_err());
+        assert!(adapter.swap(400_000, 0, &mint, &slice, Some(&[seeds])).is_err());
+        assert_eq!(read_token_account(&slice[redirect]).unwrap().1, 0);
+        assert_eq!(read_token_account(&slice[layout.source]).unwrap().1, 1_000_000);
+    }
+
+    #[test]
+    fn rejects_payloads_that_do_not_match_the_swap() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut accounts = swap_slice(false, mint, authority);
+        let slice = infos(&mut accounts);
+
+        let data = payload(false, 400_000, 39_000, 0);
+        let adapter = JupiterAdapter { instruction_data: &data };
+        assert!(adapter.validate_accounts(&mint, &slice).is_ok());
+        assert!(adapter.validate_accounts(&Pubkey::new_unique(), &slice).is_err());
+        assert!(adapter.swap(500_000, 0, &mint, &slice, None).is_err());
+        assert!(adapter.get_quote(400_000, &mint, &slice).is_err());
+
+        let with_fee = payload(false, 400_000, 39_000, 20);
+        assert!(JupiterAdapter { instruction_data: &with_fee }.validate_accounts(&mint, &slice).is_err());
+        assert!(JupiterAdapter { instruction_data: &[] }.validate_accounts(&mint, &slice).is_err());
+
+        // The transfer authority must be the source account's owner
+        let mut accounts = swap_slice(false, mint, authority);
+        accounts[JupiterRouteArgs::parse(&data).unwrap().layout().authority] = unused_account();
+        assert!(adapter.validate_accounts(&mint, &infos(&mut accounts)).is_err());
+
+        let mut accounts = swap_slice(false, mint, authority);
+        accounts[PROGRAM_INDEX] = unused_account();
+        assert!(adapter.validate_accounts(&mint, &infos(&mut accounts)).is_err());
+    }
+}
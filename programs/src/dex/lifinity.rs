@@ -0,0 +1,508 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use crate::dex::math::mul_div;
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
+use crate::state::DexType;
+
+/// Lifinity v2 Program ID
+pub const LIFINITY_V2_PROGRAM_ID: Pubkey = solana_program::pubkey!("2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEUmtrVgW4cY8z");
+
+/// Lifinity swap instruction discriminator
+const SWAP_INSTRUCTION: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
+
+// Positions in the swap account slice (see `LifinityAdapter::swap`)
+const AMM_INDEX: usize = 1;
+const DESTINATION_INDEX: usize = 4;
+const SWAP_SOURCE_INDEX: usize = 5;
+const SWAP_DESTINATION_INDEX: usize = 6;
+const POOL_MINT_INDEX: usize = 7;
+const FEE_ACCOUNT_INDEX: usize = 8;
+const ORACLE_INDICES: [usize; 3] = [10, 11, 12];
+/// Accounts passed to the Lifinity `swap` instruction itself
+const SWAP_ACCOUNTS_LEN: usize = 13;
+
+/// Size of the v2 `Amm` account
+pub const AMM_LEN: usize = 912;
+
+// `Amm` field offsets (after the 8 byte Anchor discriminator)
+const IS_INITIALIZED_OFFSET: usize = 120;
+const FREEZE_TRADE_OFFSET: usize = 122;
+const TOKEN_A_ACCOUNT_OFFSET: usize = 158;
+const TOKEN_B_ACCOUNT_OFFSET: usize = 190;
+const POOL_MINT_OFFSET: usize = 222;
+const TOKEN_A_MINT_OFFSET: usize = 254;
+const TOKEN_B_MINT_OFFSET: usize = 286;
+const FEE_ACCOUNT_OFFSET: usize = 318;
+const ORACLE_MAIN_OFFSET: usize = 350;
+const ORACLE_SUB_OFFSET: usize = 382;
+const ORACLE_PC_OFFSET: usize = 414;
+const TRADE_FEE_NUMERATOR_OFFSET: usize = 446;
+const TRADE_FEE_DENOMINATOR_OFFSET: usize = 454;
+const OWNER_TRADE_FEE_NUMERATOR_OFFSET: usize = 462;
+const OWNER_TRADE_FEE_DENOMINATOR_OFFSET: usize = 470;
+const CURVE_PARAMETERS_OFFSET: usize = 511;
+const LAST_PRICE_OFFSET: usize = 519;
+const CONFIG_DENOMINATOR_OFFSET: usize = 535;
+
+/// The parts of a Lifinity v2 `Amm` needed to validate and quote a swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifinityPoolInfo {
+    pub is_initialized: bool,
+    pub freeze_trade: u8,
+    pub token_a_account: Pubkey,
+    pub token_b_account: Pubkey,
+    pub pool_mint: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub fee_account: Pubkey,
+    pub oracle_main_account: Pubkey,
+    pub oracle_sub_account: Pubkey,
+    pub oracle_pc_account: Pubkey,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub owner_trade_fee_numerator: u64,
+    pub owner_trade_fee_denominator: u64,
+    /// Concentration: how many times the real reserves the curve behaves like
+    pub curve_parameters: u64,
+    /// Oracle price stored at the last trade, token B atoms per token A atom
+    /// scaled by `config_denominator`
+    pub last_price: u64,
+    pub config_denominator: u64,
+}
+
+impl LifinityPoolInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, LIFINITY_V2_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(data.len() == AMM_LEN, ErrorCode::InvalidPoolAccount);
+        let pool = Self {
+            is_initialized: data[IS_INITIALIZED_OFFSET] != 0,
+            freeze_trade: data[FREEZE_TRADE_OFFSET],
+            token_a_account: read_pubkey(data, TOKEN_A_ACCOUNT_OFFSET),
+            token_b_account: read_pubkey(data, TOKEN_B_ACCOUNT_OFFSET),
+            pool_mint: read_pubkey(data, POOL_MINT_OFFSET),
+            token_a_mint: read_pubkey(data, TOKEN_A_MINT_OFFSET),
+            token_b_mint: read_pubkey(data, TOKEN_B_MINT_OFFSET),
+            fee_account: read_pubkey(data, FEE_ACCOUNT_OFFSET),
+            oracle_main_account: read_pubkey(data, ORACLE_MAIN_OFFSET),
+            oracle_sub_account: read_pubkey(data, ORACLE_SUB_OFFSET),
+            oracle_pc_account: read_pubkey(data, ORACLE_PC_OFFSET),
+            trade_fee_numerator: read_u64(data, TRADE_FEE_NUMERATOR_OFFSET),
+            trade_fee_denominator: read_u64(data, TRADE_FEE_DENOMINATOR_OFFSET),
+            owner_trade_fee_numerator: read_u64(data, OWNER_TRADE_FEE_NUMERATOR_OFFSET),
+            owner_trade_fee_denominator: read_u64(data, OWNER_TRADE_FEE_DENOMINATOR_OFFSET),
+            curve_parameters: read_u64(data, CURVE_PARAMETERS_OFFSET),
+            last_price: read_u64(data, LAST_PRICE_OFFSET),
+            config_denominator: read_u64(data, CONFIG_DENOMINATOR_OFFSET),
+        };
+        require!(pool.is_initialized, ErrorCode::InvalidPoolAccount);
+        require!(
+            pool.curve_parameters > 0 && pool.last_price > 0 && pool.config_denominator > 0,
+            ErrorCode::InvalidPoolAccount
+        );
+        Ok(pool)
+    }
+
+    /// Direction for selling `source_mint`
+    pub fn a_to_b(&self, source_mint: &Pubkey) -> Result<bool> {
+        if *source_mint == self.token_a_mint {
+            Ok(true)
+        } else if *source_mint == self.token_b_mint {
+            Ok(false)
+        } else {
+            Err(ErrorCode::MintMismatch.into())
+        }
+    }
+
+    /// Trade fee plus owner fee on `amount_in`, each rounded up
+    pub fn fee(&self, amount_in: u64) -> Result<u64> {
+        let part = |numerator: u64, denominator: u64| -> Result<u64> {
+            if numerator == 0 {
+                return Ok(0);
+            }
+            require!(denominator > 0 && numerator < denominator, ErrorCode::InvalidPoolAccount);
+            Ok((amount_in as u128 * numerator as u128).div_ceil(denominator as u128) as u64)
+        };
+        Ok(part(self.trade_fee_numerator, self.trade_fee_denominator)?
+            + part(self.owner_trade_fee_numerator, self.owner_trade_fee_denominator)?)
+    }
+
+    /// Fee in basis points, for reporting
+    pub fn fee_bps(&self) -> u16 {
+        let bps = |numerator: u64, denominator: u64| {
+            if denominator == 0 { 0 } else { numerator as u128 * 10000 / denominator as u128 }
+        };
+        (bps(self.trade_fee_numerator, self.trade_fee_denominator)
+            + bps(self.owner_trade_fee_numerator, self.owner_trade_fee_denominator))
+            .min(10000) as u16
+    }
+
+    /// Check the swap slice against the accounts the pool records
+    pub fn validate_accounts(&self, accounts: &[AccountInfo]) -> Result<()> {
+        let vaults = [self.token_a_account, self.token_b_account];
+        require!(
+            vaults.contains(accounts[SWAP_SOURCE_INDEX].key)
+                && vaults.contains(accounts[SWAP_DESTINATION_INDEX].key)
+                && accounts[SWAP_SOURCE_INDEX].key != accounts[SWAP_DESTINATION_INDEX].key,
+            ErrorCode::InvalidPoolAccount
+        );
+        require_keys_eq!(*accounts[POOL_MINT_INDEX].key, self.pool_mint, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[FEE_ACCOUNT_INDEX].key, self.fee_account, ErrorCode::InvalidPoolAccount);
+        let oracles = [self.oracle_main_account, self.oracle_sub_account, self.oracle_pc_account];
+        for (index, oracle) in ORACLE_INDICES.iter().zip(oracles) {
+            require_keys_eq!(*accounts[*index].key, oracle, ErrorCode::InvalidOracleAccount);
+        }
+        Ok(())
+    }
+}
+
+/// A quoted Lifinity trade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifinityQuote {
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+/// Exact-input quote on an oracle-anchored concentrated curve.
+///
+/// Lifinity pivots a constant product curve on the oracle price and scales it
+/// by the concentration. The output side's reserve is amplified by
+/// `curve_parameters` and the input side is sized so the curve's spot price
+/// equals the stored oracle price; the trade then moves along that curve.
+pub fn quote_lifinity(
+    pool: &LifinityPoolInfo,
+    reserve_a: u64,
+    reserve_b: u64,
+    amount_in: u64,
+    a_to_b: bool,
+) -> Result<LifinityQuote> {
+    require!(amount_in > 0, PyroSwapError::InvalidAmount);
+    require!(pool.freeze_trade == 0, ErrorCode::TradingFrozen);
+
+    let fee = pool.fee(amount_in)?;
+    let net = (amount_in - fee) as u128;
+    let concentration = pool.curve_parameters as u128;
+    let (price, denominator) = (pool.last_price as u128, pool.config_denominator as u128);
+
+    let (reserve_out, virtual_out, virtual_in) = if a_to_b {
+        let virtual_b = concentration * reserve_b as u128;
+        (reserve_b, virtual_b, mul_div(virtual_b, denominator, price, false))
+    } else {
+        let virtual_a = concentration * reserve_a as u128;
+        (reserve_a, virtual_a, mul_div(virtual_a, price, denominator, false))
+    };
+    let virtual_in = virtual_in.ok_or(PyroSwapError::MathOverflow)?;
+
+    let amount_out = mul_div(virtual_out, net, virtual_in + net, false).ok_or(PyroSwapError::MathOverflow)?;
+    require!(amount_out < reserve_out as u128, PyroSwapError::InsufficientLiquidity);
+
+    Ok(LifinityQuote { amount_out: amount_out as u64, fee })
+}
+
+pub struct LifinityAdapter;
+
+impl DexAdapter for LifinityAdapter {
+    fn dex_type(&self) -> DexType {
+        DexType::Lifinity
+    }
+
+    fn program_id(&self) -> Pubkey {
+        LIFINITY_V2_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, SWAP_ACCOUNTS_LEN, &LIFINITY_V2_PROGRAM_ID)?;
+
+        let pool = LifinityPoolInfo::try_from_account(&accounts[AMM_INDEX])?;
+        pool.validate_accounts(accounts)?;
+
+        // The source vault must be the side being sold
+        let source_vault = if pool.a_to_b(source_mint)? { pool.token_a_account } else { pool.token_b_account };
+        require_keys_eq!(*accounts[SWAP_SOURCE_INDEX].key, source_vault, ErrorCode::MintMismatch);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
+        amount_in: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<DexQuote> {
+        self.validate_accounts(source_mint, accounts)?;
+
+        let pool = LifinityPoolInfo::try_from_account(&accounts[AMM_INDEX])?;
+        let a_to_b = pool.a_to_b(source_mint)?;
+        let (reserve_a, reserve_b) = read_reserves(&pool, accounts)?;
+        let quote = quote_lifinity(&pool, reserve_a, reserve_b, amount_in, a_to_b)?;
+
+        // Output at the oracle price after fees, for price impact
+        let net = (amount_in - quote.fee) as u128;
+        let spot_out = if a_to_b {
+            mul_div(net, pool.last_price as u128, pool.config_denominator as u128, false)
+        } else {
+            mul_div(net, pool.config_denominator as u128, pool.last_price as u128, false)
+        }
+        .map_or(u64::MAX, |out| out.min(u64::MAX as u128) as u64);
+
+        Ok(DexQuote {
+            dex_type: DexType::Lifinity,
+            amount_out: quote.amount_out,
+            price_impact_bps: calculate_price_impact(amount_in, quote.amount_out, spot_out),
+            fee_bps: pool.fee_bps(),
+        })
+    }
+
+    fn swap<'info>(
+        &self,
+        amount_in: u64,
+        min_amount_out: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        signer_seeds: Option<&[&[&[u8]]]>,
+    ) -> Result<u64> {
+        // Lifinity v2 swap CPI
+        // accounts expected:
+        // 0. AMM authority
+        // 1. AMM
+        // 2. User transfer authority
+        // 3. User source token account
+        // 4. User destination token account
+        // 5. Pool source vault
+        // 6. Pool destination vault
+        // 7. Pool mint
+        // 8. Fee account
+        // 9. Token program
+        // 10. Oracle main account
+        // 11. Oracle sub account
+        // 12. Oracle pc account
+        // 13. Lifinity program
+
+        self.validate_accounts(source_mint, accounts)?;
+
+        let pool = LifinityPoolInfo::try_from_account(&accounts[AMM_INDEX])?;
+        require!(pool.freeze_trade == 0, ErrorCode::TradingFrozen);
+        let (_, destination_before) = read_token_account(&accounts[DESTINATION_INDEX])?;
+
+        let mut instruction_data = Vec::with_capacity(24);
+        instruction_data.extend_from_slice(&SWAP_INSTRUCTION);
+        instruction_data.extend_from_slice(&amount_in.to_le_bytes());
+        instruction_data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+        let instruction = anchor_lang::solana_program::instruction::Instruction {
+            program_id: LIFINITY_V2_PROGRAM_ID,
+            accounts: accounts[..SWAP_ACCOUNTS_LEN].iter().map(|a| {
+                anchor_lang::solana_program::instruction::AccountMeta {
+                    pubkey: *a.key,
+                    is_signer: a.is_signer,
+                    is_writable: a.is_writable,
+                }
+            }).collect(),
+            data: instruction_data,
+        };
+
+        if let Some(seeds) = signer_seeds {
+            anchor_lang::solana_program::program::invoke_signed(&instruction, accounts, seeds)?;
+        } else {
+            invoke(&instruction, accounts)?;
+        }
+
+        let (_, destination_after) = read_token_account(&accounts[DESTINATION_INDEX])?;
+        Ok(destination_after.saturating_sub(destination_before))
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        _source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[DESTINATION_INDEX])
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[AMM_INDEX])
+    }
+}
+
+/// Pool vault balances as `(token_a, token_b)`
+fn read_reserves(pool: &LifinityPoolInfo, accounts: &[AccountInfo]) -> Result<(u64, u64)> {
+    let source = &accounts[SWAP_SOURCE_INDEX];
+    let destination = &accounts[SWAP_DESTINATION_INDEX];
+    let (vault_a, vault_b) = if *source.key == pool.token_a_account { (source, destination) } else { (destination, source) };
+    let (mint_a, reserve_a) = read_token_account(vault_a)?;
+    let (mint_b, reserve_b) = read_token_account(vault_b)?;
+    require_keys_eq!(mint_a, pool.token_a_mint, ErrorCode::InvalidPoolAccount);
+    require_keys_eq!(mint_b, pool.token_b_mint, ErrorCode::InvalidPoolAccount);
+    Ok((reserve_a, reserve_b))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid number of accounts provided")]
+    InvalidAccountsLength,
+    #[msg("Account does not match the Lifinity pool")]
+    InvalidPoolAccount,
+    #[msg("Oracle account does not match the Lifinity pool")]
+    InvalidOracleAccount,
+    #[msg("Lifinity pool has trading frozen")]
+    TradingFrozen,
+    #[msg("Source mint is not traded by this pool")]
+    MintMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+
+    /// A SOL/USDC pool: 1,000 SOL and 150,000 USDC, priced at 150 with 10x
+    /// concentration and 0.15% + 0.05% fees
+    fn amm_data(pool: &LifinityPoolInfo) -> Vec<u8> {
+        let mut data = vec![0u8; AMM_LEN];
+        data[IS_INITIALIZED_OFFSET] = pool.is_initialized as u8;
+        data[FREEZE_TRADE_OFFSET] = pool.freeze_trade;
+        write_pubkey(&mut data, TOKEN_A_ACCOUNT_OFFSET, &pool.token_a_account);
+        write_pubkey(&mut data, TOKEN_B_ACCOUNT_OFFSET, &pool.token_b_account);
+        write_pubkey(&mut data, POOL_MINT_OFFSET, &pool.pool_mint);
+        write_pubkey(&mut data, TOKEN_A_MINT_OFFSET, &pool.token_a_mint);
+        write_pubkey(&mut data, TOKEN_B_MINT_OFFSET, &pool.token_b_mint);
+        write_pubkey(&mut data, FEE_ACCOUNT_OFFSET, &pool.fee_account);
+        write_pubkey(&mut data, ORACLE_MAIN_OFFSET, &pool.oracle_main_account);
+        write_pubkey(&mut data, ORACLE_SUB_OFFSET, &pool.oracle_sub_account);
+        write_pubkey(&mut data, ORACLE_PC_OFFSET, &pool.oracle_pc_account);
+        write_u64(&mut data, TRADE_FEE_NUMERATOR_OFFSET, pool.trade_fee_numerator);
+        write_u64(&mut data, TRADE_FEE_DENOMINATOR_OFFSET, pool.trade_fee_denominator);
+        write_u64(&mut data, OWNER_TRADE_FEE_NUMERATOR_OFFSET, pool.owner_trade_fee_numerator);
+        write_u64(&mut data, OWNER_TRADE_FEE_DENOMINATOR_OFFSET, pool.owner_trade_fee_denominator);
+        write_u64(&mut data, CURVE_PARAMETERS_OFFSET, pool.curve_parameters);
+        write_u64(&mut data, LAST_PRICE_OFFSET, pool.last_price);
+        write_u64(&mut data, CONFIG_DENOMINATOR_OFFSET, pool.config_denominator);
+        data
+    }
+
+    fn pool_info() -> LifinityPoolInfo {
+        LifinityPoolInfo {
+            is_initialized: true,
+            freeze_trade: 0,
+            token_a_account: Pubkey::new_unique(),
+            token_b_account: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            fee_account: Pubkey::new_unique(),
+            oracle_main_account: Pubkey::new_unique(),
+            oracle_sub_account: Pubkey::new_unique(),
+            oracle_pc_account: Pubkey::new_unique(),
+            trade_fee_numerator: 15,
+            trade_fee_denominator: 10000,
+            owner_trade_fee_numerator: 5,
+            owner_trade_fee_denominator: 10000,
+            curve_parameters: 10,
+            last_price: 150_000_000,
+            config_denominator: 1_000_000_000,
+        }
+    }
+
+    const RESERVE_A: u64 = 1_000_000_000_000;
+    const RESERVE_B: u64 = 150_000_000_000;
+
+    /// The swap slice selling `a_to_b`'s source side
+    fn pool_accounts(pool: &LifinityPoolInfo, a_to_b: bool) -> Vec<FixtureAccount> {
+        let amm = Pubkey::new_unique();
+        let vault_a = token_account(pool.token_a_account, pool.token_a_mint, RESERVE_A);
+        let vault_b = token_account(pool.token_b_account, pool.token_b_mint, RESERVE_B);
+        let (source, destination) = if a_to_b { (vault_a, vault_b) } else { (vault_b, vault_a) };
+
+        let mut accounts: Vec<FixtureAccount> = (0..SWAP_ACCOUNTS_LEN + 1).map(|_| unused_account()).collect();
+        accounts[AMM_INDEX] = FixtureAccount::new(amm, LIFINITY_V2_PROGRAM_ID, amm_data(pool));
+        accounts[SWAP_SOURCE_INDEX] = source;
+        accounts[SWAP_DESTINATION_INDEX] = destination;
+        accounts[POOL_MINT_INDEX] = FixtureAccount::new(pool.pool_mint, anchor_spl::token::ID, vec![]);
+        accounts[FEE_ACCOUNT_INDEX] = FixtureAccount::new(pool.fee_account, anchor_spl::token::ID, vec![]);
+        let oracles = [pool.oracle_main_account, pool.oracle_sub_account, pool.oracle_pc_account];
+        for (index, oracle) in ORACLE_INDICES.iter().zip(oracles) {
+            accounts[*index] = FixtureAccount::new(oracle, Pubkey::new_unique(), vec![]);
+        }
+        accounts[SWAP_ACCOUNTS_LEN] = FixtureAccount::new(LIFINITY_V2_PROGRAM_ID, Pubkey::default(), vec![]);
+        accounts
+    }
+
+    #[test]
+    fn parses_amm_account() {
+        let pool = pool_info();
+        assert_eq!(LifinityPoolInfo::try_from_slice(&amm_data(&pool)).unwrap(), pool);
+        assert_eq!(pool.fee(10_000_000_000).unwrap(), 20_000_000);
+        assert_eq!(pool.fee_bps(), 20);
+
+        assert!(LifinityPoolInfo::try_from_slice(&amm_data(&pool)[..AMM_LEN - 1]).is_err());
+        let uninitialized = LifinityPoolInfo { is_initialized: false, ..pool };
+        assert!(LifinityPoolInfo::try_from_slice(&amm_data(&uninitialized)).is_err());
+    }
+
+    #[test]
+    fn quotes_around_the_oracle_price() {
+        let pool = pool_info();
+        // 10 SOL at 150 is 1,500 USDC before fees and impact
+        let sell = quote_lifinity(&pool, RESERVE_A, RESERVE_B, 10_000_000_000, true).unwrap();
+        assert_eq!(sell, LifinityQuote { amount_out: 1_495_507_483, fee: 20_000_000 });
+
+        let buy = quote_lifinity(&pool, RESERVE_A, RESERVE_B, 3_000_000_000, false).unwrap();
+        assert_eq!(buy, LifinityQuote { amount_out: 19_920_239_202, fee: 6_000_000 });
+
+        // Concentration deepens the curve but never beyond the real reserve
+        let large = quote_lifinity(&pool, RESERVE_A, RESERVE_B, 1_000_000_000_000, true).unwrap();
+        assert_eq!(large.amount_out, 136_115_657_392);
+        assert!(quote_lifinity(&pool, RESERVE_A, RESERVE_B, 10_000_000_000_000, true).is_err());
+
+        let frozen = LifinityPoolInfo { freeze_trade: 1, ..pool };
+        assert!(quote_lifinity(&frozen, RESERVE_A, RESERVE_B, 10_000_000_000, true).is_err());
+    }
+
+    #[test]
+    fn adapter_quotes_from_the_vaults_in_either_order() {
+        let pool = pool_info();
+        let mut accounts = pool_accounts(&pool, true);
+        let quote = LifinityAdapter.get_quote(10_000_000_000, &pool.token_a_mint, &infos(&mut accounts)).unwrap();
+        assert_eq!(quote.amount_out, 1_495_507_483);
+        assert_eq!(quote.fee_bps, 20);
+
+        let mut accounts = pool_accounts(&pool, false);
+        let quote = LifinityAdapter.get_quote(3_000_000_000, &pool.token_b_mint, &infos(&mut accounts)).unwrap();
+        assert_eq!(quote.amount_out, 19_920_239_202);
+
+        assert!(LifinityAdapter.get_quote(1_000, &Pubkey::new_unique(), &infos(&mut accounts)).is_err());
+    }
+
+    #[test]
+    fn rejects_accounts_the_pool_does_not_record() {
+        let pool = pool_info();
+        for index in [POOL_MINT_INDEX, FEE_ACCOUNT_INDEX, ORACLE_INDICES[1], SWAP_DESTINATION_INDEX] {
+            let mut accounts = pool_accounts(&pool, true);
+            accounts[index] = unused_account();
+            assert!(LifinityAdapter.get_quote(10_000_000_000, &pool.token_a_mint, &infos(&mut accounts)).is_err());
+        }
+
+        let mut accounts = pool_accounts(&pool, true);
+        accounts[SWAP_ACCOUNTS_LEN] = unused_account();
+        assert!(LifinityAdapter.get_quote(10_000_000_000, &pool.token_a_mint, &infos(&mut accounts)).is_err());
+
+        let mut accounts = pool_accounts(&pool, true);
+        accounts[AMM_INDEX].owner = Pubkey::new_unique();
+        assert!(LifinityAdapter.get_quote(10_000_000_000, &pool.token_a_mint, &infos(&mut accounts)).is_err());
+    }
+}
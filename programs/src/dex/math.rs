@@ -0,0 +1,220 @@
+//! Fixed-point helpers shared by the concentrated liquidity adapters.
+//!
+//! Q64.64 price math multiplies two u128 values, so intermediate results
+//! need 256 bits. Only the handful of operations the swap math uses are
+//! implemented here.
+
+use std::cmp::Ordering;
+
+const LOW_64: u128 = u64::MAX as u128;
+
+/// Unsigned 256-bit integer stored as two u128 halves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct U256 {
+    hi: u128,
+    lo: u128,
+}
+
+impl U256 {
+    pub const ZERO: U256 = U256 { hi: 0, lo: 0 };
+    pub const ONE: U256 = U256 { hi: 0, lo: 1 };
+
+    pub const fn from_u128(value: u128) -> Self {
+        Self { hi: 0, lo: value }
+    }
+
+    /// Full 256-bit product of two u128 values
+    pub fn mul_u128(a: u128, b: u128) -> Self {
+        let (a1, a0) = (a >> 64, a & LOW_64);
+        let (b1, b0) = (b >> 64, b & LOW_64);
+
+        let p00 = a0 * b0;
+        let p01 = a0 * b1;
+        let p10 = a1 * b0;
+        let p11 = a1 * b1;
+
+        // Sum of three values below 2^64 each, so this cannot overflow
+        let mid = (p00 >> 64) + (p01 & LOW_64) + (p10 & LOW_64);
+        Self {
+            hi: p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64),
+            lo: (p00 & LOW_64) | (mid << 64),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.hi == 0 && self.lo == 0
+    }
+
+    /// The low 128 bits, or `None` if the value does not fit
+    pub fn to_u128(self) -> Option<u128> {
+        if self.hi == 0 { Some(self.lo) } else { None }
+    }
+
+    /// The low 128 bits, discarding the rest
+    pub fn low_u128(self) -> u128 {
+        self.lo
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (lo, carry) = self.lo.overflowing_add(other.lo);
+        let hi = self.hi.checked_add(other.hi)?.checked_add(carry as u128)?;
+        Some(Self { hi, lo })
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        if self < other {
+            return None;
+        }
+        Some(self.wrapping_sub(other))
+    }
+
+    fn wrapping_sub(self, other: Self) -> Self {
+        let (lo, borrow) = self.lo.overflowing_sub(other.lo);
+        let hi = self.hi.wrapping_sub(other.hi).wrapping_sub(borrow as u128);
+        Self { hi, lo }
+    }
+
+    /// Shift left by 64 bits, or `None` if any set bit would be lost
+    pub fn checked_shl_64(self) -> Option<Self> {
+        if self.hi >> 64 != 0 {
+            return None;
+        }
+        Some(Self {
+            hi: (self.hi << 64) | (self.lo >> 64),
+            lo: self.lo << 64,
+        })
+    }
+
+    /// Shift right by `bits` (< 256)
+    pub fn shift_right(self, bits: u32) -> Self {
+        match bits {
+            0 => self,
+            1..=127 => Self {
+                hi: self.hi >> bits,
+                lo: (self.lo >> bits) | (self.hi << (128 - bits)),
+            },
+            _ => Self { hi: 0, lo: self.hi >> (bits - 128) },
+        }
+    }
+
+    fn bits(&self) -> u32 {
+        if self.hi != 0 {
+            256 - self.hi.leading_zeros()
+        } else {
+            128 - self.lo.leading_zeros()
+        }
+    }
+
+    fn bit(&self, index: u32) -> bool {
+        if index >= 128 {
+            (self.hi >> (index - 128)) & 1 == 1
+        } else {
+            (self.lo >> index) & 1 == 1
+        }
+    }
+
+    fn set_bit(&mut self, index: u32) {
+        if index >= 128 {
+            self.hi |= 1 << (index - 128);
+        } else {
+            self.lo |= 1 << index;
+        }
+    }
+
+    /// Quotient and remainder, or `None` when dividing by zero
+    pub fn checked_div_rem(self, divisor: Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        if self.hi == 0 && divisor.hi == 0 {
+            return Some((Self::from_u128(self.lo / divisor.lo), Self::from_u128(self.lo % divisor.lo)));
+        }
+
+        // Schoolbook binary long division
+        let mut quotient = Self::ZERO;
+        let mut remainder = Self::ZERO;
+        for i in (0..self.bits()).rev() {
+            let carry = remainder.hi >> 127 == 1;
+            remainder = Self {
+                hi: (remainder.hi << 1) | (remainder.lo >> 127),
+                lo: (remainder.lo << 1) | self.bit(i) as u128,
+            };
+            if carry || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                quotient.set_bit(i);
+            }
+        }
+        Some((quotient, remainder))
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hi.cmp(&other.hi).then(self.lo.cmp(&other.lo))
+    }
+}
+
+/// `a * b / denominator`, rounded down or up, if the result fits in a u128
+pub fn mul_div(a: u128, b: u128, denominator: u128, round_up: bool) -> Option<u128> {
+    let (quotient, remainder) = U256::mul_u128(a, b).checked_div_rem(U256::from_u128(denominator))?;
+    let quotient = quotient.to_u128()?;
+    if round_up && !remainder.is_zero() {
+        quotient.checked_add(1)
+    } else {
+        Some(quotient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplies_full_width() {
+        let product = U256::mul_u128(u128::MAX, u128::MAX);
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1
+        assert_eq!(product, U256 { hi: u128::MAX - 1, lo: 1 });
+        assert_eq!(U256::mul_u128(1 << 64, 1 << 64), U256 { hi: 1, lo: 0 });
+        assert_eq!(U256::mul_u128(12345, 678), U256::from_u128(12345 * 678));
+    }
+
+    #[test]
+    fn divides_wide_values() {
+        let a = 0xdead_beef_0123_4567_89ab_cdef_1234_5678u128;
+        let b = 0x0fed_cba9_8765_4321_0f0f_0f0f_f0f0_f0f0u128;
+        let (q, r) = U256::mul_u128(a, b).checked_div_rem(U256::from_u128(b)).unwrap();
+        assert_eq!(q, U256::from_u128(a));
+        assert!(r.is_zero());
+
+        let (q, r) = U256::mul_u128(a, b)
+            .checked_add(U256::from_u128(7))
+            .unwrap()
+            .checked_div_rem(U256::from_u128(a))
+            .unwrap();
+        assert_eq!(q, U256::from_u128(b));
+        assert_eq!(r, U256::from_u128(7));
+
+        // Divisor with the top bit set exercises the shifted-out carry
+        let top = U256 { hi: 1 << 127, lo: 0 };
+        let (q, r) = U256 { hi: u128::MAX, lo: u128::MAX }.checked_div_rem(top).unwrap();
+        assert_eq!(q, U256::ONE);
+        assert_eq!(r, U256 { hi: u128::MAX >> 1, lo: u128::MAX });
+
+        assert!(U256::ONE.checked_div_rem(U256::ZERO).is_none());
+    }
+
+    #[test]
+    fn shifts_and_rounds() {
+        assert_eq!(U256::from_u128(1 << 100).checked_shl_64().unwrap().shift_right(164), U256::ONE);
+        assert!(U256 { hi: 1 << 64, lo: 0 }.checked_shl_64().is_none());
+        assert_eq!(mul_div(10, 10, 3, false), Some(33));
+        assert_eq!(mul_div(10, 10, 3, true), Some(34));
+        assert_eq!(mul_div(u128::MAX, 2, 1, false), None);
+    }
+}
@@ -1,46 +1,358 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke;
-use crate::dex::{DexQuote, DexAdapter};
+use crate::dex::math::mul_div;
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
 use crate::state::DexType;
 
 /// Meteora DLMM Program ID
 pub const METEORA_DLMM_PROGRAM_ID: Pubkey = solana_program::pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
 
-/// Meteora swap instruction discriminator
-const SWAP_INSTRUCTION: [u8; 8] = [0x14, 0x8e, 0x41, 0x5c, 0x7b, 0x8c, 0x3a, 0x9f];
+/// Meteora swap instruction discriminator (`global:swap`)
+const SWAP_INSTRUCTION: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
+
+// Positions in the swap account slice (see `MeteoraAdapter::swap`)
+const LB_PAIR_INDEX: usize = 0;
+const USER_TOKEN_IN_INDEX: usize = 4;
+const USER_TOKEN_OUT_INDEX: usize = 5;
+const TOKEN_X_MINT_INDEX: usize = 6;
+const TOKEN_Y_MINT_INDEX: usize = 7;
+/// DLMM's event CPI takes its own program as an instruction account
+const PROGRAM_INDEX: usize = 14;
+/// Bin arrays follow the 15 accounts of the `swap` instruction
+const BIN_ARRAYS_START: usize = 15;
+
+/// Size of the `LbPair` account
+pub const LB_PAIR_LEN: usize = 904;
+
+// `LbPair` field offsets (after the 8 byte Anchor discriminator):
+// `StaticParameters`, `VariableParameters`, then pair state
+const BASE_FACTOR_OFFSET: usize = 8;
+const FILTER_PERIOD_OFFSET: usize = 10;
+const DECAY_PERIOD_OFFSET: usize = 12;
+const REDUCTION_FACTOR_OFFSET: usize = 14;
+const VARIABLE_FEE_CONTROL_OFFSET: usize = 16;
+const MAX_VOLATILITY_ACCUMULATOR_OFFSET: usize = 20;
+const MIN_BIN_ID_OFFSET: usize = 24;
+const MAX_BIN_ID_OFFSET: usize = 28;
+const BASE_FEE_POWER_FACTOR_OFFSET: usize = 34;
+const VOLATILITY_ACCUMULATOR_OFFSET: usize = 40;
+const VOLATILITY_REFERENCE_OFFSET: usize = 44;
+const INDEX_REFERENCE_OFFSET: usize = 48;
+const LAST_UPDATE_TIMESTAMP_OFFSET: usize = 56;
+const ACTIVE_ID_OFFSET: usize = 76;
+const BIN_STEP_OFFSET: usize = 80;
+const STATUS_OFFSET: usize = 82;
+const TOKEN_X_MINT_OFFSET: usize = 88;
+const TOKEN_Y_MINT_OFFSET: usize = 120;
+
+/// Size of a `BinArray` account
+pub const BIN_ARRAY_LEN: usize = 10136;
+/// Bins stored per bin array
+pub const MAX_BIN_PER_ARRAY: i32 = 70;
+
+// `BinArray` layout: index, version, padding, owning pair, then 70 bins of
+// `amount_x`, `amount_y`, `price` followed by fee and reward bookkeeping
+const BIN_ARRAY_INDEX_OFFSET: usize = 8;
+const BIN_ARRAY_LB_PAIR_OFFSET: usize = 24;
+const BINS_OFFSET: usize = 56;
+const BIN_LEN: usize = 144;
+
+/// DLMM fee rates carry 9 decimals
+const FEE_PRECISION: u128 = 1_000_000_000;
+/// Fees are capped at 10%
+const MAX_FEE_RATE: u128 = 100_000_000;
+const BASIS_POINT_MAX: u64 = 10_000;
+/// `pair_status` of a pair open for trading
+const PAIR_STATUS_ENABLED: u8 = 0;
+
+const ONE_X64: u128 = 1 << 64;
+
+/// The parts of the `LbPair` account needed to reproduce a swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LbPairInfo {
+    pub base_factor: u16,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub variable_fee_control: u32,
+    pub max_volatility_accumulator: u32,
+    pub min_bin_id: i32,
+    pub max_bin_id: i32,
+    pub base_fee_power_factor: u8,
+    pub volatility_accumulator: u32,
+    pub volatility_reference: u32,
+    pub index_reference: i32,
+    pub last_update_timestamp: i64,
+    pub active_id: i32,
+    pub bin_step: u16,
+    pub status: u8,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+}
+
+impl LbPairInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, METEORA_DLMM_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(data.len() == LB_PAIR_LEN, ErrorCode::InvalidPoolAccount);
+        Ok(Self {
+            base_factor: read_u16(data, BASE_FACTOR_OFFSET),
+            filter_period: read_u16(data, FILTER_PERIOD_OFFSET),
+            decay_period: read_u16(data, DECAY_PERIOD_OFFSET),
+            reduction_factor: read_u16(data, REDUCTION_FACTOR_OFFSET),
+            variable_fee_control: read_u32(data, VARIABLE_FEE_CONTROL_OFFSET),
+            max_volatility_accumulator: read_u32(data, MAX_VOLATILITY_ACCUMULATOR_OFFSET),
+            min_bin_id: read_u32(data, MIN_BIN_ID_OFFSET) as i32,
+            max_bin_id: read_u32(data, MAX_BIN_ID_OFFSET) as i32,
+            base_fee_power_factor: data[BASE_FEE_POWER_FACTOR_OFFSET],
+            volatility_accumulator: read_u32(data, VOLATILITY_ACCUMULATOR_OFFSET),
+            volatility_reference: read_u32(data, VOLATILITY_REFERENCE_OFFSET),
+            index_reference: read_u32(data, INDEX_REFERENCE_OFFSET) as i32,
+            last_update_timestamp: i64::from_le_bytes(
+                data[LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8].try_into().unwrap(),
+            ),
+            active_id: read_u32(data, ACTIVE_ID_OFFSET) as i32,
+            bin_step: read_u16(data, BIN_STEP_OFFSET),
+            status: data[STATUS_OFFSET],
+            token_x_mint: read_pubkey(data, TOKEN_X_MINT_OFFSET),
+            token_y_mint: read_pubkey(data, TOKEN_Y_MINT_OFFSET),
+        })
+    }
+
+    /// Selling X for Y (`swap_for_y`) or Y for X
+    pub fn swap_for_y(&self, source_mint: &Pubkey) -> Result<bool> {
+        if *source_mint == self.token_x_mint {
+            Ok(true)
+        } else if *source_mint == self.token_y_mint {
+            Ok(false)
+        } else {
+            Err(ErrorCode::MintMismatch.into())
+        }
+    }
+
+    /// Decay the volatility reference once per swap, as `update_references` does
+    fn update_references(&mut self, now: i64) {
+        let elapsed = now.saturating_sub(self.last_update_timestamp);
+        if elapsed >= self.filter_period as i64 {
+            self.index_reference = self.active_id;
+            self.volatility_reference = if elapsed < self.decay_period as i64 {
+                (self.volatility_accumulator as u64 * self.reduction_factor as u64 / BASIS_POINT_MAX) as u32
+            } else {
+                0
+            };
+        }
+    }
+
+    /// Volatility grows with every bin crossed away from the reference
+    fn update_volatility_accumulator(&mut self) {
+        let delta_id = (self.index_reference as i64 - self.active_id as i64).unsigned_abs();
+        let accumulator = self.volatility_reference as u64 + delta_id * BASIS_POINT_MAX;
+        self.volatility_accumulator = accumulator.min(self.max_volatility_accumulator as u64) as u32;
+    }
+
+    pub fn base_fee_rate(&self) -> u128 {
+        self.base_factor as u128 * self.bin_step as u128 * 10 * 10u128.pow(self.base_fee_power_factor as u32)
+    }
+
+    pub fn variable_fee_rate(&self) -> u128 {
+        if self.variable_fee_control == 0 {
+            return 0;
+        }
+        let square_vfa_bin = (self.volatility_accumulator as u128 * self.bin_step as u128).pow(2);
+        (self.variable_fee_control as u128 * square_vfa_bin).div_ceil(100_000_000_000)
+    }
+
+    /// Base plus variable fee, in `FEE_PRECISION` units
+    pub fn total_fee_rate(&self) -> u128 {
+        (self.base_fee_rate() + self.variable_fee_rate()).min(MAX_FEE_RATE)
+    }
+
+    /// Fee to add on top of a fee-exclusive amount
+    fn compute_fee(&self, amount: u64) -> Result<u64> {
+        let rate = self.total_fee_rate();
+        let fee = mul_div(amount as u128, rate, FEE_PRECISION - rate, true).ok_or(PyroSwapError::MathOverflow)?;
+        Ok(u64::try_from(fee).map_err(|_| PyroSwapError::MathOverflow)?)
+    }
+
+    /// Fee contained in a fee-inclusive amount
+    fn compute_fee_from_amount(&self, amount_with_fees: u64) -> Result<u64> {
+        let fee = mul_div(amount_with_fees as u128, self.total_fee_rate(), FEE_PRECISION, true)
+            .ok_or(PyroSwapError::MathOverflow)?;
+        Ok(fee as u64)
+    }
+
+    fn advance_active_bin(&mut self, swap_for_y: bool) -> Result<()> {
+        let next = if swap_for_y { self.active_id - 1 } else { self.active_id + 1 };
+        require!(
+            next >= self.min_bin_id && next <= self.max_bin_id,
+            PyroSwapError::InsufficientLiquidity
+        );
+        self.active_id = next;
+        Ok(())
+    }
+}
+
+/// One DLMM bin: reserves and the cached price (zero until first touched)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinInfo {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub price: u128,
+}
+
+/// A bin array reduced to what the swap loop reads
+#[derive(Debug, Clone)]
+pub struct BinArrayInfo {
+    pub key: Pubkey,
+    pub index: i64,
+    pub bins: Vec<BinInfo>,
+}
+
+impl BinArrayInfo {
+    pub fn try_from_account(account: &AccountInfo, lb_pair: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*account.owner, METEORA_DLMM_PROGRAM_ID, ErrorCode::InvalidBinArray);
+        let data = account.try_borrow_data()?;
+        require!(data.len() == BIN_ARRAY_LEN, ErrorCode::InvalidBinArray);
+        require_keys_eq!(read_pubkey(&data, BIN_ARRAY_LB_PAIR_OFFSET), *lb_pair, ErrorCode::InvalidBinArray);
+        Ok(Self::from_slice(*account.key, &data))
+    }
+
+    pub fn from_slice(key: Pubkey, data: &[u8]) -> Self {
+        let bins = (0..MAX_BIN_PER_ARRAY as usize)
+            .map(|i| {
+                let offset = BINS_OFFSET + i * BIN_LEN;
+                BinInfo {
+                    amount_x: read_u64(data, offset),
+                    amount_y: read_u64(data, offset + 8),
+                    price: u128::from_le_bytes(data[offset + 16..offset + 32].try_into().unwrap()),
+                }
+            })
+            .collect();
+        Self {
+            key,
+            index: i64::from_le_bytes(data[BIN_ARRAY_INDEX_OFFSET..BIN_ARRAY_INDEX_OFFSET + 8].try_into().unwrap()),
+            bins,
+        }
+    }
+
+    fn lower_bin_id(&self) -> i64 {
+        self.index * MAX_BIN_PER_ARRAY as i64
+    }
+
+    fn upper_bin_id(&self) -> i64 {
+        self.lower_bin_id() + MAX_BIN_PER_ARRAY as i64 - 1
+    }
+
+    fn contains(&self, bin_id: i32) -> bool {
+        (self.lower_bin_id()..=self.upper_bin_id()).contains(&(bin_id as i64))
+    }
+
+    fn bin(&self, bin_id: i32) -> BinInfo {
+        self.bins[(bin_id as i64 - self.lower_bin_id()) as usize]
+    }
+}
+
+/// Bin array holding `bin_id`, rounding towards negative infinity
+pub fn bin_id_to_bin_array_index(bin_id: i32) -> i64 {
+    (bin_id as i64).div_euclid(MAX_BIN_PER_ARRAY as i64)
+}
+
+/// PDA of the bin array at `index` for `lb_pair`
+pub fn bin_array_address(lb_pair: &Pubkey, index: i64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"bin_array", lb_pair.as_ref(), &index.to_le_bytes()],
+        &METEORA_DLMM_PROGRAM_ID,
+    ).0
+}
+
+/// Result of simulating a DLMM exact-input swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DlmmQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub end_active_id: i32,
+    /// Bin arrays the swap touches, in order: the CPI's remaining accounts
+    pub bin_arrays: Vec<Pubkey>,
+}
 
 pub struct MeteoraAdapter;
 
 impl DexAdapter for MeteoraAdapter {
-    fn get_quote(
+    fn dex_type(&self) -> DexType {
+        DexType::Meteora
+    }
+
+    fn program_id(&self) -> Pubkey {
+        METEORA_DLMM_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, PROGRAM_INDEX, &METEORA_DLMM_PROGRAM_ID)?;
+
+        let lb_pair = LbPairInfo::try_from_account(&accounts[LB_PAIR_INDEX])?;
+        require_keys_eq!(*accounts[TOKEN_X_MINT_INDEX].key, lb_pair.token_x_mint, ErrorCode::MintMismatch);
+        require_keys_eq!(*accounts[TOKEN_Y_MINT_INDEX].key, lb_pair.token_y_mint, ErrorCode::MintMismatch);
+
+        // DLMM has no direction argument: it sells whichever of X/Y the
+        // user_token_in account holds, so the token accounts must line up
+        let swap_for_y = lb_pair.swap_for_y(source_mint)?;
+        let destination_mint = if swap_for_y { lb_pair.token_y_mint } else { lb_pair.token_x_mint };
+        let (token_in_mint, _) = read_token_account(&accounts[USER_TOKEN_IN_INDEX])?;
+        let (token_out_mint, _) = read_token_account(&accounts[USER_TOKEN_OUT_INDEX])?;
+        require_keys_eq!(token_in_mint, *source_mint, ErrorCode::MintMismatch);
+        require_keys_eq!(token_out_mint, destination_mint, ErrorCode::MintMismatch);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
         amount_in: u64,
-        _input_decimals: u8,
-        _output_decimals: u8,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
     ) -> Result<DexQuote> {
-        // Meteora DLMM (Dynamic Liquidity Market Maker) has dynamic fees
-        // Base fee is typically 0.1-0.3%
-        let fee_bps = 20u16; // 0.2% base fee
-        
-        let amount_after_fee = amount_in
-            .checked_mul(10000 - fee_bps as u64)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap();
-        
-        // DLMM provides better prices due to concentrated liquidity
-        let amount_out = amount_after_fee;
-        
+        self.validate_accounts(source_mint, accounts)?;
+        require!(accounts.len() > BIN_ARRAYS_START, ErrorCode::InvalidAccountsLength);
+
+        let lb_pair_account = &accounts[LB_PAIR_INDEX];
+        let lb_pair = LbPairInfo::try_from_account(lb_pair_account)?;
+        let bin_arrays = accounts[BIN_ARRAYS_START..]
+            .iter()
+            .map(|a| BinArrayInfo::try_from_account(a, lb_pair_account.key))
+            .collect::<Result<Vec<_>>>()?;
+
+        let swap_for_y = lb_pair.swap_for_y(source_mint)?;
+        let now = Clock::get()?.unix_timestamp;
+        let quote = quote_dlmm(&lb_pair, &bin_arrays, amount_in, swap_for_y, now)?;
+
+        // Output at the active bin's price after the current fee, for price impact
+        let mut start = lb_pair.clone();
+        start.update_references(now);
+        start.update_volatility_accumulator();
+        let fee_rate = start.total_fee_rate();
+        let amount_after_fee = amount_in - start.compute_fee_from_amount(amount_in)?;
+        let spot_out = bin_amount_out(amount_after_fee, price_from_id(lb_pair.active_id, lb_pair.bin_step)?, swap_for_y)
+            .unwrap_or(u64::MAX);
+
         Ok(DexQuote {
             dex_type: DexType::Meteora,
-            amount_out,
-            price_impact_bps: 5, // 0.05% estimated impact (better than AMM)
-            fee_bps,
+            amount_out: quote.amount_out,
+            price_impact_bps: calculate_price_impact(amount_in, quote.amount_out, spot_out),
+            fee_bps: (fee_rate * BASIS_POINT_MAX as u128 / FEE_PRECISION) as u16,
         })
     }
 
     fn swap<'info>(
+        &self,
         amount_in: u64,
         min_amount_out: u64,
+        source_mint: &Pubkey,
         accounts: &[AccountInfo<'info>],
         signer_seeds: Option<&[&[&[u8]]]>,
     ) -> Result<u64> {
@@ -61,11 +373,13 @@ impl DexAdapter for MeteoraAdapter {
         // 12. Token Y program
         // 13. Event authority
         // 14. Program
+        // 15.. Bin arrays the swap may cross
 
-        require!(accounts.len() >= 15, ErrorCode::InvalidAccountsLength);
+        self.validate_accounts(source_mint, accounts)?;
+        let (_, destination_before) = read_token_account(&accounts[USER_TOKEN_OUT_INDEX])?;
 
-        // Build Meteora swap instruction
-        let mut instruction_data = Vec::with_capacity(17);
+        // Build Meteora swap instruction: amount_in, min_amount_out
+        let mut instruction_data = Vec::with_capacity(24);
         instruction_data.extend_from_slice(&SWAP_INSTRUCTION);
         instruction_data.extend_from_slice(&amount_in.to_le_bytes());
         instruction_data.extend_from_slice(&min_amount_out.to_le_bytes());
@@ -88,21 +402,406 @@ impl DexAdapter for MeteoraAdapter {
             invoke(&instruction, accounts)?;
         }
 
-        Ok(min_amount_out)
+        let (_, destination_after) = read_token_account(&accounts[USER_TOKEN_OUT_INDEX])?;
+        let amount_out = destination_after.checked_sub(destination_before).ok_or(PyroSwapError::MathOverflow)?;
+        require!(amount_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+        Ok(amount_out)
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        _source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[USER_TOKEN_OUT_INDEX])
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[LB_PAIR_INDEX])
     }
 }
 
-/// Helper to get Meteora DLMM pool info
+/// Helper to get Meteora DLMM pool info: `(active_id, base_fee_bps)`
 pub fn get_meteora_pool_info<'info>(
     lb_pair_account: &AccountInfo<'info>,
-) -> Result<(u32, u16)> {
-    // In production, parse LB pair account to get active_id and base_fee_bps
-    // For now, return placeholder values
-    Ok((8388608u32, 20)) // active_id (price bin), fee_bps
+) -> Result<(i32, u16)> {
+    let lb_pair = LbPairInfo::try_from_account(lb_pair_account)?;
+    let base_fee_bps = lb_pair.base_fee_rate() * BASIS_POINT_MAX as u128 / FEE_PRECISION;
+    Ok((lb_pair.active_id, base_fee_bps as u16))
+}
+
+/// Simulate DLMM's swap loop for an exact input amount at time `now`.
+///
+/// `bin_arrays` are in swap direction, starting with the array holding the
+/// active bin. Arrays with no liquidity may be skipped, as the program jumps
+/// empty gaps. Fails where the program would: when the trade runs past the
+/// supplied arrays or the pair's bin range.
+pub fn quote_dlmm(
+    lb_pair: &LbPairInfo,
+    bin_arrays: &[BinArrayInfo],
+    amount: u64,
+    swap_for_y: bool,
+    now: i64,
+) -> Result<DlmmQuote> {
+    require!(amount > 0, PyroSwapError::InvalidAmount);
+    require!(lb_pair.status == PAIR_STATUS_ENABLED, ErrorCode::PairDisabled);
+
+    let mut pair = lb_pair.clone();
+    pair.update_references(now);
+
+    let mut amount_left = amount;
+    let mut amount_out: u64 = 0;
+    let mut fee_total: u64 = 0;
+    let mut touched = Vec::new();
+    let mut arrays = bin_arrays.iter();
+
+    while amount_left > 0 {
+        let array = arrays.next().ok_or(PyroSwapError::InsufficientLiquidity)?;
+        if !array.contains(pair.active_id) {
+            // The next array must lie ahead; hop the empty gap to its near edge
+            let ahead = if swap_for_y {
+                array.upper_bin_id() < pair.active_id as i64
+            } else {
+                array.lower_bin_id() > pair.active_id as i64
+            };
+            require!(ahead, ErrorCode::InvalidBinArray);
+            pair.active_id = if swap_for_y { array.upper_bin_id() } else { array.lower_bin_id() } as i32;
+        }
+        touched.push(array.key);
+
+        while array.contains(pair.active_id) && amount_left > 0 {
+            pair.update_volatility_accumulator();
+            let bin = array.bin(pair.active_id);
+            let price = if bin.price == 0 { price_from_id(pair.active_id, pair.bin_step)? } else { bin.price };
+
+            let liquidity_out = if swap_for_y { bin.amount_y } else { bin.amount_x };
+            if liquidity_out > 0 {
+                let (amount_in_with_fees, out, fee) = swap_in_bin(&pair, bin, price, amount_left, swap_for_y)?;
+                amount_left -= amount_in_with_fees;
+                amount_out = amount_out.checked_add(out).ok_or(PyroSwapError::MathOverflow)?;
+                fee_total = fee_total.checked_add(fee).ok_or(PyroSwapError::MathOverflow)?;
+            }
+            if amount_left > 0 {
+                pair.advance_active_bin(swap_for_y)?;
+            }
+        }
+    }
+
+    Ok(DlmmQuote {
+        amount_in: amount,
+        amount_out,
+        fee: fee_total,
+        end_active_id: pair.active_id,
+        bin_arrays: touched,
+    })
+}
+
+/// `Bin::swap`: fill as much of `amount_in` (fee-inclusive) as the bin holds,
+/// returning `(amount_in_with_fees, amount_out, fee)`
+fn swap_in_bin(pair: &LbPairInfo, bin: BinInfo, price: u128, amount_in: u64, swap_for_y: bool) -> Result<(u64, u64, u64)> {
+    let max_amount_out = if swap_for_y { bin.amount_y } else { bin.amount_x };
+    // Input that buys out the whole bin, rounded up
+    let max_amount_in = if swap_for_y {
+        mul_div(max_amount_out as u128, ONE_X64, price, true)
+    } else {
+        mul_div(max_amount_out as u128, price, ONE_X64, true)
+    }
+    .and_then(|a| u64::try_from(a).ok())
+    .ok_or(PyroSwapError::MathOverflow)?;
+    let max_fee = pair.compute_fee(max_amount_in)?;
+    let max_amount_in = max_amount_in.checked_add(max_fee).ok_or(PyroSwapError::MathOverflow)?;
+
+    if amount_in >= max_amount_in {
+        Ok((max_amount_in, max_amount_out, max_fee))
+    } else {
+        let fee = pair.compute_fee_from_amount(amount_in)?;
+        let out = bin_amount_out(amount_in - fee, price, swap_for_y).ok_or(PyroSwapError::MathOverflow)?;
+        Ok((amount_in, out.min(max_amount_out), fee))
+    }
+}
+
+/// Output for a fee-exclusive input at a bin price, rounded down
+fn bin_amount_out(amount_in: u64, price: u128, swap_for_y: bool) -> Option<u64> {
+    let out = if swap_for_y {
+        mul_div(amount_in as u128, price, ONE_X64, false)
+    } else {
+        mul_div(amount_in as u128, ONE_X64, price, false)
+    }?;
+    u64::try_from(out).ok()
+}
+
+/// `(1 + bin_step / 10000) ^ bin_id` in Q64.64, as DLMM's `get_price_from_id`
+pub fn price_from_id(bin_id: i32, bin_step: u16) -> Result<u128> {
+    let bps = ((bin_step as u128) << 64) / BASIS_POINT_MAX as u128;
+    Ok(pow(ONE_X64 + bps, bin_id).ok_or(PyroSwapError::MathOverflow)?)
+}
+
+/// DLMM's Q64.64 exponentiation by squaring. Bases above one are inverted
+/// first so every intermediate product stays within a u128.
+fn pow(base: u128, exp: i32) -> Option<u128> {
+    const MAX_EXPONENTIAL: u32 = 0x80000;
+    if exp == 0 {
+        return Some(ONE_X64);
+    }
+    let mut invert = exp.is_negative();
+    let exp = exp.unsigned_abs();
+    if exp >= MAX_EXPONENTIAL {
+        return None;
+    }
+
+    let mut squared_base = base;
+    let mut result = ONE_X64;
+    if squared_base >= result {
+        squared_base = u128::MAX.checked_div(squared_base)?;
+        invert = !invert;
+    }
+    for bit in 0..19 {
+        if exp & (1 << bit) > 0 {
+            result = result.checked_mul(squared_base)? >> 64;
+        }
+        squared_base = squared_base.checked_mul(squared_base)? >> 64;
+    }
+    if result == 0 {
+        return None;
+    }
+    if invert {
+        result = u128::MAX.checked_div(result)?;
+    }
+    Some(result)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid number of accounts provided")]
     InvalidAccountsLength,
+    #[msg("Account is not a DLMM LB pair")]
+    InvalidPoolAccount,
+    #[msg("Token accounts do not match the pool's tokens")]
+    MintMismatch,
+    #[msg("Bin arrays do not cover the swap from the active bin")]
+    InvalidBinArray,
+    #[msg("Pair is not enabled for trading")]
+    PairDisabled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+    use anchor_lang::solana_program::{entrypoint::ProgramResult, instruction::Instruction};
+
+    const MINT_X: Pubkey = Pubkey::new_from_array([1; 32]);
+    const MINT_Y: Pubkey = Pubkey::new_from_array([2; 32]);
+    /// Ten seconds after the pair's last swap: inside the filter period
+    const NOW: i64 = 1_010;
+
+    /// 25 bps bin step at bin 100, 0.25% base fee plus a variable fee from a
+    /// volatility reference of 5000 anchored two bins below the active bin
+    fn lb_pair_data() -> Vec<u8> {
+        let mut data = vec![0u8; LB_PAIR_LEN];
+        let mut put = |offset: usize, bytes: &[u8]| data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        put(BASE_FACTOR_OFFSET, &10_000u16.to_le_bytes());
+        put(FILTER_PERIOD_OFFSET, &30u16.to_le_bytes());
+        put(DECAY_PERIOD_OFFSET, &600u16.to_le_bytes());
+        put(REDUCTION_FACTOR_OFFSET, &5_000u16.to_le_bytes());
+        put(VARIABLE_FEE_CONTROL_OFFSET, &40_000u32.to_le_bytes());
+        put(MAX_VOLATILITY_ACCUMULATOR_OFFSET, &350_000u32.to_le_bytes());
+        put(MIN_BIN_ID_OFFSET, &(-443_636i32).to_le_bytes());
+        put(MAX_BIN_ID_OFFSET, &443_636i32.to_le_bytes());
+        put(VOLATILITY_ACCUMULATOR_OFFSET, &10_000u32.to_le_bytes());
+        put(VOLATILITY_REFERENCE_OFFSET, &5_000u32.to_le_bytes());
+        put(INDEX_REFERENCE_OFFSET, &98i32.to_le_bytes());
+        put(LAST_UPDATE_TIMESTAMP_OFFSET, &1_000i64.to_le_bytes());
+        put(ACTIVE_ID_OFFSET, &100i32.to_le_bytes());
+        put(BIN_STEP_OFFSET, &25u16.to_le_bytes());
+        put(TOKEN_X_MINT_OFFSET, MINT_X.as_ref());
+        put(TOKEN_Y_MINT_OFFSET, MINT_Y.as_ref());
+        data
+    }
+
+    /// Y below the active bin, X above it, the active bin holds both. Array 0
+    /// holds more Y; array 2 is empty and array 3 holds more X.
+    fn bin_reserves(bin_id: i32) -> (u64, u64) {
+        match bin_id {
+            65..=69 => (0, 3_000_000_000),
+            95..=99 => (0, 1_000_000_000 + (100 - bin_id as u64) * 50_000_000),
+            100 => (400_000_000, 600_000_000),
+            101..=105 => (2_000_000_000, 0),
+            210..=215 => (5_000_000_000, 0),
+            _ => (0, 0),
+        }
+    }
+
+    fn bin_array_data(index: i64, lb_pair: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; BIN_ARRAY_LEN];
+        data[BIN_ARRAY_INDEX_OFFSET..BIN_ARRAY_INDEX_OFFSET + 8].copy_from_slice(&index.to_le_bytes());
+        write_pubkey(&mut data, BIN_ARRAY_LB_PAIR_OFFSET, lb_pair);
+        for slot in 0..MAX_BIN_PER_ARRAY {
+            let (amount_x, amount_y) = bin_reserves(index as i32 * MAX_BIN_PER_ARRAY + slot);
+            let offset = BINS_OFFSET + slot as usize * BIN_LEN;
+            write_u64(&mut data, offset, amount_x);
+            write_u64(&mut data, offset + 8, amount_y);
+        }
+        data
+    }
+
+    fn pool_accounts(bin_array_indices: &[i64]) -> Vec<FixtureAccount> {
+        let lb_pair = Pubkey::new_unique();
+        let mut accounts: Vec<FixtureAccount> = (0..BIN_ARRAYS_START).map(|_| unused_account()).collect();
+        accounts[LB_PAIR_INDEX] = FixtureAccount::new(lb_pair, METEORA_DLMM_PROGRAM_ID, lb_pair_data());
+        for index in bin_array_indices {
+            accounts.push(FixtureAccount::new(
+                Pubkey::new_unique(),
+                METEORA_DLMM_PROGRAM_ID,
+                bin_array_data(*index, &lb_pair),
+            ));
+        }
+        accounts
+    }
+
+    fn quote(bin_array_indices: &[i64], amount: u64, swap_for_y: bool, now: i64) -> Result<DlmmQuote> {
+        let mut accounts = pool_accounts(bin_array_indices);
+        let keys: Vec<Pubkey> = accounts.iter().map(|a| a.key).collect();
+        let infos = infos(&mut accounts);
+        let lb_pair = LbPairInfo::try_from_account(&infos[LB_PAIR_INDEX])?;
+        let arrays = infos[BIN_ARRAYS_START..]
+            .iter()
+            .map(|a| BinArrayInfo::try_from_account(a, &keys[LB_PAIR_INDEX]))
+            .collect::<Result<Vec<_>>>()?;
+        let quote = quote_dlmm(&lb_pair, &arrays, amount, swap_for_y, now)?;
+        // Touched arrays are reported as account keys, in slice order
+        assert_eq!(quote.bin_arrays, keys[BIN_ARRAYS_START..BIN_ARRAYS_START + quote.bin_arrays.len()]);
+        Ok(quote)
+    }
+
+    #[test]
+    fn parses_lb_pair_and_fees() {
+        let lb_pair = LbPairInfo::try_from_slice(&lb_pair_data()).unwrap();
+        assert_eq!((lb_pair.active_id, lb_pair.bin_step), (100, 25));
+        assert_eq!(lb_pair.base_fee_rate(), 2_500_000);
+
+        let mut account = FixtureAccount::new(Pubkey::new_unique(), METEORA_DLMM_PROGRAM_ID, lb_pair_data());
+        assert_eq!(get_meteora_pool_info(&account.info()).unwrap(), (100, 25));
+
+        // Two bins from the reference: 5000 + 2 * 10000
+        let mut pair = lb_pair.clone();
+        pair.update_references(NOW);
+        pair.update_volatility_accumulator();
+        assert_eq!(pair.volatility_accumulator, 25_000);
+        assert_eq!(pair.variable_fee_rate(), 156_250);
+    }
+
+    #[test]
+    fn prices_bins_from_id() {
+        assert_eq!(price_from_id(0, 25).unwrap(), ONE_X64);
+        assert_eq!(price_from_id(100, 25).unwrap(), 23_678_699_809_202_413_098);
+        assert_eq!(bin_id_to_bin_array_index(69), 0);
+        assert_eq!(bin_id_to_bin_array_index(70), 1);
+        assert_eq!(bin_id_to_bin_array_index(-1), -1);
+        assert_eq!(bin_id_to_bin_array_index(-70), -1);
+        assert_eq!(bin_id_to_bin_array_index(-71), -2);
+    }
+
+    #[test]
+    fn quotes_x_for_y_within_the_active_bin() {
+        let result = quote(&[1, 0], 100_000_000, true, NOW).unwrap();
+        assert_eq!(result.amount_out, 128_021_526);
+        assert_eq!(result.fee, 265_625);
+        assert_eq!(result.end_active_id, 100);
+        assert_eq!(result.bin_arrays.len(), 1);
+    }
+
+    #[test]
+    fn quotes_x_for_y_across_bin_arrays() {
+        let result = quote(&[1, 0], 8_000_000_000, true, NOW).unwrap();
+        assert_eq!(result.amount_out, 9_831_758_932);
+        assert_eq!(result.fee, 86_635_857);
+        assert_eq!(result.end_active_id, 68);
+        assert_eq!(result.bin_arrays.len(), 2);
+    }
+
+    #[test]
+    fn decays_volatility_after_the_filter_period() {
+        // The reference resets to the active bin, so only the decayed
+        // reference feeds the variable fee
+        let result = quote(&[1, 0], 100_000_000, true, 1_100).unwrap();
+        assert_eq!(result.amount_out, 128_040_780);
+        assert_eq!(result.fee, 250_625);
+    }
+
+    #[test]
+    fn quotes_y_for_x_and_hops_empty_arrays() {
+        let result = quote(&[1, 3], 1_000_000_000, false, NOW).unwrap();
+        assert_eq!(result.amount_out, 775_977_814);
+        assert_eq!(result.fee, 2_729_028);
+        assert_eq!(result.end_active_id, 101);
+
+        // Array 2 is empty, so the swap continues at the first bin of array 3
+        let result = quote(&[1, 3], 20_000_000_000, false, NOW).unwrap();
+        assert_eq!(result.amount_out, 14_125_568_139);
+        assert_eq!(result.fee, 259_912_936);
+        assert_eq!(result.end_active_id, 210);
+        assert_eq!(result.bin_arrays.len(), 2);
+    }
+
+    #[test]
+    fn fails_past_the_supplied_bin_arrays() {
+        assert!(quote(&[1], 8_000_000_000, true, NOW).is_err());
+        // Arrays behind the active bin cannot be used
+        assert!(quote(&[1, 3], 8_000_000_000, true, NOW).is_err());
+    }
+
+    fn mock_dlmm(instruction: &Instruction, account_infos: &[AccountInfo], _signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        let amount_in = u64::from_le_bytes(instruction.data[8..16].try_into().unwrap());
+        let min_amount_out = u64::from_le_bytes(instruction.data[16..24].try_into().unwrap());
+        adjust_token_balance(cpi_account(account_infos, &instruction.accounts[USER_TOKEN_IN_INDEX])?, -(amount_in as i128))?;
+        adjust_token_balance(cpi_account(account_infos, &instruction.accounts[USER_TOKEN_OUT_INDEX])?, min_amount_out as i128 + 3)
+    }
+
+    #[test]
+    fn reports_the_measured_output() {
+        mock_program(METEORA_DLMM_PROGRAM_ID, mock_dlmm);
+        let mut accounts = pool_accounts(&[1]);
+        accounts[USER_TOKEN_IN_INDEX] = token_account(Pubkey::new_unique(), MINT_X, 5_000_000);
+        accounts[USER_TOKEN_OUT_INDEX] = token_account(Pubkey::new_unique(), MINT_Y, 0);
+        accounts[TOKEN_X_MINT_INDEX] = FixtureAccount::new(MINT_X, Pubkey::default(), vec![]);
+        accounts[TOKEN_Y_MINT_INDEX] = FixtureAccount::new(MINT_Y, Pubkey::default(), vec![]);
+        accounts[PROGRAM_INDEX] = FixtureAccount::new(METEORA_DLMM_PROGRAM_ID, Pubkey::default(), vec![]);
+        let slice = infos(&mut accounts);
+
+        assert_eq!(MeteoraAdapter.swap(1_000_000, 990_000, &MINT_X, &slice, None).unwrap(), 990_003);
+        assert_eq!(read_token_account(&slice[USER_TOKEN_IN_INDEX]).unwrap().1, 4_000_000);
+        assert!(MeteoraAdapter.swap(1_000_000, 990_000, &MINT_Y, &slice, None).is_err());
+    }
+
+    #[test]
+    fn direction_follows_source_mint() {
+        let mut account = FixtureAccount::new(Pubkey::new_unique(), METEORA_DLMM_PROGRAM_ID, lb_pair_data());
+
+        let lb_pair = LbPairInfo::try_from_account(&account.info()).unwrap();
+        assert!(lb_pair.swap_for_y(&MINT_X).unwrap());
+        assert!(!lb_pair.swap_for_y(&MINT_Y).unwrap());
+        assert!(lb_pair.swap_for_y(&Pubkey::new_unique()).is_err());
+
+        account.owner = Pubkey::new_unique();
+        assert!(LbPairInfo::try_from_account(&account.info()).is_err());
+    }
 }
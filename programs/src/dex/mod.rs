@@ -1,13 +1,35 @@
 // DEX integration modules
 pub mod raydium;
+pub mod raydium_clmm;
+pub mod raydium_cpmm;
 pub mod orca;
 pub mod meteora;
 pub mod pumpfun;
+pub mod phoenix;
+pub mod lifinity;
+pub mod generic;
+pub mod jupiter;
+pub mod math;
+pub mod route;
+pub mod split;
+pub mod venue;
 
 pub use raydium::*;
+pub use raydium_clmm::*;
+pub use raydium_cpmm::*;
 pub use orca::*;
 pub use meteora::*;
 pub use pumpfun::*;
+pub use phoenix::*;
+pub use lifinity::*;
+pub use generic::*;
+pub use jupiter::*;
+pub use route::*;
+pub use split::*;
+pub use venue::*;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
 
 use anchor_lang::prelude::*;
 use crate::state::DexType;
@@ -21,22 +43,74 @@ pub struct DexQuote {
     pub fee_bps: u16,
 }
 
-/// Trait for DEX integrations
+/// A DEX integration. Adapters are stateless and object safe; callers get
+/// the one for a venue from `adapter_for` rather than matching on `DexType`.
 pub trait DexAdapter {
-    /// Get a quote for swapping tokens
-    fn get_quote(
+    /// The venue this adapter trades on
+    fn dex_type(&self) -> DexType;
+
+    /// The program `swap` invokes
+    fn program_id(&self) -> Pubkey;
+
+    /// Check the swap slice is laid out the way `swap` expects for selling
+    /// `source_mint`: its length, the program account, and that the pool's
+    /// own accounts match what the pool state records
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()>;
+
+    /// Get a quote for swapping tokens against the live pool state.
+    /// `accounts` is the same slice that `swap` passes to the CPI.
+    fn get_quote<'info>(
+        &self,
         amount_in: u64,
-        input_decimals: u8,
-        output_decimals: u8,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
     ) -> Result<DexQuote>;
 
-    /// Execute a swap
+    /// Execute a swap, selling `source_mint` for the pool's other token
     fn swap<'info>(
+        &self,
         amount_in: u64,
         min_amount_out: u64,
+        source_mint: &Pubkey,
         accounts: &[AccountInfo<'info>],
         signer_seeds: Option<&[&[&[u8]]]>,
     ) -> Result<u64>;
+
+    /// The token account in the swap slice that receives the output when
+    /// selling `source_mint`, so callers can measure what a swap delivered
+    fn destination_account<'a, 'info>(
+        &self,
+        source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>>;
+
+    /// The pool, market or curve account in the swap slice, so callers can
+    /// pin a swap to a known pool
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>>;
+}
+
+/// The adapter for each venue. Adding a venue means a `DexType` variant, its
+/// `u8` code in `DexType::try_from`, and an arm here.
+pub fn adapter_for(dex_type: DexType) -> &'static dyn DexAdapter {
+    match dex_type {
+        DexType::Raydium => &RaydiumAdapter,
+        DexType::Orca => &OrcaAdapter,
+        DexType::Meteora => &MeteoraAdapter,
+        DexType::PumpFun => &PumpFunAdapter,
+        DexType::Phoenix => &PhoenixAdapter,
+        DexType::Lifinity => &LifinityAdapter,
+        DexType::RaydiumClmm => &RaydiumClmmAdapter,
+        DexType::RaydiumCpmm => &RaydiumCpmmAdapter,
+        DexType::Generic => &GenericAdapter { instruction_data: &[] },
+        DexType::Jupiter => &JupiterAdapter { instruction_data: &[] },
+    }
+}
+
+/// Require the program account at `index` of a swap slice to be `program_id`
+pub fn check_program_account(accounts: &[AccountInfo], index: usize, program_id: &Pubkey) -> Result<()> {
+    let program = accounts.get(index).ok_or(crate::errors::PyroSwapError::InvalidDexProgram)?;
+    require_keys_eq!(*program.key, *program_id, crate::errors::PyroSwapError::InvalidDexProgram);
+    Ok(())
 }
 
 /// Find the best quote across all DEXes
@@ -63,3 +137,38 @@ pub fn calculate_price_impact(
     
     ((diff as u128 * 10000) / expected_out as u128) as u16
 }
+
+/// Read `(mint, amount)` from an SPL Token or Token-2022 account
+pub fn read_token_account(account: &AccountInfo) -> Result<(Pubkey, u64)> {
+    require!(
+        *account.owner == anchor_spl::token::ID || *account.owner == anchor_spl::token_2022::ID,
+        crate::errors::PyroSwapError::InvalidTokenAccount
+    );
+    let data = account.try_borrow_data()?;
+    require!(data.len() >= 165, crate::errors::PyroSwapError::InvalidTokenAccount);
+    let mint = Pubkey::new_from_array(data[0..32].try_into().unwrap());
+    let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
+    Ok((mint, amount))
+}
+
+/// Token-2022 transfer fee withheld when `amount` of `mint` moves in `epoch`.
+/// SPL Token mints and Token-2022 mints without the extension charge nothing.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64, epoch: u64) -> Result<u64> {
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+    };
+    use anchor_spl::token_2022::spl_token_2022::state::Mint;
+
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(0);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)
+        .map_err(|_| crate::errors::PyroSwapError::InvalidTokenAccount)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => Ok(config
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or(crate::errors::PyroSwapError::MathOverflow)?),
+        Err(_) => Ok(0),
+    }
+}
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke;
-use crate::dex::{DexQuote, DexAdapter};
+use crate::dex::math::{mul_div, U256};
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
 use crate::state::DexType;
 
 /// Orca Whirlpool Program ID
@@ -9,70 +11,291 @@ pub const ORCA_WHIRLPOOL_PROGRAM_ID: Pubkey = solana_program::pubkey!("whirLbMii
 /// Orca swap instruction discriminator
 const SWAP_INSTRUCTION: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
 
+// Positions in the swap account slice (see `OrcaAdapter::swap`)
+const WHIRLPOOL_INDEX: usize = 2;
+const TOKEN_OWNER_ACCOUNT_A_INDEX: usize = 3;
+const TOKEN_VAULT_A_INDEX: usize = 4;
+const TOKEN_OWNER_ACCOUNT_B_INDEX: usize = 5;
+const TOKEN_VAULT_B_INDEX: usize = 6;
+const TICK_ARRAY_INDICES: [usize; 3] = [7, 8, 9];
+/// Accounts passed to the Whirlpool `swap` instruction itself
+const SWAP_ACCOUNTS_LEN: usize = 11;
+
+/// Size of the `Whirlpool` account
+pub const WHIRLPOOL_LEN: usize = 653;
+
+// `Whirlpool` field offsets (after the 8 byte Anchor discriminator)
+const TICK_SPACING_OFFSET: usize = 41;
+const FEE_RATE_OFFSET: usize = 45;
+const LIQUIDITY_OFFSET: usize = 49;
+const SQRT_PRICE_OFFSET: usize = 65;
+const TICK_CURRENT_INDEX_OFFSET: usize = 81;
+const TOKEN_MINT_A_OFFSET: usize = 101;
+const TOKEN_VAULT_A_OFFSET: usize = 133;
+const TOKEN_MINT_B_OFFSET: usize = 181;
+const TOKEN_VAULT_B_OFFSET: usize = 213;
+
+/// Size of a `TickArray` account
+pub const TICK_ARRAY_LEN: usize = 9988;
+/// Ticks stored per tick array
+pub const TICK_ARRAY_SIZE: i32 = 88;
+
+// `TickArray` layout: start index, 88 ticks, then the owning whirlpool
+const START_TICK_INDEX_OFFSET: usize = 8;
+const TICKS_OFFSET: usize = 12;
+const TICK_LEN: usize = 113;
+const TICK_ARRAY_WHIRLPOOL_OFFSET: usize = 9956;
+
+pub const MIN_TICK_INDEX: i32 = -443636;
+pub const MAX_TICK_INDEX: i32 = 443636;
+pub const MIN_SQRT_PRICE_X64: u128 = 4295048016;
+pub const MAX_SQRT_PRICE_X64: u128 = 79226673515401279992447579055;
+
+/// Whirlpool fee rates are expressed in hundredths of a basis point
+const FEE_RATE_MUL_VALUE: u128 = 1_000_000;
+
+/// The parts of the `Whirlpool` account needed to reproduce a swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhirlpoolInfo {
+    pub tick_spacing: u16,
+    pub fee_rate: u16,
+    pub liquidity: u128,
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+    pub token_mint_a: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_b: Pubkey,
+}
+
+impl WhirlpoolInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, ORCA_WHIRLPOOL_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(data.len() == WHIRLPOOL_LEN, ErrorCode::InvalidPoolAccount);
+        Ok(Self {
+            tick_spacing: u16::from_le_bytes(data[TICK_SPACING_OFFSET..TICK_SPACING_OFFSET + 2].try_into().unwrap()),
+            fee_rate: u16::from_le_bytes(data[FEE_RATE_OFFSET..FEE_RATE_OFFSET + 2].try_into().unwrap()),
+            liquidity: read_u128(data, LIQUIDITY_OFFSET),
+            sqrt_price: read_u128(data, SQRT_PRICE_OFFSET),
+            tick_current_index: i32::from_le_bytes(
+                data[TICK_CURRENT_INDEX_OFFSET..TICK_CURRENT_INDEX_OFFSET + 4].try_into().unwrap(),
+            ),
+            token_mint_a: read_pubkey(data, TOKEN_MINT_A_OFFSET),
+            token_vault_a: read_pubkey(data, TOKEN_VAULT_A_OFFSET),
+            token_mint_b: read_pubkey(data, TOKEN_MINT_B_OFFSET),
+            token_vault_b: read_pubkey(data, TOKEN_VAULT_B_OFFSET),
+        })
+    }
+
+    /// Selling token A moves the price down (`a_to_b`), selling B moves it up
+    pub fn a_to_b(&self, source_mint: &Pubkey) -> Result<bool> {
+        if *source_mint == self.token_mint_a {
+            Ok(true)
+        } else if *source_mint == self.token_mint_b {
+            Ok(false)
+        } else {
+            Err(ErrorCode::MintMismatch.into())
+        }
+    }
+}
+
+/// A tick array reduced to what the swap loop reads: `(initialized, liquidity_net)` per tick
+#[derive(Debug, Clone)]
+pub struct TickArrayInfo {
+    pub start_tick_index: i32,
+    pub ticks: Vec<(bool, i128)>,
+}
+
+impl TickArrayInfo {
+    pub fn try_from_account(account: &AccountInfo, whirlpool: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*account.owner, ORCA_WHIRLPOOL_PROGRAM_ID, ErrorCode::InvalidTickArray);
+        let data = account.try_borrow_data()?;
+        require!(data.len() == TICK_ARRAY_LEN, ErrorCode::InvalidTickArray);
+        require_keys_eq!(read_pubkey(&data, TICK_ARRAY_WHIRLPOOL_OFFSET), *whirlpool, ErrorCode::InvalidTickArray);
+        Ok(Self::from_slice(&data))
+    }
+
+    pub fn from_slice(data: &[u8]) -> Self {
+        let ticks = (0..TICK_ARRAY_SIZE as usize)
+            .map(|i| {
+                let offset = TICKS_OFFSET + i * TICK_LEN;
+                let liquidity_net = i128::from_le_bytes(data[offset + 1..offset + 17].try_into().unwrap());
+                (data[offset] != 0, liquidity_net)
+            })
+            .collect();
+        Self {
+            start_tick_index: i32::from_le_bytes(
+                data[START_TICK_INDEX_OFFSET..START_TICK_INDEX_OFFSET + 4].try_into().unwrap(),
+            ),
+            ticks,
+        }
+    }
+
+    fn ticks_in_array(tick_spacing: u16) -> i32 {
+        TICK_ARRAY_SIZE * tick_spacing as i32
+    }
+
+    /// b_to_a searches start one tick spacing lower, since the current tick
+    /// may sit on the last slot of the previous array
+    fn in_search_range(&self, tick_index: i32, tick_spacing: u16, shifted: bool) -> bool {
+        let mut lower = self.start_tick_index;
+        let mut upper = self.start_tick_index + Self::ticks_in_array(tick_spacing);
+        if shifted {
+            lower -= tick_spacing as i32;
+            upper -= tick_spacing as i32;
+        }
+        tick_index >= lower && tick_index < upper
+    }
+
+    fn tick_offset(&self, tick_index: i32, tick_spacing: u16) -> i32 {
+        (tick_index - self.start_tick_index).div_euclid(tick_spacing as i32)
+    }
+
+    /// Next initialized tick inside this array in the swap direction
+    fn next_initialized_tick(&self, tick_index: i32, tick_spacing: u16, a_to_b: bool) -> Result<Option<i32>> {
+        require!(
+            self.in_search_range(tick_index, tick_spacing, !a_to_b),
+            ErrorCode::InvalidTickArray
+        );
+        let mut offset = self.tick_offset(tick_index, tick_spacing);
+        // Moving up, the tick we are standing on has already been crossed
+        if !a_to_b {
+            offset += 1;
+        }
+        while (0..TICK_ARRAY_SIZE).contains(&offset) {
+            if self.ticks[offset as usize].0 {
+                return Ok(Some(self.start_tick_index + offset * tick_spacing as i32));
+            }
+            offset = if a_to_b { offset - 1 } else { offset + 1 };
+        }
+        Ok(None)
+    }
+
+    fn liquidity_net(&self, tick_index: i32, tick_spacing: u16) -> Option<i128> {
+        if (tick_index - self.start_tick_index) % tick_spacing as i32 != 0 {
+            return None;
+        }
+        let offset = self.tick_offset(tick_index, tick_spacing);
+        match self.ticks.get(usize::try_from(offset).ok()?) {
+            Some((true, liquidity_net)) => Some(*liquidity_net),
+            _ => None,
+        }
+    }
+}
+
+/// Result of simulating a Whirlpool exact-input swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhirlpoolQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub end_sqrt_price: u128,
+    /// Limit to pass to the swap instruction for this direction
+    pub sqrt_price_limit: u128,
+}
+
 pub struct OrcaAdapter;
 
 impl DexAdapter for OrcaAdapter {
-    fn get_quote(
+    fn dex_type(&self) -> DexType {
+        DexType::Orca
+    }
+
+    fn program_id(&self) -> Pubkey {
+        ORCA_WHIRLPOOL_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, SWAP_ACCOUNTS_LEN, &ORCA_WHIRLPOOL_PROGRAM_ID)?;
+
+        let pool = WhirlpoolInfo::try_from_account(&accounts[WHIRLPOOL_INDEX])?;
+        pool.a_to_b(source_mint)?;
+        require_keys_eq!(*accounts[TOKEN_VAULT_A_INDEX].key, pool.token_vault_a, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[TOKEN_VAULT_B_INDEX].key, pool.token_vault_b, ErrorCode::InvalidPoolAccount);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
         amount_in: u64,
-        _input_decimals: u8,
-        _output_decimals: u8,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
     ) -> Result<DexQuote> {
-        // Orca Whirlpools have dynamic fees based on tier
-        // Common tiers: 0.01%, 0.05%, 0.3%, 1%
-        let fee_bps = 30u16; // 0.3% for standard pools
-        
-        let amount_after_fee = amount_in
-            .checked_mul(10000 - fee_bps as u64)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap();
-        
-        // Simplified quote - in production, use Whirlpool's sqrt price
-        let amount_out = amount_after_fee;
-        
+        self.validate_accounts(source_mint, accounts)?;
+
+        let whirlpool_account = &accounts[WHIRLPOOL_INDEX];
+        let pool = WhirlpoolInfo::try_from_account(whirlpool_account)?;
+        let tick_arrays = TICK_ARRAY_INDICES
+            .iter()
+            .map(|&i| TickArrayInfo::try_from_account(&accounts[i], whirlpool_account.key))
+            .collect::<Result<Vec<_>>>()?;
+
+        let a_to_b = pool.a_to_b(source_mint)?;
+        let quote = quote_whirlpool(&pool, &tick_arrays, amount_in, a_to_b)?;
+
+        let amount_after_fee = mul_div(
+            amount_in as u128,
+            FEE_RATE_MUL_VALUE - pool.fee_rate as u128,
+            FEE_RATE_MUL_VALUE,
+            false,
+        ).ok_or(PyroSwapError::MathOverflow)?;
+        let spot_out = spot_amount_out(amount_after_fee, pool.sqrt_price, a_to_b);
+
         Ok(DexQuote {
             dex_type: DexType::Orca,
-            amount_out,
-            price_impact_bps: 8, // 0.08% estimated impact
-            fee_bps,
+            amount_out: quote.amount_out,
+            price_impact_bps: calculate_price_impact(amount_in, quote.amount_out, spot_out),
+            fee_bps: pool.fee_rate / 100,
         })
     }
 
     fn swap<'info>(
+        &self,
         amount_in: u64,
         min_amount_out: u64,
+        source_mint: &Pubkey,
         accounts: &[AccountInfo<'info>],
         signer_seeds: Option<&[&[&[u8]]]>,
     ) -> Result<u64> {
         // Orca Whirlpool swap CPI
-        // accounts expected:
+        // accounts expected, in the order of Whirlpool's `swap` instruction:
         // 0. Token program
-        // 1. Whirlpool program
+        // 1. Token authority
         // 2. Whirlpool
-        // 3. Token authority
+        // 3. Token owner account A
         // 4. Token vault A
-        // 5. Token vault B
-        // 6. Tick array 0
-        // 7. Tick array 1
-        // 8. Tick array 2
-        // 9. Oracle
-        // 10. User token account A
-        // 11. User token account B
-        // 12. User authority
+        // 5. Token owner account B
+        // 6. Token vault B
+        // 7. Tick array 0
+        // 8. Tick array 1
+        // 9. Tick array 2
+        // 10. Oracle
+        // 11. Whirlpool program (passed to the CPI, not to the instruction)
+
+        self.validate_accounts(source_mint, accounts)?;
 
-        require!(accounts.len() >= 13, ErrorCode::InvalidAccountsLength);
+        let pool = WhirlpoolInfo::try_from_account(&accounts[WHIRLPOOL_INDEX])?;
+        let a_to_b = pool.a_to_b(source_mint)?;
+        let destination = &accounts[if a_to_b { TOKEN_OWNER_ACCOUNT_B_INDEX } else { TOKEN_OWNER_ACCOUNT_A_INDEX }];
+        let (_, destination_before) = read_token_account(destination)?;
 
         // Build Orca swap instruction
-        let mut instruction_data = Vec::with_capacity(25);
-        instruction_data.extend_from_slice(&SWAP_INSTRUCTION);
-        instruction_data.extend_from_slice(&amount_in.to_le_bytes());
-        instruction_data.extend_from_slice(&min_amount_out.to_le_bytes());
-        instruction_data.extend_from_slice(&[1u8]); // a_to_b direction
-        instruction_data.extend_from_slice(&[0u8; 8]); // sqrt_price_limit (0 = no limit)
+        let instruction_data = swap_instruction_data(
+            amount_in,
+            min_amount_out,
+            default_sqrt_price_limit(a_to_b),
+            a_to_b,
+        );
 
         let instruction = anchor_lang::solana_program::instruction::Instruction {
             program_id: ORCA_WHIRLPOOL_PROGRAM_ID,
-            accounts: accounts.iter().map(|a| {
+            accounts: accounts[..SWAP_ACCOUNTS_LEN].iter().map(|a| {
                 anchor_lang::solana_program::instruction::AccountMeta {
                     pubkey: *a.key,
                     is_signer: a.is_signer,
@@ -88,21 +311,670 @@ impl DexAdapter for OrcaAdapter {
             invoke(&instruction, accounts)?;
         }
 
-        Ok(min_amount_out)
+        let (_, destination_after) = read_token_account(destination)?;
+        let amount_out = destination_after.checked_sub(destination_before).ok_or(PyroSwapError::MathOverflow)?;
+        require!(amount_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+        Ok(amount_out)
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        let pool = WhirlpoolInfo::try_from_account(&accounts[WHIRLPOOL_INDEX])?;
+        let index = if pool.a_to_b(source_mint)? { TOKEN_OWNER_ACCOUNT_B_INDEX } else { TOKEN_OWNER_ACCOUNT_A_INDEX };
+        Ok(&accounts[index])
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[WHIRLPOOL_INDEX])
     }
 }
 
-/// Helper to get Orca Whirlpool info
+/// Helper to get Orca Whirlpool info: `(sqrt_price, fee_bps)`
 pub fn get_orca_whirlpool_info<'info>(
     whirlpool_account: &AccountInfo<'info>,
 ) -> Result<(u128, u16)> {
-    // In production, parse whirlpool account to get sqrt_price and fee_rate
-    // For now, return placeholder values
-    Ok((79228162514264337593543950336u128, 30)) // sqrt_price, fee_bps
+    let pool = WhirlpoolInfo::try_from_account(whirlpool_account)?;
+    Ok((pool.sqrt_price, pool.fee_rate / 100))
+}
+
+/// Whirlpool `swap` args: amount, other_amount_threshold, sqrt_price_limit,
+/// amount_specified_is_input, a_to_b. We always specify the input amount, so
+/// the threshold is the minimum output.
+pub fn swap_instruction_data(
+    amount_in: u64,
+    min_amount_out: u64,
+    sqrt_price_limit: u128,
+    a_to_b: bool,
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(42);
+    data.extend_from_slice(&SWAP_INSTRUCTION);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.push(1); // amount_specified_is_input
+    data.push(a_to_b as u8);
+    data
+}
+
+/// The limit that lets an exact-input swap run as far as liquidity allows
+pub fn default_sqrt_price_limit(a_to_b: bool) -> u128 {
+    if a_to_b { MIN_SQRT_PRICE_X64 } else { MAX_SQRT_PRICE_X64 }
+}
+
+/// Simulate Whirlpool's swap loop for an exact input amount.
+///
+/// `tick_arrays` must be in swap direction, starting with the array that
+/// holds the current tick, exactly as passed to the swap instruction.
+/// Fails where the program would: when the trade runs past the supplied
+/// tick arrays, or when the pool cannot absorb the whole input.
+pub fn quote_whirlpool(
+    pool: &WhirlpoolInfo,
+    tick_arrays: &[TickArrayInfo],
+    amount: u64,
+    a_to_b: bool,
+) -> Result<WhirlpoolQuote> {
+    require!(amount > 0, PyroSwapError::InvalidAmount);
+    let tick_spacing = pool.tick_spacing;
+    require!(tick_spacing > 0, ErrorCode::InvalidPoolAccount);
+
+    let sequence = tick_array_sequence(tick_arrays, pool.tick_current_index, tick_spacing, a_to_b)?;
+    let sqrt_price_limit = default_sqrt_price_limit(a_to_b);
+
+    let mut amount_remaining = amount;
+    let mut amount_out: u64 = 0;
+    let mut fee_total: u64 = 0;
+    let mut curr_sqrt_price = pool.sqrt_price;
+    let mut curr_tick_index = pool.tick_current_index;
+    let mut curr_liquidity = pool.liquidity;
+    let mut array_index = 0;
+
+    while amount_remaining > 0 && curr_sqrt_price != sqrt_price_limit {
+        let (next_array_index, next_tick_index) =
+            next_initialized_tick(sequence, curr_tick_index, tick_spacing, a_to_b, array_index)?;
+        array_index = next_array_index;
+
+        let next_tick_sqrt_price = sqrt_price_from_tick_index(next_tick_index.clamp(MIN_TICK_INDEX, MAX_TICK_INDEX));
+        let sqrt_price_target = if a_to_b {
+            next_tick_sqrt_price.max(sqrt_price_limit)
+        } else {
+            next_tick_sqrt_price.min(sqrt_price_limit)
+        };
+
+        let step = compute_swap_step(
+            amount_remaining,
+            pool.fee_rate,
+            curr_liquidity,
+            curr_sqrt_price,
+            sqrt_price_target,
+            a_to_b,
+        )?;
+
+        amount_remaining = amount_remaining
+            .checked_sub(step.amount_in)
+            .and_then(|a| a.checked_sub(step.fee_amount))
+            .ok_or(PyroSwapError::MathOverflow)?;
+        amount_out = amount_out.checked_add(step.amount_out).ok_or(PyroSwapError::MathOverflow)?;
+        fee_total = fee_total.checked_add(step.fee_amount).ok_or(PyroSwapError::MathOverflow)?;
+
+        if step.next_sqrt_price == next_tick_sqrt_price {
+            // Crossing an initialized tick moves liquidity in or out of range
+            let array = &sequence[array_index];
+            if let Some(liquidity_net) = array.liquidity_net(next_tick_index, tick_spacing) {
+                let delta = if a_to_b { -liquidity_net } else { liquidity_net };
+                curr_liquidity = add_liquidity_delta(curr_liquidity, delta)?;
+            }
+            // Leaving through the edge of an array continues in the next one
+            let offset = array.tick_offset(next_tick_index, tick_spacing);
+            if (a_to_b && offset == 0) || (!a_to_b && offset == TICK_ARRAY_SIZE - 1) {
+                array_index += 1;
+            }
+            curr_tick_index = if a_to_b { next_tick_index - 1 } else { next_tick_index };
+        }
+        // A step that stops between ticks consumed all remaining input or hit
+        // the limit, so the loop ends without needing the new tick index
+        curr_sqrt_price = step.next_sqrt_price;
+    }
+
+    require!(amount_remaining == 0, PyroSwapError::InsufficientLiquidity);
+
+    Ok(WhirlpoolQuote {
+        amount_in: amount,
+        amount_out,
+        fee_amount: fee_total,
+        end_sqrt_price: curr_sqrt_price,
+        sqrt_price_limit,
+    })
+}
+
+/// Keep the leading run of arrays that are contiguous in swap direction;
+/// repeated arrays (allowed when liquidity is concentrated) end the run
+fn tick_array_sequence(
+    tick_arrays: &[TickArrayInfo],
+    tick_current_index: i32,
+    tick_spacing: u16,
+    a_to_b: bool,
+) -> Result<&[TickArrayInfo]> {
+    let first = tick_arrays.first().ok_or(ErrorCode::InvalidTickArray)?;
+    require!(
+        first.in_search_range(tick_current_index, tick_spacing, !a_to_b),
+        ErrorCode::InvalidTickArray
+    );
+
+    let step = TickArrayInfo::ticks_in_array(tick_spacing);
+    let mut len = 1;
+    while len < tick_arrays.len() {
+        let expected_start = if a_to_b {
+            tick_arrays[len - 1].start_tick_index - step
+        } else {
+            tick_arrays[len - 1].start_tick_index + step
+        };
+        if tick_arrays[len].start_tick_index != expected_start {
+            break;
+        }
+        len += 1;
+    }
+    Ok(&tick_arrays[..len])
+}
+
+/// Next initialized tick across the sequence; the far edge of the last array
+/// if none is initialized
+fn next_initialized_tick(
+    sequence: &[TickArrayInfo],
+    tick_index: i32,
+    tick_spacing: u16,
+    a_to_b: bool,
+    start_array_index: usize,
+) -> Result<(usize, i32)> {
+    let ticks_in_array = TickArrayInfo::ticks_in_array(tick_spacing);
+    let mut search_index = tick_index;
+    let mut array_index = start_array_index;
+    loop {
+        let array = sequence.get(array_index).ok_or(ErrorCode::InvalidTickArray)?;
+        if let Some(next) = array.next_initialized_tick(search_index, tick_spacing, a_to_b)? {
+            return Ok((array_index, next));
+        }
+        if array_index + 1 == sequence.len() {
+            let edge = if a_to_b {
+                array.start_tick_index
+            } else {
+                array.start_tick_index + ticks_in_array - 1
+            };
+            return Ok((array_index, edge));
+        }
+        search_index = if a_to_b {
+            array.start_tick_index - 1
+        } else {
+            array.start_tick_index + ticks_in_array - 1
+        };
+        array_index += 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SwapStep {
+    amount_in: u64,
+    amount_out: u64,
+    next_sqrt_price: u128,
+    fee_amount: u64,
+}
+
+/// One exact-input step of Whirlpool's `compute_swap` towards `sqrt_price_target`
+fn compute_swap_step(
+    amount_remaining: u64,
+    fee_rate: u16,
+    liquidity: u128,
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    a_to_b: bool,
+) -> Result<SwapStep> {
+    let fee_rate = fee_rate as u128;
+    let amount_less_fee = mul_div(
+        amount_remaining as u128,
+        FEE_RATE_MUL_VALUE - fee_rate,
+        FEE_RATE_MUL_VALUE,
+        false,
+    ).ok_or(PyroSwapError::MathOverflow)? as u64;
+
+    // Input needed to reach the target; `None` when it exceeds a u64
+    let input_to_target = input_delta(sqrt_price_current, sqrt_price_target, liquidity, a_to_b);
+
+    let next_sqrt_price = match input_to_target {
+        Some(needed) if needed <= amount_less_fee => sqrt_price_target,
+        _ => next_sqrt_price_from_input(sqrt_price_current, liquidity, amount_less_fee, a_to_b)?,
+    };
+    let is_max_swap = next_sqrt_price == sqrt_price_target;
+
+    let amount_in = match input_to_target {
+        Some(needed) if is_max_swap => needed,
+        _ => input_delta(sqrt_price_current, next_sqrt_price, liquidity, a_to_b)
+            .ok_or(PyroSwapError::MathOverflow)?,
+    };
+    let amount_out = output_delta(sqrt_price_current, next_sqrt_price, liquidity, a_to_b)?;
+
+    let fee_amount = if is_max_swap {
+        mul_div(amount_in as u128, fee_rate, FEE_RATE_MUL_VALUE - fee_rate, true)
+            .and_then(|f| u64::try_from(f).ok())
+            .ok_or(PyroSwapError::MathOverflow)?
+    } else {
+        amount_remaining - amount_in
+    };
+
+    Ok(SwapStep { amount_in, amount_out, next_sqrt_price, fee_amount })
+}
+
+fn input_delta(sqrt_price_0: u128, sqrt_price_1: u128, liquidity: u128, a_to_b: bool) -> Option<u64> {
+    if a_to_b {
+        amount_delta_a(sqrt_price_0, sqrt_price_1, liquidity, true)
+    } else {
+        amount_delta_b(sqrt_price_0, sqrt_price_1, liquidity, true)
+    }
+}
+
+fn output_delta(sqrt_price_0: u128, sqrt_price_1: u128, liquidity: u128, a_to_b: bool) -> Result<u64> {
+    let delta = if a_to_b {
+        amount_delta_b(sqrt_price_0, sqrt_price_1, liquidity, false)
+    } else {
+        amount_delta_a(sqrt_price_0, sqrt_price_1, liquidity, false)
+    };
+    Ok(delta.ok_or(PyroSwapError::MathOverflow)?)
+}
+
+/// Token A between two prices: `L * (upper - lower) / (upper * lower)` in Q64.64
+fn amount_delta_a(sqrt_price_0: u128, sqrt_price_1: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (lower, upper) = if sqrt_price_0 < sqrt_price_1 {
+        (sqrt_price_0, sqrt_price_1)
+    } else {
+        (sqrt_price_1, sqrt_price_0)
+    };
+    let numerator = U256::mul_u128(liquidity, upper - lower).checked_shl_64()?;
+    let denominator = U256::mul_u128(upper, lower);
+    let (quotient, remainder) = numerator.checked_div_rem(denominator)?;
+    let mut result = quotient.to_u128()?;
+    if round_up && !remainder.is_zero() {
+        result += 1;
+    }
+    u64::try_from(result).ok()
+}
+
+/// Token B between two prices: `L * (upper - lower)` in Q64.64
+fn amount_delta_b(sqrt_price_0: u128, sqrt_price_1: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let diff = sqrt_price_0.abs_diff(sqrt_price_1);
+    let product = U256::mul_u128(liquidity, diff);
+    let result = u64::try_from(product.shift_right(64).to_u128()?).ok()?;
+    if round_up && product.low_u128() & u64::MAX as u128 > 0 {
+        result.checked_add(1)
+    } else {
+        Some(result)
+    }
+}
+
+/// Price after adding `amount` of input, rounded against the trader
+fn next_sqrt_price_from_input(sqrt_price: u128, liquidity: u128, amount: u64, a_to_b: bool) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price);
+    }
+    require!(liquidity > 0, PyroSwapError::InsufficientLiquidity);
+
+    if a_to_b {
+        // L * P / (L + amount * P), rounded up
+        let product = U256::mul_u128(sqrt_price, amount as u128);
+        let numerator = U256::mul_u128(liquidity, sqrt_price)
+            .checked_shl_64()
+            .ok_or(PyroSwapError::MathOverflow)?;
+        let denominator = U256::from_u128(liquidity)
+            .checked_shl_64()
+            .and_then(|l| l.checked_add(product))
+            .ok_or(PyroSwapError::MathOverflow)?;
+        let (quotient, remainder) = numerator
+            .checked_div_rem(denominator)
+            .ok_or(PyroSwapError::MathOverflow)?;
+        let mut price = quotient.to_u128().ok_or(PyroSwapError::MathOverflow)?;
+        if !remainder.is_zero() {
+            price += 1;
+        }
+        require!(price >= MIN_SQRT_PRICE_X64, ErrorCode::SqrtPriceOutOfBounds);
+        Ok(price)
+    } else {
+        // P + amount / L, rounded down
+        let delta = ((amount as u128) << 64) / liquidity;
+        let price = sqrt_price.checked_add(delta).ok_or(ErrorCode::SqrtPriceOutOfBounds)?;
+        require!(price <= MAX_SQRT_PRICE_X64, ErrorCode::SqrtPriceOutOfBounds);
+        Ok(price)
+    }
+}
+
+fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    let updated = if delta >= 0 {
+        liquidity.checked_add(delta.unsigned_abs())
+    } else {
+        liquidity.checked_sub(delta.unsigned_abs())
+    };
+    Ok(updated.ok_or(PyroSwapError::MathOverflow)?)
+}
+
+/// Output at the current price with no curve movement, for price impact
+fn spot_amount_out(amount_after_fee: u128, sqrt_price: u128, a_to_b: bool) -> u64 {
+    let one = 1u128 << 64;
+    let out = if a_to_b {
+        mul_div(amount_after_fee, sqrt_price, one, false).and_then(|x| mul_div(x, sqrt_price, one, false))
+    } else {
+        mul_div(amount_after_fee, one, sqrt_price, false).and_then(|x| mul_div(x, one, sqrt_price, false))
+    };
+    out.map_or(u64::MAX, |x| x.min(u64::MAX as u128) as u64)
+}
+
+/// `sqrt(1.0001^tick)` in Q64.64, bit for bit with Whirlpool's `tick_math`
+pub fn sqrt_price_from_tick_index(tick: i32) -> u128 {
+    if tick >= 0 {
+        sqrt_price_positive_tick(tick)
+    } else {
+        sqrt_price_negative_tick(tick)
+    }
+}
+
+/// Q96 products of `sqrt(1.0001)^(2^i)`, shifted down to Q64 at the end
+fn sqrt_price_positive_tick(tick: i32) -> u128 {
+    const FACTORS: [u128; 18] = [
+        79236085330515764027303304731,
+        79244008939048815603706035061,
+        79259858533276714757314932305,
+        79291567232598584799939703904,
+        79355022692464371645785046466,
+        79482085999252804386437311141,
+        79736823300114093921829183326,
+        80248749790819932309965073892,
+        81282483887344747381513967011,
+        83390072131320151908154831281,
+        87770609709833776024991924138,
+        97234110755111693312479820773,
+        119332217159966728226237229890,
+        179736315981702064433883588727,
+        407748233172238350107850275304,
+        2098478828474011932436660412517,
+        55581415166113811149459800483533,
+        38992368544603139932233054999993551,
+    ];
+    let mut ratio: u128 = if tick & 1 != 0 {
+        79232123823359799118286999567
+    } else {
+        79228162514264337593543950336
+    };
+    for (i, factor) in FACTORS.iter().enumerate() {
+        if tick & (2 << i) != 0 {
+            ratio = U256::mul_u128(ratio, *factor).shift_right(96).low_u128();
+        }
+    }
+    ratio >> 32
+}
+
+/// Q64 products of `1 / sqrt(1.0001)^(2^i)`
+fn sqrt_price_negative_tick(tick: i32) -> u128 {
+    const FACTORS: [u128; 18] = [
+        18444899583751176498,
+        18443055278223354162,
+        18439367220385604838,
+        18431993317065449817,
+        18417254355718160513,
+        18387811781193591352,
+        18329067761203520168,
+        18212142134806087854,
+        17980523815641551639,
+        17526086738831147013,
+        16651378430235024244,
+        15030750278693429944,
+        12247334978882834399,
+        8131365268884726200,
+        3584323654723342297,
+        696457651847595233,
+        26294789957452057,
+        37481735321082,
+    ];
+    let abs_tick = tick.abs();
+    let mut ratio: u128 = if abs_tick & 1 != 0 {
+        18445821805675392311
+    } else {
+        18446744073709551616
+    };
+    for (i, factor) in FACTORS.iter().enumerate() {
+        if abs_tick & (2 << i) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+    ratio
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid number of accounts provided")]
     InvalidAccountsLength,
+    #[msg("Account is not a Whirlpool")]
+    InvalidPoolAccount,
+    #[msg("Tick arrays do not cover the swap from the current tick")]
+    InvalidTickArray,
+    #[msg("Swap would move the price outside Whirlpool bounds")]
+    SqrtPriceOutOfBounds,
+    #[msg("Source mint is neither of the pool's tokens")]
+    MintMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+    use anchor_lang::solana_program::entrypoint::ProgramResult;
+    use anchor_lang::solana_program::instruction::Instruction;
+
+    /// 0.3% pool, tick spacing 64, sitting at tick -1000 inside two positions:
+    /// [-3200, 3200) with 4e12 and [-1280, -640) with 2e12 liquidity. Two
+    /// further positions sit in the outer arrays on each side.
+    const SQRT_PRICE: u128 = 17_547_129_613_991_611_122;
+    const TICK_CURRENT: i32 = -1000;
+    const LIQUIDITY: u128 = 6_000_000_000_000;
+    const MINT_A: Pubkey = Pubkey::new_from_array([1; 32]);
+    const MINT_B: Pubkey = Pubkey::new_from_array([2; 32]);
+    const VAULT_A: Pubkey = Pubkey::new_from_array([3; 32]);
+    const VAULT_B: Pubkey = Pubkey::new_from_array([4; 32]);
+    const TICKS: [(i32, i128); 8] = [
+        (-12800, 1_000_000_000_000),
+        (-11520, -1_000_000_000_000),
+        (-3200, 4_000_000_000_000),
+        (-1280, 2_000_000_000_000),
+        (-640, -2_000_000_000_000),
+        (3200, -4_000_000_000_000),
+        (4032, 1_000_000_000_000),
+        (7040, -1_000_000_000_000),
+    ];
+
+    fn whirlpool_data() -> Vec<u8> {
+        let mut data = vec![0u8; WHIRLPOOL_LEN];
+        data[TICK_SPACING_OFFSET..TICK_SPACING_OFFSET + 2].copy_from_slice(&64u16.to_le_bytes());
+        data[FEE_RATE_OFFSET..FEE_RATE_OFFSET + 2].copy_from_slice(&3000u16.to_le_bytes());
+        data[LIQUIDITY_OFFSET..LIQUIDITY_OFFSET + 16].copy_from_slice(&LIQUIDITY.to_le_bytes());
+        data[SQRT_PRICE_OFFSET..SQRT_PRICE_OFFSET + 16].copy_from_slice(&SQRT_PRICE.to_le_bytes());
+        data[TICK_CURRENT_INDEX_OFFSET..TICK_CURRENT_INDEX_OFFSET + 4].copy_from_slice(&TICK_CURRENT.to_le_bytes());
+        write_pubkey(&mut data, TOKEN_MINT_A_OFFSET, &MINT_A);
+        write_pubkey(&mut data, TOKEN_MINT_B_OFFSET, &MINT_B);
+        write_pubkey(&mut data, TOKEN_VAULT_A_OFFSET, &VAULT_A);
+        write_pubkey(&mut data, TOKEN_VAULT_B_OFFSET, &VAULT_B);
+        data
+    }
+
+    fn tick_array_data(start_tick_index: i32, whirlpool: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; TICK_ARRAY_LEN];
+        data[START_TICK_INDEX_OFFSET..START_TICK_INDEX_OFFSET + 4].copy_from_slice(&start_tick_index.to_le_bytes());
+        for (tick, liquidity_net) in TICKS {
+            let slot = tick - start_tick_index;
+            if (0..TICK_ARRAY_SIZE * 64).contains(&slot) {
+                let offset = TICKS_OFFSET + (slot / 64) as usize * TICK_LEN;
+                data[offset] = 1;
+                data[offset + 1..offset + 17].copy_from_slice(&liquidity_net.to_le_bytes());
+            }
+        }
+        write_pubkey(&mut data, TICK_ARRAY_WHIRLPOOL_OFFSET, whirlpool);
+        data
+    }
+
+    fn pool_accounts(tick_array_starts: [i32; 3]) -> Vec<FixtureAccount> {
+        let whirlpool = Pubkey::new_unique();
+        let mut accounts: Vec<FixtureAccount> = (0..SWAP_ACCOUNTS_LEN + 1).map(|_| unused_account()).collect();
+        accounts[WHIRLPOOL_INDEX] = FixtureAccount::new(whirlpool, ORCA_WHIRLPOOL_PROGRAM_ID, whirlpool_data());
+        accounts[TOKEN_VAULT_A_INDEX] = FixtureAccount::new(VAULT_A, Pubkey::default(), vec![]);
+        accounts[TOKEN_VAULT_B_INDEX] = FixtureAccount::new(VAULT_B, Pubkey::default(), vec![]);
+        accounts[SWAP_ACCOUNTS_LEN] = FixtureAccount::new(ORCA_WHIRLPOOL_PROGRAM_ID, Pubkey::default(), vec![]);
+        for (index, start) in TICK_ARRAY_INDICES.iter().zip(tick_array_starts) {
+            accounts[*index] = FixtureAccount::new(
+                Pubkey::new_unique(),
+                ORCA_WHIRLPOOL_PROGRAM_ID,
+                tick_array_data(start, &whirlpool),
+            );
+        }
+        accounts
+    }
+
+    fn quote(tick_array_starts: [i32; 3], amount: u64, a_to_b: bool) -> Result<WhirlpoolQuote> {
+        let mut accounts = pool_accounts(tick_array_starts);
+        let infos = infos(&mut accounts);
+        let pool = WhirlpoolInfo::try_from_account(&infos[WHIRLPOOL_INDEX])?;
+        let arrays = TICK_ARRAY_INDICES
+            .iter()
+            .map(|&i| TickArrayInfo::try_from_account(&infos[i], infos[WHIRLPOOL_INDEX].key))
+            .collect::<Result<Vec<_>>>()?;
+        quote_whirlpool(&pool, &arrays, amount, a_to_b)
+    }
+
+    const DOWN: [i32; 3] = [-5632, -11264, -16896];
+    const UP: [i32; 3] = [-5632, 0, 5632];
+
+    #[test]
+    fn tick_math_matches_whirlpool_bounds() {
+        assert_eq!(sqrt_price_from_tick_index(0), 1u128 << 64);
+        assert_eq!(sqrt_price_from_tick_index(MAX_TICK_INDEX), MAX_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_from_tick_index(MIN_TICK_INDEX), MIN_SQRT_PRICE_X64);
+    }
+
+    #[test]
+    fn quotes_a_to_b_within_the_current_range() {
+        let result = quote(DOWN, 1_000_000_000, true).unwrap();
+        assert_eq!(result.amount_out, 901_984_845);
+        assert_eq!(result.fee_amount, 3_000_000);
+        assert_eq!(result.end_sqrt_price, 17_544_356_500_057_234_692);
+        assert_eq!(result.sqrt_price_limit, MIN_SQRT_PRICE_X64);
+    }
+
+    #[test]
+    fn quotes_a_to_b_across_ticks_and_arrays() {
+        // Crosses -1280 and -3200, an empty array, then -11520 in the last one
+        let result = quote(DOWN, 600_000_000_000, true).unwrap();
+        assert_eq!(result.amount_out, 446_858_120_274);
+        assert_eq!(result.fee_amount, 1_800_000_002);
+        assert_eq!(result.end_sqrt_price, 9_925_693_146_247_796_593);
+    }
+
+    #[test]
+    fn quotes_b_to_a() {
+        let small = quote(UP, 1_000_000_000, false).unwrap();
+        assert_eq!(small.amount_out, 1_101_657_452);
+        assert_eq!(small.end_sqrt_price, 17_550_194_847_965_192_525);
+        assert_eq!(small.sqrt_price_limit, MAX_SQRT_PRICE_X64);
+
+        // Crosses -640, 3200 and 4032
+        let large = quote(UP, 1_000_000_000_000, false).unwrap();
+        assert_eq!(large.amount_out, 880_227_183_413);
+        assert_eq!(large.fee_amount, 3_000_000_002);
+        assert_eq!(large.end_sqrt_price, 23_920_167_035_287_174_099);
+    }
+
+    #[test]
+    fn fails_past_the_supplied_tick_arrays() {
+        assert!(quote(DOWN, 700_000_000_000, true).is_err());
+        // Arrays in the wrong direction do not hold the path
+        assert!(quote(UP, 600_000_000_000, true).is_err());
+    }
+
+    #[test]
+    fn adapter_quotes_from_accounts() {
+        // Direction follows the source mint
+        let mut accounts = pool_accounts(DOWN);
+        let quote = OrcaAdapter.get_quote(1_000_000_000, &MINT_A, &infos(&mut accounts)).unwrap();
+        assert_eq!(quote.amount_out, 901_984_845);
+        assert_eq!(quote.fee_bps, 30);
+
+        let mut accounts = pool_accounts(UP);
+        let quote = OrcaAdapter.get_quote(1_000_000_000, &MINT_B, &infos(&mut accounts)).unwrap();
+        assert_eq!(quote.amount_out, 1_101_657_452);
+
+        let mut accounts = pool_accounts(DOWN);
+        assert!(OrcaAdapter.get_quote(1_000_000_000, &Pubkey::new_unique(), &infos(&mut accounts)).is_err());
+
+        // Tick arrays must belong to the quoted whirlpool
+        let mut accounts = pool_accounts(DOWN);
+        accounts[TICK_ARRAY_INDICES[1]].data[TICK_ARRAY_WHIRLPOOL_OFFSET] ^= 1;
+        assert!(OrcaAdapter.get_quote(1_000_000_000, &MINT_A, &infos(&mut accounts)).is_err());
+
+        // So must the vaults, and the program follows the instruction accounts
+        let mut accounts = pool_accounts(DOWN);
+        accounts.swap(TOKEN_VAULT_A_INDEX, TOKEN_VAULT_B_INDEX);
+        assert!(OrcaAdapter.get_quote(1_000_000_000, &MINT_A, &infos(&mut accounts)).is_err());
+        let mut accounts = pool_accounts(DOWN);
+        accounts[SWAP_ACCOUNTS_LEN] = unused_account();
+        assert!(OrcaAdapter.get_quote(1_000_000_000, &MINT_A, &infos(&mut accounts)).is_err());
+    }
+
+    /// Stands in for the Whirlpool program: moves `amount` out of the owner
+    /// account on the input side and pays a little more than the threshold
+    /// into the other one
+    fn mock_whirlpool(instruction: &Instruction, account_infos: &[AccountInfo], _signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        let amount = u64::from_le_bytes(instruction.data[8..16].try_into().unwrap());
+        let threshold = u64::from_le_bytes(instruction.data[16..24].try_into().unwrap());
+        let (input, output) = if instruction.data[41] == 1 {
+            (TOKEN_OWNER_ACCOUNT_A_INDEX, TOKEN_OWNER_ACCOUNT_B_INDEX)
+        } else {
+            (TOKEN_OWNER_ACCOUNT_B_INDEX, TOKEN_OWNER_ACCOUNT_A_INDEX)
+        };
+        adjust_token_balance(cpi_account(account_infos, &instruction.accounts[input])?, -(amount as i128))?;
+        adjust_token_balance(cpi_account(account_infos, &instruction.accounts[output])?, threshold as i128 + 7)
+    }
+
+    #[test]
+    fn reports_the_measured_output_in_either_direction() {
+        mock_program(ORCA_WHIRLPOOL_PROGRAM_ID, mock_whirlpool);
+        for (source_mint, tick_arrays) in [(MINT_A, DOWN), (MINT_B, UP)] {
+            let mut accounts = pool_accounts(tick_arrays);
+            accounts[TOKEN_OWNER_ACCOUNT_A_INDEX] = token_account(Pubkey::new_unique(), MINT_A, 5_000_000_000);
+            accounts[TOKEN_OWNER_ACCOUNT_B_INDEX] = token_account(Pubkey::new_unique(), MINT_B, 5_000_000_000);
+            let slice = infos(&mut accounts);
+
+            assert_eq!(OrcaAdapter.swap(1_000_000_000, 850_000_000, &source_mint, &slice, None).unwrap(), 850_000_007);
+            let destination = OrcaAdapter.destination_account(&source_mint, &slice).unwrap();
+            assert_eq!(read_token_account(destination).unwrap().1, 5_850_000_007);
+        }
+    }
+
+    #[test]
+    fn encodes_swap_args() {
+        let data = swap_instruction_data(500, 450, default_sqrt_price_limit(false), false);
+        assert_eq!(data.len(), 42);
+        assert_eq!(data[..8], SWAP_INSTRUCTION);
+        assert_eq!(u64::from_le_bytes(data[8..16].try_into().unwrap()), 500);
+        assert_eq!(u64::from_le_bytes(data[16..24].try_into().unwrap()), 450);
+        assert_eq!(u128::from_le_bytes(data[24..40].try_into().unwrap()), MAX_SQRT_PRICE_X64);
+        assert_eq!(data[40..], [1, 0]);
+
+        let data = swap_instruction_data(500, 450, default_sqrt_price_limit(true), true);
+        assert_eq!(u128::from_le_bytes(data[24..40].try_into().unwrap()), MIN_SQRT_PRICE_X64);
+        assert_eq!(data[40..], [1, 1]);
+    }
 }
@@ -0,0 +1,681 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
+use crate::state::DexType;
+
+/// Phoenix v1 Program ID
+pub const PHOENIX_PROGRAM_ID: Pubkey = solana_program::pubkey!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY");
+
+/// `PhoenixInstruction::Swap` tag
+const SWAP_INSTRUCTION: u8 = 0;
+
+/// `OrderPacket::ImmediateOrCancel` variant index
+const IMMEDIATE_OR_CANCEL: u8 = 2;
+
+/// `SelfTradeBehavior::CancelProvide`
+const CANCEL_PROVIDE: u8 = 1;
+
+// Positions in the swap account slice (see `PhoenixAdapter::swap`)
+const PROGRAM_INDEX: usize = 0;
+const MARKET_INDEX: usize = 2;
+const BASE_ACCOUNT_INDEX: usize = 4;
+const QUOTE_ACCOUNT_INDEX: usize = 5;
+const BASE_VAULT_INDEX: usize = 6;
+const QUOTE_VAULT_INDEX: usize = 7;
+const SWAP_ACCOUNTS_LEN: usize = 9;
+
+// `MarketHeader` field offsets
+const STATUS_OFFSET: usize = 8;
+const BIDS_SIZE_OFFSET: usize = 16;
+const ASKS_SIZE_OFFSET: usize = 24;
+const BASE_MINT_OFFSET: usize = 48;
+const BASE_VAULT_OFFSET: usize = 80;
+const BASE_LOT_SIZE_OFFSET: usize = 112;
+const QUOTE_MINT_OFFSET: usize = 128;
+const QUOTE_VAULT_OFFSET: usize = 160;
+const QUOTE_LOT_SIZE_OFFSET: usize = 192;
+
+// `FIFOMarket` field offsets, following the 576 byte header and 256 bytes of padding
+const BASE_LOTS_PER_BASE_UNIT_OFFSET: usize = 832;
+const TICK_SIZE_OFFSET: usize = 840;
+const TAKER_FEE_BPS_OFFSET: usize = 856;
+const BIDS_OFFSET: usize = 880;
+
+/// `MarketStatus::Active`, the only status that accepts taker orders
+const STATUS_ACTIVE: u64 = 1;
+
+/// Upper bound on book capacity accepted from account data
+const MAX_ORDERS_PER_SIDE: u64 = 1 << 16;
+
+// Sokoban `RedBlackTree` layout: sequence number, root, padding, then the node
+// allocator's size/bump/free list ahead of the node array. Nodes are 1-indexed
+// and hold four u32 registers (left, right, parent, color) before the entry.
+const ROOT_OFFSET: usize = 8;
+const TREE_HEADER_LEN: usize = 40;
+const NODE_LEN: usize = 64;
+const LEFT_REGISTER: usize = 0;
+const RIGHT_REGISTER: usize = 4;
+const PRICE_IN_TICKS_OFFSET: usize = 16;
+const NUM_BASE_LOTS_OFFSET: usize = 40;
+const LAST_VALID_SLOT_OFFSET: usize = 48;
+const LAST_VALID_TIMESTAMP_OFFSET: usize = 56;
+
+/// Taker side of a Phoenix order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoenixSide {
+    /// Spend quote for base, matching against the asks
+    Bid,
+    /// Sell base for quote, matching against the bids
+    Ask,
+}
+
+/// The parts of a Phoenix market needed to match a taker order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoenixMarketInfo {
+    pub status: u64,
+    pub bids_size: u64,
+    pub asks_size: u64,
+    pub base_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub base_lot_size: u64,
+    pub quote_mint: Pubkey,
+    pub quote_vault: Pubkey,
+    pub quote_lot_size: u64,
+    pub base_lots_per_base_unit: u64,
+    pub tick_size_in_quote_lots_per_base_unit: u64,
+    pub taker_fee_bps: u64,
+}
+
+impl PhoenixMarketInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, PHOENIX_PROGRAM_ID, ErrorCode::InvalidMarketAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= BIDS_OFFSET, ErrorCode::InvalidMarketAccount);
+        let market = Self {
+            status: read_u64(data, STATUS_OFFSET),
+            bids_size: read_u64(data, BIDS_SIZE_OFFSET),
+            asks_size: read_u64(data, ASKS_SIZE_OFFSET),
+            base_mint: read_pubkey(data, BASE_MINT_OFFSET),
+            base_vault: read_pubkey(data, BASE_VAULT_OFFSET),
+            base_lot_size: read_u64(data, BASE_LOT_SIZE_OFFSET),
+            quote_mint: read_pubkey(data, QUOTE_MINT_OFFSET),
+            quote_vault: read_pubkey(data, QUOTE_VAULT_OFFSET),
+            quote_lot_size: read_u64(data, QUOTE_LOT_SIZE_OFFSET),
+            base_lots_per_base_unit: read_u64(data, BASE_LOTS_PER_BASE_UNIT_OFFSET),
+            tick_size_in_quote_lots_per_base_unit: read_u64(data, TICK_SIZE_OFFSET),
+            taker_fee_bps: read_u64(data, TAKER_FEE_BPS_OFFSET),
+        };
+        require!(
+            market.base_lot_size > 0
+                && market.quote_lot_size > 0
+                && market.base_lots_per_base_unit > 0
+                && market.tick_size_in_quote_lots_per_base_unit > 0
+                && market.taker_fee_bps < 10000
+                && market.bids_size <= MAX_ORDERS_PER_SIDE
+                && market.asks_size <= MAX_ORDERS_PER_SIDE,
+            ErrorCode::InvalidMarketAccount
+        );
+        require!(data.len() >= market.asks_offset() + tree_len(market.asks_size), ErrorCode::InvalidMarketAccount);
+        Ok(market)
+    }
+
+    fn asks_offset(&self) -> usize {
+        BIDS_OFFSET + tree_len(self.bids_size)
+    }
+
+    /// Taking side for selling `source_mint`
+    pub fn side(&self, source_mint: &Pubkey) -> Result<PhoenixSide> {
+        if *source_mint == self.base_mint {
+            Ok(PhoenixSide::Ask)
+        } else if *source_mint == self.quote_mint {
+            Ok(PhoenixSide::Bid)
+        } else {
+            Err(ErrorCode::MintMismatch.into())
+        }
+    }
+
+    /// Resting orders a taker on `side` matches against, best price first
+    pub fn book<'a>(&self, data: &'a [u8], side: PhoenixSide) -> OrderBook<'a> {
+        let (offset, capacity) = match side {
+            PhoenixSide::Bid => (self.asks_offset(), self.asks_size),
+            PhoenixSide::Ask => (BIDS_OFFSET, self.bids_size),
+        };
+        OrderBook::new(&data[offset..offset + tree_len(capacity)], capacity)
+    }
+}
+
+/// A resting maker order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestingOrder {
+    pub price_in_ticks: u64,
+    pub num_base_lots: u64,
+    pub last_valid_slot: u64,
+    pub last_valid_unix_timestamp: u64,
+}
+
+impl RestingOrder {
+    /// Expired orders are skipped by the matching engine; zero means no expiry
+    pub fn is_expired(&self, slot: u64, now: i64) -> bool {
+        (self.last_valid_slot != 0 && self.last_valid_slot < slot)
+            || (self.last_valid_unix_timestamp != 0 && (self.last_valid_unix_timestamp as i64) < now)
+    }
+}
+
+/// In-order walk of one side of the book. Phoenix orders bid keys in reverse,
+/// so both trees yield the best price first.
+pub struct OrderBook<'a> {
+    tree: &'a [u8],
+    capacity: u64,
+    stack: Vec<u32>,
+    current: u32,
+    visited: u64,
+}
+
+impl<'a> OrderBook<'a> {
+    fn new(tree: &'a [u8], capacity: u64) -> Self {
+        let root = read_u32(tree, ROOT_OFFSET);
+        Self { tree, capacity, stack: Vec::new(), current: root, visited: 0 }
+    }
+
+    fn node(&self, index: u32) -> Option<&'a [u8]> {
+        if index == 0 || index as u64 > self.capacity {
+            return None;
+        }
+        let start = TREE_HEADER_LEN + (index as usize - 1) * NODE_LEN;
+        self.tree.get(start..start + NODE_LEN)
+    }
+}
+
+impl Iterator for OrderBook<'_> {
+    type Item = RestingOrder;
+
+    fn next(&mut self) -> Option<RestingOrder> {
+        // A corrupt tree ends the walk rather than looping
+        if self.visited >= self.capacity {
+            return None;
+        }
+        while self.current != 0 && self.stack.len() as u64 <= self.capacity {
+            let node = self.node(self.current)?;
+            self.stack.push(self.current);
+            self.current = read_u32(node, LEFT_REGISTER);
+        }
+        let index = self.stack.pop()?;
+        let node = self.node(index)?;
+        self.current = read_u32(node, RIGHT_REGISTER);
+        self.visited += 1;
+        Some(RestingOrder {
+            price_in_ticks: read_u64(node, PRICE_IN_TICKS_OFFSET),
+            num_base_lots: read_u64(node, NUM_BASE_LOTS_OFFSET),
+            last_valid_slot: read_u64(node, LAST_VALID_SLOT_OFFSET),
+            last_valid_unix_timestamp: read_u64(node, LAST_VALID_TIMESTAMP_OFFSET),
+        })
+    }
+}
+
+/// A quoted IOC fill, in lots and atoms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoenixQuote {
+    pub base_lots: u64,
+    /// Quote lots matched, before the taker fee
+    pub quote_lots: u64,
+    pub fee_lots: u64,
+    /// Atoms of the source token the fill consumes
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+/// Exact-input IOC quote, following `FIFOMarket::match_order`: budgets are
+/// tracked in adjusted quote lots (quote lots × base lots per base unit), buys
+/// reserve the taker fee up front, and the fee rounds up.
+pub fn quote_phoenix(
+    market: &PhoenixMarketInfo,
+    book: impl Iterator<Item = RestingOrder>,
+    amount_in: u64,
+    side: PhoenixSide,
+    slot: u64,
+    now: i64,
+) -> Result<PhoenixQuote> {
+    require!(amount_in > 0, PyroSwapError::InvalidAmount);
+
+    let tick_size = market.tick_size_in_quote_lots_per_base_unit as u128;
+    let base_lots_per_base_unit = market.base_lots_per_base_unit as u128;
+    let fee_bps = market.taker_fee_bps as u128;
+
+    let (mut base_lot_budget, mut adjusted_quote_lot_budget) = match side {
+        PhoenixSide::Ask => ((amount_in / market.base_lot_size) as u128, u128::MAX),
+        PhoenixSide::Bid => {
+            let quote_lots = (amount_in / market.quote_lot_size) as u128;
+            (u128::MAX, quote_lots * 10000 / (10000 + fee_bps) * base_lots_per_base_unit)
+        }
+    };
+    require!(base_lot_budget > 0 && adjusted_quote_lot_budget > 0, ErrorCode::AmountBelowLotSize);
+
+    let mut matched_base_lots = 0u128;
+    let mut matched_adjusted_quote_lots = 0u128;
+    for order in book {
+        if base_lot_budget == 0 || adjusted_quote_lot_budget == 0 {
+            break;
+        }
+        if order.is_expired(slot, now) {
+            continue;
+        }
+
+        let price = order.price_in_ticks as u128 * tick_size;
+        let resting_base_lots = order.num_base_lots as u128;
+        let quoted = price.saturating_mul(resting_base_lots);
+        if resting_base_lots <= base_lot_budget && quoted <= adjusted_quote_lot_budget {
+            matched_base_lots += resting_base_lots;
+            matched_adjusted_quote_lots += quoted;
+            base_lot_budget -= resting_base_lots;
+            adjusted_quote_lot_budget -= quoted;
+        } else {
+            // Partial fill exhausts the taker
+            let base_lots = base_lot_budget.min(adjusted_quote_lot_budget / price.max(1));
+            matched_base_lots += base_lots;
+            matched_adjusted_quote_lots += price * base_lots;
+            break;
+        }
+    }
+    require!(matched_base_lots > 0, PyroSwapError::InsufficientLiquidity);
+
+    let to_u64 = |value: u128| u64::try_from(value).map_err(|_| error!(PyroSwapError::MathOverflow));
+    let base_lots = to_u64(matched_base_lots)?;
+    let base_atoms = to_u64(matched_base_lots * market.base_lot_size as u128)?;
+
+    // The buyer pays for rounding, the seller absorbs it
+    let quote_lots = match side {
+        PhoenixSide::Bid => matched_adjusted_quote_lots.div_ceil(base_lots_per_base_unit),
+        PhoenixSide::Ask => matched_adjusted_quote_lots / base_lots_per_base_unit,
+    };
+    let fee_lots = (quote_lots * fee_bps).div_ceil(10000);
+
+    match side {
+        PhoenixSide::Bid => Ok(PhoenixQuote {
+            base_lots,
+            quote_lots: to_u64(quote_lots)?,
+            fee_lots: to_u64(fee_lots)?,
+            amount_in: to_u64((quote_lots + fee_lots) * market.quote_lot_size as u128)?,
+            amount_out: base_atoms,
+        }),
+        PhoenixSide::Ask => Ok(PhoenixQuote {
+            base_lots,
+            quote_lots: to_u64(quote_lots)?,
+            fee_lots: to_u64(fee_lots)?,
+            amount_in: base_atoms,
+            amount_out: to_u64((quote_lots - fee_lots) * market.quote_lot_size as u128)?,
+        }),
+    }
+}
+
+/// `Swap` with an `ImmediateOrCancel` packet: no price limit, the whole input
+/// as the budget and the minimum output as the fill floor, both in lots
+pub fn ioc_instruction_data(
+    side: PhoenixSide,
+    num_base_lots: u64,
+    num_quote_lots: u64,
+    min_base_lots_to_fill: u64,
+    min_quote_lots_to_fill: u64,
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(57);
+    data.push(SWAP_INSTRUCTION);
+    data.push(IMMEDIATE_OR_CANCEL);
+    data.push(match side {
+        PhoenixSide::Bid => 0,
+        PhoenixSide::Ask => 1,
+    });
+    data.push(0); // price_in_ticks: None
+    data.extend_from_slice(&num_base_lots.to_le_bytes());
+    data.extend_from_slice(&num_quote_lots.to_le_bytes());
+    data.extend_from_slice(&min_base_lots_to_fill.to_le_bytes());
+    data.extend_from_slice(&min_quote_lots_to_fill.to_le_bytes());
+    data.push(CANCEL_PROVIDE);
+    data.push(0); // match_limit: None
+    data.extend_from_slice(&0u128.to_le_bytes()); // client_order_id
+    data.push(0); // use_only_deposited_funds
+    data.push(0); // last_valid_slot: None
+    data.push(0); // last_valid_unix_timestamp_in_seconds: None
+    data
+}
+
+pub struct PhoenixAdapter;
+
+impl DexAdapter for PhoenixAdapter {
+    fn dex_type(&self) -> DexType {
+        DexType::Phoenix
+    }
+
+    fn program_id(&self) -> Pubkey {
+        PHOENIX_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() >= SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, PROGRAM_INDEX, &PHOENIX_PROGRAM_ID)?;
+
+        let market = PhoenixMarketInfo::try_from_account(&accounts[MARKET_INDEX])?;
+        market.side(source_mint)?;
+        require_keys_eq!(*accounts[BASE_VAULT_INDEX].key, market.base_vault, ErrorCode::InvalidMarketAccount);
+        require_keys_eq!(*accounts[QUOTE_VAULT_INDEX].key, market.quote_vault, ErrorCode::InvalidMarketAccount);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
+        amount_in: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<DexQuote> {
+        self.validate_accounts(source_mint, accounts)?;
+
+        let market_account = &accounts[MARKET_INDEX];
+        let market = PhoenixMarketInfo::try_from_account(market_account)?;
+        require!(market.status == STATUS_ACTIVE, ErrorCode::MarketNotActive);
+        let side = market.side(source_mint)?;
+
+        let clock = Clock::get()?;
+        let data = market_account.try_borrow_data()?;
+        let quote = quote_phoenix(&market, market.book(&data, side), amount_in, side, clock.slot, clock.unix_timestamp)?;
+
+        // Output at the best price with unlimited depth, for price impact
+        let spot_out = market
+            .book(&data, side)
+            .find(|order| !order.is_expired(clock.slot, clock.unix_timestamp))
+            .map(|best| RestingOrder { num_base_lots: u64::MAX, ..best })
+            .and_then(|best| {
+                quote_phoenix(&market, std::iter::once(best), amount_in, side, clock.slot, clock.unix_timestamp).ok()
+            })
+            .map_or(quote.amount_out, |spot| spot.amount_out);
+
+        Ok(DexQuote {
+            dex_type: DexType::Phoenix,
+            amount_out: quote.amount_out,
+            price_impact_bps: calculate_price_impact(amount_in, quote.amount_out, spot_out),
+            fee_bps: market.taker_fee_bps as u16,
+        })
+    }
+
+    fn swap<'info>(
+        &self,
+        amount_in: u64,
+        min_amount_out: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        signer_seeds: Option<&[&[&[u8]]]>,
+    ) -> Result<u64> {
+        // Phoenix swap CPI
+        // accounts expected:
+        // 0. Phoenix program
+        // 1. Log authority
+        // 2. Market
+        // 3. Trader (signer)
+        // 4. Trader base token account
+        // 5. Trader quote token account
+        // 6. Base vault
+        // 7. Quote vault
+        // 8. Token program
+
+        self.validate_accounts(source_mint, accounts)?;
+
+        let market = PhoenixMarketInfo::try_from_account(&accounts[MARKET_INDEX])?;
+        require!(market.status == STATUS_ACTIVE, ErrorCode::MarketNotActive);
+
+        let (base_mint, base_before) = read_token_account(&accounts[BASE_ACCOUNT_INDEX])?;
+        let (quote_mint, quote_before) = read_token_account(&accounts[QUOTE_ACCOUNT_INDEX])?;
+        require_keys_eq!(base_mint, market.base_mint, ErrorCode::MintMismatch);
+        require_keys_eq!(quote_mint, market.quote_mint, ErrorCode::MintMismatch);
+
+        // Convert atoms to lots: the input rounds down, the floor rounds up
+        let side = market.side(source_mint)?;
+        let instruction_data = match side {
+            PhoenixSide::Ask => {
+                let num_base_lots = amount_in / market.base_lot_size;
+                require!(num_base_lots > 0, ErrorCode::AmountBelowLotSize);
+                ioc_instruction_data(side, num_base_lots, 0, 0, min_amount_out.div_ceil(market.quote_lot_size))
+            }
+            PhoenixSide::Bid => {
+                let num_quote_lots = amount_in / market.quote_lot_size;
+                require!(num_quote_lots > 0, ErrorCode::AmountBelowLotSize);
+                ioc_instruction_data(side, 0, num_quote_lots, min_amount_out.div_ceil(market.base_lot_size), 0)
+            }
+        };
+
+        let instruction = anchor_lang::solana_program::instruction::Instruction {
+            program_id: PHOENIX_PROGRAM_ID,
+            accounts: accounts[..SWAP_ACCOUNTS_LEN].iter().map(|a| {
+                anchor_lang::solana_program::instruction::AccountMeta {
+                    pubkey: *a.key,
+                    is_signer: a.is_signer,
+                    is_writable: a.is_writable,
+                }
+            }).collect(),
+            data: instruction_data,
+        };
+
+        if let Some(seeds) = signer_seeds {
+            anchor_lang::solana_program::program::invoke_signed(&instruction, accounts, seeds)?;
+        } else {
+            invoke(&instruction, accounts)?;
+        }
+
+        // Lot rounding leaves dust, so report what actually arrived
+        let amount_out = match side {
+            PhoenixSide::Ask => read_token_account(&accounts[QUOTE_ACCOUNT_INDEX])?.1.saturating_sub(quote_before),
+            PhoenixSide::Bid => read_token_account(&accounts[BASE_ACCOUNT_INDEX])?.1.saturating_sub(base_before),
+        };
+        require!(amount_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+
+        Ok(amount_out)
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() >= SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        let market = PhoenixMarketInfo::try_from_account(&accounts[MARKET_INDEX])?;
+        let index = match market.side(source_mint)? {
+            PhoenixSide::Ask => QUOTE_ACCOUNT_INDEX,
+            PhoenixSide::Bid => BASE_ACCOUNT_INDEX,
+        };
+        Ok(&accounts[index])
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() >= SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[MARKET_INDEX])
+    }
+}
+
+fn tree_len(capacity: u64) -> usize {
+    TREE_HEADER_LEN + capacity as usize * NODE_LEN
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid number of accounts provided")]
+    InvalidAccountsLength,
+    #[msg("Account is not a Phoenix market")]
+    InvalidMarketAccount,
+    #[msg("Phoenix market is not accepting taker orders")]
+    MarketNotActive,
+    #[msg("Source mint is not traded on this market")]
+    MintMismatch,
+    #[msg("Amount is smaller than one lot")]
+    AmountBelowLotSize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+
+    const SLOT: u64 = 100;
+    const NOW: i64 = 2_000;
+
+    fn order(price_in_ticks: u64, num_base_lots: u64) -> RestingOrder {
+        RestingOrder { price_in_ticks, num_base_lots, last_valid_slot: 0, last_valid_unix_timestamp: 0 }
+    }
+
+    /// Asks best-first, led by an order whose slot has passed
+    fn asks() -> Vec<RestingOrder> {
+        vec![
+            RestingOrder { last_valid_slot: 10, ..order(149_000, 1_000) },
+            order(150_000, 2_000),
+            order(150_100, 5_000),
+            order(150_250, 10_000),
+        ]
+    }
+
+    /// Bids best-first, led by an order whose timestamp has passed
+    fn bids() -> Vec<RestingOrder> {
+        vec![
+            RestingOrder { last_valid_unix_timestamp: 1_000, ..order(150_500, 1_000) },
+            order(149_900, 3_000),
+            order(149_800, 4_000),
+            order(149_500, 20_000),
+        ]
+    }
+
+    /// Lays `orders` out as a balanced tree, numbering nodes in pre-order so
+    /// the walk has to follow the registers rather than the node indices
+    fn write_tree(data: &mut [u8], offset: usize, orders: &[RestingOrder]) {
+        fn build(data: &mut [u8], offset: usize, orders: &[RestingOrder], next: &mut u32) -> u32 {
+            if orders.is_empty() {
+                return 0;
+            }
+            let mid = orders.len() / 2;
+            let index = *next;
+            *next += 1;
+            let left = build(data, offset, &orders[..mid], next);
+            let right = build(data, offset, &orders[mid + 1..], next);
+
+            let node = offset + TREE_HEADER_LEN + (index as usize - 1) * NODE_LEN;
+            data[node + LEFT_REGISTER..node + LEFT_REGISTER + 4].copy_from_slice(&left.to_le_bytes());
+            data[node + RIGHT_REGISTER..node + RIGHT_REGISTER + 4].copy_from_slice(&right.to_le_bytes());
+            write_u64(data, node + PRICE_IN_TICKS_OFFSET, orders[mid].price_in_ticks);
+            write_u64(data, node + NUM_BASE_LOTS_OFFSET, orders[mid].num_base_lots);
+            write_u64(data, node + LAST_VALID_SLOT_OFFSET, orders[mid].last_valid_slot);
+            write_u64(data, node + LAST_VALID_TIMESTAMP_OFFSET, orders[mid].last_valid_unix_timestamp);
+            index
+        }
+        let root = build(data, offset, orders, &mut 1);
+        data[offset + ROOT_OFFSET..offset + ROOT_OFFSET + 4].copy_from_slice(&root.to_le_bytes());
+    }
+
+    /// A SOL/USDC style market: 0.001 SOL lots, 1e-6 USDC ticks, 2 bps taker fee
+    fn market_data(base_mint: &Pubkey, quote_mint: &Pubkey) -> Vec<u8> {
+        let capacity = 8;
+        let mut data = vec![0u8; BIDS_OFFSET + 2 * tree_len(capacity)];
+        write_u64(&mut data, STATUS_OFFSET, STATUS_ACTIVE);
+        write_u64(&mut data, BIDS_SIZE_OFFSET, capacity);
+        write_u64(&mut data, ASKS_SIZE_OFFSET, capacity);
+        write_pubkey(&mut data, BASE_MINT_OFFSET, base_mint);
+        write_u64(&mut data, BASE_LOT_SIZE_OFFSET, 1_000_000);
+        write_pubkey(&mut data, QUOTE_MINT_OFFSET, quote_mint);
+        write_u64(&mut data, QUOTE_LOT_SIZE_OFFSET, 1);
+        write_u64(&mut data, BASE_LOTS_PER_BASE_UNIT_OFFSET, 1_000);
+        write_u64(&mut data, TICK_SIZE_OFFSET, 1_000);
+        write_u64(&mut data, TAKER_FEE_BPS_OFFSET, 2);
+        write_tree(&mut data, BIDS_OFFSET, &bids());
+        write_tree(&mut data, BIDS_OFFSET + tree_len(capacity), &asks());
+        data
+    }
+
+    fn quote(data: &[u8], amount_in: u64, side: PhoenixSide) -> Result<PhoenixQuote> {
+        let market = PhoenixMarketInfo::try_from_slice(data).unwrap();
+        quote_phoenix(&market, market.book(data, side), amount_in, side, SLOT, NOW)
+    }
+
+    #[test]
+    fn walks_the_book_best_price_first() {
+        let data = market_data(&Pubkey::new_unique(), &Pubkey::new_unique());
+        let market = PhoenixMarketInfo::try_from_slice(&data).unwrap();
+        assert_eq!(market.book(&data, PhoenixSide::Bid).collect::<Vec<_>>(), asks());
+        assert_eq!(market.book(&data, PhoenixSide::Ask).collect::<Vec<_>>(), bids());
+    }
+
+    #[test]
+    fn quotes_buy_with_fee_reserved_from_the_budget() {
+        let data = market_data(&Pubkey::new_unique(), &Pubkey::new_unique());
+        // 500 USDC skips the stale ask, clears the first level and part of the second
+        let result = quote(&data, 500_000_000, PhoenixSide::Bid).unwrap();
+        assert_eq!(result.base_lots, 3_331);
+        assert_eq!(result.quote_lots, 499_783_100);
+        assert_eq!(result.fee_lots, 99_957);
+        assert_eq!(result.amount_in, 499_883_057);
+        assert_eq!(result.amount_out, 3_331_000_000);
+
+        let result = quote(&data, 3_000_000_000, PhoenixSide::Bid).unwrap();
+        assert_eq!(result.amount_out, 17_000_000_000);
+        assert_eq!(result.amount_in, 2_553_510_600);
+    }
+
+    #[test]
+    fn quotes_sell_with_fee_taken_from_proceeds() {
+        let data = market_data(&Pubkey::new_unique(), &Pubkey::new_unique());
+        // 5 SOL skips the expired bid and fills across two levels
+        let result = quote(&data, 5_000_000_000, PhoenixSide::Ask).unwrap();
+        assert_eq!(result.quote_lots, 749_300_000);
+        assert_eq!(result.fee_lots, 149_860);
+        assert_eq!(result.amount_out, 749_150_140);
+
+        // More than the book holds fills what is there
+        let result = quote(&data, 30_000_000_000, PhoenixSide::Ask).unwrap();
+        assert_eq!(result.base_lots, 27_000);
+        assert_eq!(result.amount_in, 27_000_000_000);
+        assert_eq!(result.amount_out, 4_038_092_220);
+    }
+
+    #[test]
+    fn rejects_amounts_below_one_lot_and_empty_books() {
+        let data = market_data(&Pubkey::new_unique(), &Pubkey::new_unique());
+        assert!(quote(&data, 999_999, PhoenixSide::Ask).is_err());
+
+        let market = PhoenixMarketInfo::try_from_slice(&data).unwrap();
+        let stale = vec![RestingOrder { last_valid_slot: 1, ..order(150_000, 1_000) }];
+        assert!(quote_phoenix(&market, stale.into_iter(), 1_000_000_000, PhoenixSide::Bid, SLOT, NOW).is_err());
+    }
+
+    #[test]
+    fn side_follows_source_mint() {
+        let (base_mint, quote_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut account = FixtureAccount::new(Pubkey::new_unique(), PHOENIX_PROGRAM_ID, market_data(&base_mint, &quote_mint));
+        let market = PhoenixMarketInfo::try_from_account(&account.info()).unwrap();
+        assert_eq!(market.side(&base_mint).unwrap(), PhoenixSide::Ask);
+        assert_eq!(market.side(&quote_mint).unwrap(), PhoenixSide::Bid);
+        assert!(market.side(&Pubkey::new_unique()).is_err());
+
+        let mut foreign = FixtureAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), market_data(&base_mint, &quote_mint));
+        assert!(PhoenixMarketInfo::try_from_account(&foreign.info()).is_err());
+    }
+
+    #[test]
+    fn encodes_ioc_packet() {
+        let data = ioc_instruction_data(PhoenixSide::Ask, 5_000, 0, 0, 749_000_000);
+        assert_eq!(data.len(), 57);
+        assert_eq!(&data[..4], &[SWAP_INSTRUCTION, IMMEDIATE_OR_CANCEL, 1, 0]);
+        assert_eq!(data[4..12], 5_000u64.to_le_bytes());
+        assert_eq!(data[12..20], 0u64.to_le_bytes());
+        assert_eq!(data[28..36], 749_000_000u64.to_le_bytes());
+        assert_eq!(data[36], CANCEL_PROVIDE);
+        assert!(data[37..].iter().all(|b| *b == 0));
+    }
+}
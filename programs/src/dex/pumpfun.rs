@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke;
-use crate::dex::{DexQuote, DexAdapter};
-use crate::state::DexType;
+use anchor_spl::token_interface::{self, Approve, Revoke, SyncNative};
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
+use crate::state::{DexType, MigrationRoute};
 
 /// Pump.fun Program ID
 pub const PUMPFUN_PROGRAM_ID: Pubkey = solana_program::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
@@ -12,39 +14,237 @@ const BUY_INSTRUCTION: [u8; 8] = [0x66, 0x06, 0x3d, 0x12, 0x01, 0xda, 0xeb, 0xea
 /// Pump.fun sell instruction discriminator
 const SELL_INSTRUCTION: [u8; 8] = [0x33, 0xe6, 0x85, 0xa4, 0x01, 0x7f, 0x83, 0xad];
 
+/// Native SOL mint: the quote side of every bonding curve
+pub const NATIVE_MINT: Pubkey = solana_program::pubkey!("So11111111111111111111111111111111111111112");
+
+/// Seed of the per-mint bonding curve PDA
+pub const BONDING_CURVE_SEED: &[u8] = b"bonding-curve";
+
+// Positions in the swap account slice (see `PumpFunAdapter::swap`)
+const GLOBAL_INDEX: usize = 0;
+const MINT_INDEX: usize = 2;
+const BONDING_CURVE_INDEX: usize = 3;
+const USER_TOKEN_ACCOUNT_INDEX: usize = 5;
+const USER_INDEX: usize = 6;
+/// The curve program's event CPI takes the program as an instruction account
+const PROGRAM_INDEX: usize = 11;
+
+// `Global` field offsets (after the 8 byte Anchor discriminator)
+const GLOBAL_MIN_LEN: usize = 113;
+const FEE_BASIS_POINTS_OFFSET: usize = 105;
+
+// `BondingCurve` field offsets; newer curves append a creator after `complete`
+const BONDING_CURVE_MIN_LEN: usize = 49;
+const VIRTUAL_TOKEN_RESERVES_OFFSET: usize = 8;
+const VIRTUAL_SOL_RESERVES_OFFSET: usize = 16;
+const REAL_TOKEN_RESERVES_OFFSET: usize = 24;
+const REAL_SOL_RESERVES_OFFSET: usize = 32;
+const COMPLETE_OFFSET: usize = 48;
+
+/// Reserves of a pump.fun bonding curve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BondingCurveInfo {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    /// Set once the curve has migrated; it no longer trades
+    pub complete: bool,
+}
+
+impl BondingCurveInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, PUMPFUN_PROGRAM_ID, ErrorCode::InvalidCurveAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= BONDING_CURVE_MIN_LEN, ErrorCode::InvalidCurveAccount);
+        Ok(Self {
+            virtual_token_reserves: read_u64(data, VIRTUAL_TOKEN_RESERVES_OFFSET),
+            virtual_sol_reserves: read_u64(data, VIRTUAL_SOL_RESERVES_OFFSET),
+            real_token_reserves: read_u64(data, REAL_TOKEN_RESERVES_OFFSET),
+            real_sol_reserves: read_u64(data, REAL_SOL_RESERVES_OFFSET),
+            complete: data[COMPLETE_OFFSET] != 0,
+        })
+    }
+
+    /// Tokens bought with `sol_amount` of lamports after fees, as the SDK computes it
+    pub fn buy_tokens_for_sol(&self, sol_amount: u64) -> Result<u64> {
+        if sol_amount == 0 {
+            return Ok(0);
+        }
+        let invariant = self.virtual_sol_reserves as u128 * self.virtual_token_reserves as u128;
+        let new_sol_reserves = self.virtual_sol_reserves as u128 + sol_amount as u128;
+        let new_token_reserves = invariant / new_sol_reserves + 1;
+        let tokens = (self.virtual_token_reserves as u128).saturating_sub(new_token_reserves);
+        Ok(tokens.min(self.real_token_reserves as u128) as u64)
+    }
+
+    /// Lamports the program charges for `token_amount`, before fees
+    pub fn buy_cost(&self, token_amount: u64) -> Result<u64> {
+        require!(token_amount < self.virtual_token_reserves, PyroSwapError::InsufficientLiquidity);
+        let cost = token_amount as u128 * self.virtual_sol_reserves as u128
+            / (self.virtual_token_reserves - token_amount) as u128
+            + 1;
+        Ok(u64::try_from(cost).map_err(|_| PyroSwapError::MathOverflow)?)
+    }
+
+    /// Lamports received for selling `token_amount`, before fees
+    pub fn sell_proceeds(&self, token_amount: u64) -> u64 {
+        let proceeds = token_amount as u128 * self.virtual_sol_reserves as u128
+            / (self.virtual_token_reserves as u128 + token_amount as u128);
+        proceeds.min(self.real_sol_reserves as u128) as u64
+    }
+}
+
+/// A quoted bonding-curve trade and the instruction args that execute it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PumpFunQuote {
+    pub is_buy: bool,
+    /// Tokens received on a buy, lamports received on a sell
+    pub amount_out: u64,
+    /// Lamports paid to the fee recipient
+    pub fee: u64,
+}
+
+/// Exact-input quote: lamports in for a buy, tokens in for a sell
+pub fn quote_curve(curve: &BondingCurveInfo, fee_basis_points: u64, amount_in: u64, is_buy: bool) -> Result<PumpFunQuote> {
+    require!(!curve.complete, ErrorCode::CurveComplete);
+    require!(amount_in > 0, PyroSwapError::InvalidAmount);
+    require!(fee_basis_points < 10000, ErrorCode::InvalidCurveAccount);
+
+    if is_buy {
+        // The program charges its fee on top of the curve cost
+        let sol_for_curve = (amount_in as u128 * 10000 / (10000 + fee_basis_points as u128)) as u64;
+        let mut tokens = curve.buy_tokens_for_sol(sol_for_curve)?;
+        let mut cost = curve.buy_cost(tokens)?;
+        let mut fee = cost * fee_basis_points / 10000;
+        // Rounding can leave the total a lamport over budget
+        while tokens > 0 && cost + fee > amount_in {
+            tokens -= 1;
+            cost = curve.buy_cost(tokens)?;
+            fee = cost * fee_basis_points / 10000;
+        }
+        require!(tokens > 0, PyroSwapError::InsufficientLiquidity);
+        Ok(PumpFunQuote { is_buy, amount_out: tokens, fee })
+    } else {
+        let proceeds = curve.sell_proceeds(amount_in);
+        let fee = proceeds * fee_basis_points / 10000;
+        Ok(PumpFunQuote { is_buy, amount_out: proceeds - fee, fee })
+    }
+}
+
+/// Buys spend SOL for the curve's token; sells go the other way
+pub fn buys_token(curve_mint: &Pubkey, source_mint: &Pubkey) -> Result<bool> {
+    if *source_mint == NATIVE_MINT {
+        Ok(true)
+    } else if source_mint == curve_mint {
+        Ok(false)
+    } else {
+        Err(ErrorCode::MintMismatch.into())
+    }
+}
+
+/// The token side of a pair traded on a bonding curve
+pub fn curve_token_mint(input_mint: &Pubkey, output_mint: &Pubkey) -> Pubkey {
+    if *input_mint == NATIVE_MINT { *output_mint } else { *input_mint }
+}
+
+/// Address of the bonding curve pump.fun derives for `mint`
+pub fn bonding_curve_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[BONDING_CURVE_SEED, mint.as_ref()], &PUMPFUN_PROGRAM_ID).0
+}
+
+/// The registered pool a position on `token_mint`'s curve trades on once
+/// the curve completes, or `None` while the curve still trades. The swap
+/// slice must name that pool.
+pub fn migration_target<'r>(
+    curve: &BondingCurveInfo,
+    route: Option<&'r MigrationRoute>,
+    token_mint: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<Option<&'r MigrationRoute>> {
+    if !curve.complete {
+        return Ok(None);
+    }
+    let route = route.ok_or(PyroSwapError::MigrationRouteMissing)?;
+    require_keys_eq!(route.mint, *token_mint, PyroSwapError::InvalidMigrationRoute);
+    require_keys_eq!(
+        *crate::dex::adapter_for(route.dex_type).pool_account(accounts)?.key,
+        route.pool,
+        PyroSwapError::InvalidMigrationRoute
+    );
+    Ok(Some(route))
+}
+
+/// `buy(amount, max_sol_cost)` or `sell(amount, min_sol_output)`: both take a
+/// token amount first
+pub fn curve_instruction_data(is_buy: bool, token_amount: u64, sol_limit: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(if is_buy { &BUY_INSTRUCTION } else { &SELL_INSTRUCTION });
+    data.extend_from_slice(&token_amount.to_le_bytes());
+    data.extend_from_slice(&sol_limit.to_le_bytes());
+    data
+}
+
+
 pub struct PumpFunAdapter;
 
 impl DexAdapter for PumpFunAdapter {
-    fn get_quote(
+    fn dex_type(&self) -> DexType {
+        DexType::PumpFun
+    }
+
+    fn program_id(&self) -> Pubkey {
+        PUMPFUN_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, PROGRAM_INDEX, &PUMPFUN_PROGRAM_ID)?;
+
+        let mint = accounts[MINT_INDEX].key;
+        buys_token(mint, source_mint)?;
+        require_keys_eq!(*accounts[BONDING_CURVE_INDEX].key, bonding_curve_address(mint), ErrorCode::InvalidCurveAccount);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
         amount_in: u64,
-        _input_decimals: u8,
-        _output_decimals: u8,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
     ) -> Result<DexQuote> {
-        // Pump.fun uses a bonding curve model
-        // Fee is typically 1% on buys and sells
-        let fee_bps = 100u16; // 1% fee
-        
-        let amount_after_fee = amount_in
-            .checked_mul(10000 - fee_bps as u64)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap();
-        
-        // Bonding curve provides different pricing
-        // Price impact can be higher on smaller pools
-        let amount_out = amount_after_fee;
-        
+        self.validate_accounts(source_mint, accounts)?;
+
+        let fee_basis_points = read_fee_basis_points(&accounts[GLOBAL_INDEX])?;
+        let curve = BondingCurveInfo::try_from_account(&accounts[BONDING_CURVE_INDEX])?;
+        let is_buy = buys_token(accounts[MINT_INDEX].key, source_mint)?;
+        let quote = quote_curve(&curve, fee_basis_points, amount_in, is_buy)?;
+
+        // Output at the current virtual price after fees, for price impact
+        let after_fee = amount_in as u128 * (10000 - fee_basis_points as u128) / 10000;
+        let spot_out = if is_buy {
+            after_fee * curve.virtual_token_reserves as u128 / curve.virtual_sol_reserves.max(1) as u128
+        } else {
+            after_fee * curve.virtual_sol_reserves as u128 / curve.virtual_token_reserves.max(1) as u128
+        };
+
         Ok(DexQuote {
             dex_type: DexType::PumpFun,
-            amount_out,
-            price_impact_bps: 50, // 0.5% estimated impact (higher for bonding curves)
-            fee_bps,
+            amount_out: quote.amount_out,
+            price_impact_bps: calculate_price_impact(amount_in, quote.amount_out, spot_out.min(u64::MAX as u128) as u64),
+            fee_bps: fee_basis_points as u16,
         })
     }
 
     fn swap<'info>(
+        &self,
         amount_in: u64,
         min_amount_out: u64,
+        source_mint: &Pubkey,
         accounts: &[AccountInfo<'info>],
         signer_seeds: Option<&[&[&[u8]]]>,
     ) -> Result<u64> {
@@ -56,31 +256,47 @@ impl DexAdapter for PumpFunAdapter {
         // 3. Bonding curve
         // 4. Associated bonding curve
         // 5. User token account
-        // 6. User SOL account
+        // 6. User (pays or receives SOL)
         // 7. System program
-        // 8. Token program
-        // 9. Rent
+        // 8. Token program (buy) / Associated token program (sell)
+        // 9. Rent (buy) / Token program (sell)
         // 10. Event authority
         // 11. Program
 
-        require!(accounts.len() >= 12, ErrorCode::InvalidAccountsLength);
+        self.validate_accounts(source_mint, accounts)?;
 
-        // Determine if buying or selling based on account setup
-        // For now, assume buying (SOL -> Token)
-        let instruction_discriminator = BUY_INSTRUCTION;
+        let is_buy = buys_token(accounts[MINT_INDEX].key, source_mint)?;
+        let instruction_data = if is_buy {
+            // Buys name the token amount, so size it from the curve and let
+            // the whole input be the SOL ceiling
+            let fee_basis_points = read_fee_basis_points(&accounts[GLOBAL_INDEX])?;
+            let curve = BondingCurveInfo::try_from_account(&accounts[BONDING_CURVE_INDEX])?;
+            let quote = quote_curve(&curve, fee_basis_points, amount_in, true)?;
+            require!(quote.amount_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+            curve_instruction_data(true, quote.amount_out, amount_in)
+        } else {
+            let curve = BondingCurveInfo::try_from_account(&accounts[BONDING_CURVE_INDEX])?;
+            require!(!curve.complete, ErrorCode::CurveComplete);
+            curve_instruction_data(false, amount_in, min_amount_out)
+        };
 
-        // Build Pump.fun instruction
-        let mut instruction_data = Vec::with_capacity(25);
-        instruction_data.extend_from_slice(&instruction_discriminator);
-        instruction_data.extend_from_slice(&amount_in.to_le_bytes());
-        instruction_data.extend_from_slice(&min_amount_out.to_le_bytes());
+        // Buys land tokens in the user's token account, sells pay the user lamports
+        let output_balance = |accounts: &[AccountInfo<'info>]| -> Result<u64> {
+            if is_buy {
+                Ok(read_token_account(&accounts[USER_TOKEN_ACCOUNT_INDEX])?.1)
+            } else {
+                Ok(accounts[USER_INDEX].lamports())
+            }
+        };
+        let balance_before = output_balance(accounts)?;
 
         let instruction = anchor_lang::solana_program::instruction::Instruction {
             program_id: PUMPFUN_PROGRAM_ID,
-            accounts: accounts.iter().map(|a| {
+            accounts: accounts.iter().enumerate().map(|(i, a)| {
                 anchor_lang::solana_program::instruction::AccountMeta {
                     pubkey: *a.key,
-                    is_signer: a.is_signer,
+                    // The user may be a PDA signing through `signer_seeds`
+                    is_signer: a.is_signer || i == USER_INDEX,
                     is_writable: a.is_writable,
                 }
             }).collect(),
@@ -93,24 +309,323 @@ impl DexAdapter for PumpFunAdapter {
             invoke(&instruction, accounts)?;
         }
 
-        Ok(min_amount_out)
+        let amount_out = output_balance(accounts)?
+            .checked_sub(balance_before)
+            .ok_or(PyroSwapError::MathOverflow)?;
+        require!(amount_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+        Ok(amount_out)
     }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        // Sells pay out lamports to the user, which only shows up as a token
+        // balance once a wSOL user is synced (see `sell_into_output_vault`)
+        if buys_token(accounts[MINT_INDEX].key, source_mint)? {
+            Ok(&accounts[USER_TOKEN_ACCOUNT_INDEX])
+        } else {
+            Ok(&accounts[USER_INDEX])
+        }
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[BONDING_CURVE_INDEX])
+    }
+}
+
+/// Sell a position vault's curve tokens into its wSOL output vault.
+///
+/// The curve pays a sell out as lamports to the signing `user`, who must also
+/// be able to move the tokens. The vault is its own token-program-owned
+/// authority, so lamports paid to it could never be moved on. Instead the
+/// vault delegates `amount_in` to the output vault, which signs the sell as
+/// the user, is credited the lamports directly and syncs them into its
+/// wrapped balance. `signer_seeds` must sign for both vaults.
+#[allow(clippy::too_many_arguments)]
+pub fn sell_into_output_vault<'a, 'info>(
+    token_program: &AccountInfo<'a>,
+    output_token_program: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    output_vault: &AccountInfo<'a>,
+    amount_in: u64,
+    min_amount_out: u64,
+    source_mint: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+    require!(!buys_token(accounts[MINT_INDEX].key, source_mint)?, ErrorCode::MintMismatch);
+    require_keys_eq!(*accounts[USER_TOKEN_ACCOUNT_INDEX].key, *vault.key, PyroSwapError::InvalidTokenAccount);
+    require_keys_eq!(*accounts[USER_INDEX].key, *output_vault.key, PyroSwapError::InvalidTokenAccount);
+
+    token_interface::approve(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Approve { to: vault.clone(), delegate: output_vault.clone(), authority: vault.clone() },
+            signer_seeds,
+        ),
+        amount_in,
+    )?;
+    let amount_out = PumpFunAdapter.swap(amount_in, min_amount_out, source_mint, accounts, Some(signer_seeds))?;
+    token_interface::sync_native(CpiContext::new(
+        output_token_program.clone(),
+        SyncNative { account: output_vault.clone() },
+    ))?;
+    // The curve takes exactly `amount_in`, but don't leave a stale allowance behind
+    token_interface::revoke(CpiContext::new_with_signer(
+        token_program.clone(),
+        Revoke { source: vault.clone(), authority: vault.clone() },
+        signer_seeds,
+    ))?;
+    Ok(amount_out)
 }
 
-/// Helper to get Pump.fun bonding curve info
+/// Helper to get Pump.fun bonding curve info:
+/// `(virtual_token_reserves, virtual_sol_reserves, real_token_reserves)`
 pub fn get_pumpfun_curve_info<'info>(
     bonding_curve_account: &AccountInfo<'info>,
 ) -> Result<(u64, u64, u64)> {
-    // In production, parse bonding curve account to get:
-    // - virtual_token_reserves
-    // - virtual_sol_reserves
-    // - real_token_reserves
-    // For now, return placeholder values
-    Ok((1_000_000_000_000, 30_000_000_000, 800_000_000_000))
+    let curve = BondingCurveInfo::try_from_account(bonding_curve_account)?;
+    Ok((curve.virtual_token_reserves, curve.virtual_sol_reserves, curve.real_token_reserves))
+}
+
+fn read_fee_basis_points(global: &AccountInfo) -> Result<u64> {
+    require_keys_eq!(*global.owner, PUMPFUN_PROGRAM_ID, ErrorCode::InvalidCurveAccount);
+    let data = global.try_borrow_data()?;
+    require!(data.len() >= GLOBAL_MIN_LEN, ErrorCode::InvalidCurveAccount);
+    Ok(read_u64(&data, FEE_BASIS_POINTS_OFFSET))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid number of accounts provided")]
     InvalidAccountsLength,
+    #[msg("Account is not a pump.fun curve or global state")]
+    InvalidCurveAccount,
+    #[msg("Bonding curve is complete and no longer trades")]
+    CurveComplete,
+    #[msg("Source mint is neither SOL nor the curve's token")]
+    MintMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+    use anchor_lang::solana_program::{entrypoint::ProgramResult, instruction::Instruction, program_error::ProgramError};
+
+    /// A curve partway to graduation: 15 SOL raised, 1% fee
+    fn curve_data(complete: bool) -> Vec<u8> {
+        let mut data = vec![0u8; 81];
+        write_u64(&mut data, VIRTUAL_TOKEN_RESERVES_OFFSET, 715_333_333_333_333);
+        write_u64(&mut data, VIRTUAL_SOL_RESERVES_OFFSET, 45_000_000_000);
+        write_u64(&mut data, REAL_TOKEN_RESERVES_OFFSET, 435_433_333_333_333);
+        write_u64(&mut data, REAL_SOL_RESERVES_OFFSET, 15_000_000_000);
+        data[COMPLETE_OFFSET] = complete as u8;
+        data
+    }
+
+    fn pool_accounts(mint: Pubkey, complete: bool) -> Vec<FixtureAccount> {
+        let mut global = vec![0u8; GLOBAL_MIN_LEN];
+        write_u64(&mut global, FEE_BASIS_POINTS_OFFSET, 100);
+
+        let mut accounts: Vec<FixtureAccount> = (0..=PROGRAM_INDEX).map(|_| unused_account()).collect();
+        accounts[GLOBAL_INDEX] = FixtureAccount::new(Pubkey::new_unique(), PUMPFUN_PROGRAM_ID, global);
+        accounts[MINT_INDEX] = FixtureAccount::new(mint, anchor_spl::token::ID, vec![0u8; 82]);
+        accounts[BONDING_CURVE_INDEX] = FixtureAccount::new(bonding_curve_address(&mint), PUMPFUN_PROGRAM_ID, curve_data(complete));
+        accounts[PROGRAM_INDEX] = FixtureAccount::new(PUMPFUN_PROGRAM_ID, Pubkey::default(), vec![]);
+        accounts
+    }
+
+    #[test]
+    fn quotes_buy_within_the_sol_budget() {
+        let curve = BondingCurveInfo::try_from_slice(&curve_data(false)).unwrap();
+        let quote = quote_curve(&curve, 100, 1_000_000_000, true).unwrap();
+        assert_eq!(quote.amount_out, 15_400_071_748_038);
+        assert_eq!(quote.fee, 9_900_990);
+        // Curve cost plus fee never exceeds the lamports offered
+        assert!(curve.buy_cost(quote.amount_out).unwrap() + quote.fee <= 1_000_000_000);
+    }
+
+    #[test]
+    fn quotes_sell_net_of_fees() {
+        let curve = BondingCurveInfo::try_from_slice(&curve_data(false)).unwrap();
+        let quote = quote_curve(&curve, 100, 10_000_000_000_000, false).unwrap();
+        assert_eq!((quote.amount_out, quote.fee), (614_200_367, 6_204_044));
+
+        // Proceeds are capped by the SOL actually held by the curve
+        let quote = quote_curve(&curve, 100, 400_000_000_000_000, false).unwrap();
+        assert_eq!((quote.amount_out, quote.fee), (14_850_000_000, 150_000_000));
+    }
+
+    #[test]
+    fn direction_follows_source_mint() {
+        let mint = Pubkey::new_unique();
+        let mut accounts = pool_accounts(mint, false);
+        let buy = PumpFunAdapter.get_quote(1_000_000_000, &NATIVE_MINT, &infos(&mut accounts)).unwrap();
+        assert_eq!(buy.amount_out, 15_400_071_748_038);
+        assert_eq!(buy.fee_bps, 100);
+
+        let sell = PumpFunAdapter.get_quote(10_000_000_000_000, &mint, &infos(&mut accounts)).unwrap();
+        assert_eq!(sell.amount_out, 614_200_367);
+
+        assert!(PumpFunAdapter.get_quote(1_000, &Pubkey::new_unique(), &infos(&mut accounts)).is_err());
+
+        // Only the mint's own curve is accepted
+        accounts[BONDING_CURVE_INDEX].key = Pubkey::new_unique();
+        assert!(PumpFunAdapter.get_quote(1_000_000_000, &NATIVE_MINT, &infos(&mut accounts)).is_err());
+    }
+
+    #[test]
+    fn token_mint_is_the_non_sol_side() {
+        let mint = Pubkey::new_unique();
+        assert_eq!(curve_token_mint(&NATIVE_MINT, &mint), mint);
+        assert_eq!(curve_token_mint(&mint, &NATIVE_MINT), mint);
+    }
+
+    #[test]
+    fn refuses_completed_curves() {
+        let mint = Pubkey::new_unique();
+        let mut accounts = pool_accounts(mint, true);
+        assert!(PumpFunAdapter.get_quote(1_000_000_000, &NATIVE_MINT, &infos(&mut accounts)).is_err());
+        assert!(PumpFunAdapter.get_quote(1_000_000_000, &mint, &infos(&mut accounts)).is_err());
+    }
+
+    #[test]
+    fn encodes_buy_and_sell_args() {
+        let buy = curve_instruction_data(true, 15_400_071_748_038, 1_000_000_000);
+        assert_eq!(buy[..8], BUY_INSTRUCTION);
+        assert_eq!(u64::from_le_bytes(buy[8..16].try_into().unwrap()), 15_400_071_748_038);
+        assert_eq!(u64::from_le_bytes(buy[16..24].try_into().unwrap()), 1_000_000_000);
+
+        let sell = curve_instruction_data(false, 10_000, 9_000);
+        assert_eq!(sell[..8], SELL_INSTRUCTION);
+        assert_eq!(u64::from_le_bytes(sell[8..16].try_into().unwrap()), 10_000);
+        assert_eq!(u64::from_le_bytes(sell[16..24].try_into().unwrap()), 9_000);
+    }
+
+    /// A curve sell: the signing user, owner or delegate of the token
+    /// account, hands over the tokens and is paid the net proceeds in lamports
+    fn mock_pump(instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        assert_eq!(instruction.data[..8], SELL_INSTRUCTION);
+        let amount = u64::from_le_bytes(instruction.data[8..16].try_into().unwrap());
+        let min_sol_output = u64::from_le_bytes(instruction.data[16..24].try_into().unwrap());
+        let user = &instruction.accounts[USER_INDEX];
+        let token_account = cpi_account(account_infos, &instruction.accounts[USER_TOKEN_ACCOUNT_INDEX])?;
+        {
+            let data = token_account.try_borrow_data()?;
+            let delegated = data[72] == 1
+                && data[76..108] == user.pubkey.to_bytes()
+                && u64::from_le_bytes(data[121..129].try_into().unwrap()) >= amount;
+            if !cpi_signed(user, account_infos, signers_seeds) || !(delegated || data[32..64] == user.pubkey.to_bytes()) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+        adjust_token_balance(token_account, -(amount as i128))?;
+
+        let curve_account = cpi_account(account_infos, &instruction.accounts[BONDING_CURVE_INDEX])?;
+        let curve = BondingCurveInfo::try_from_slice(&curve_account.try_borrow_data()?).unwrap();
+        let fee_basis_points = read_fee_basis_points(cpi_account(account_infos, &instruction.accounts[GLOBAL_INDEX])?).unwrap();
+        let proceeds = quote_curve(&curve, fee_basis_points, amount, false).unwrap().amount_out;
+        if proceeds < min_sol_output {
+            return Err(ProgramError::Custom(6003));
+        }
+        **curve_account.try_borrow_mut_lamports()? -= proceeds;
+        **cpi_account(account_infos, user)?.try_borrow_mut_lamports()? += proceeds;
+        Ok(())
+    }
+
+    /// A curve token vault and its wSOL output vault, both PDAs of `position`
+    fn position_sell_accounts(mint: Pubkey, position: &Pubkey, tokens: u64) -> (Vec<FixtureAccount>, u8, u8) {
+        let (vault, vault_bump) = Pubkey::find_program_address(&[b"vault", position.as_ref()], &crate::ID);
+        let (output_vault, output_vault_bump) = Pubkey::find_program_address(&[b"output_vault", position.as_ref()], &crate::ID);
+        let mut accounts = pool_accounts(mint, false);
+        accounts[BONDING_CURVE_INDEX].lamports = 30_000_000_000;
+        accounts[USER_TOKEN_ACCOUNT_INDEX] = token_account(vault, mint, tokens);
+        write_pubkey(&mut accounts[USER_TOKEN_ACCOUNT_INDEX].data, 32, &vault);
+        accounts[USER_INDEX] = native_token_account(output_vault, 0);
+        write_pubkey(&mut accounts[USER_INDEX].data, 32, &vault);
+        (accounts, vault_bump, output_vault_bump)
+    }
+
+    #[test]
+    fn sells_into_the_output_vault() {
+        mock_program(PUMPFUN_PROGRAM_ID, mock_pump);
+        mock_token_program();
+        let mint = Pubkey::new_unique();
+        let position = Pubkey::new_unique();
+        let (mut accounts, vault_bump, output_vault_bump) = position_sell_accounts(mint, &position, 10_000_000_000_000);
+        let mut token_program = FixtureAccount::new(anchor_spl::token::ID, Pubkey::default(), vec![]);
+        let token_program = token_program.info();
+        let slice = infos(&mut accounts);
+        let (vault, output_vault) = (&slice[USER_TOKEN_ACCOUNT_INDEX], &slice[USER_INDEX]);
+        assert_eq!(PumpFunAdapter.destination_account(&mint, &slice).unwrap().key, output_vault.key);
+
+        let vault_seeds: &[&[u8]] = &[b"vault", position.as_ref(), &[vault_bump]];
+        let output_vault_seeds: &[&[u8]] = &[b"output_vault", position.as_ref(), &[output_vault_bump]];
+        let sell = |min_amount_out: u64, seeds: &[&[&[u8]]]| {
+            sell_into_output_vault(&token_program, &token_program, vault, output_vault, 10_000_000_000_000, min_amount_out, &mint, &slice, seeds)
+        };
+
+        // The output vault must sign the sell as the curve's user
+        assert!(sell(614_000_000, &[vault_seeds]).is_err());
+        assert_eq!(sell(614_000_000, &[vault_seeds, output_vault_seeds]).unwrap(), 614_200_367);
+        assert_eq!(read_token_account(output_vault).unwrap().1, 614_200_367);
+        assert_eq!(read_token_account(vault).unwrap().1, 0);
+        // No allowance is left behind
+        assert_eq!(vault.try_borrow_data().unwrap()[72], 0);
+    }
+
+    #[test]
+    fn sells_only_from_the_position_vaults() {
+        let mint = Pubkey::new_unique();
+        let position = Pubkey::new_unique();
+        let (mut accounts, _, _) = position_sell_accounts(mint, &position, 10_000_000_000_000);
+        let mut token_program = FixtureAccount::new(anchor_spl::token::ID, Pubkey::default(), vec![]);
+        let token_program = token_program.info();
+        let mut other = native_token_account(Pubkey::new_unique(), 0);
+        let other = other.info();
+        let slice = infos(&mut accounts);
+        let (vault, output_vault) = (&slice[USER_TOKEN_ACCOUNT_INDEX], &slice[USER_INDEX]);
+
+        assert!(sell_into_output_vault(&token_program, &token_program, vault, output_vault, 1_000, 0, &NATIVE_MINT, &slice, &[]).is_err());
+        assert!(sell_into_output_vault(&token_program, &token_program, &other, output_vault, 1_000, 0, &mint, &slice, &[]).is_err());
+        assert!(sell_into_output_vault(&token_program, &token_program, vault, &other, 1_000, 0, &mint, &slice, &[]).is_err());
+    }
+
+    #[test]
+    fn reroutes_a_sell_on_a_completed_curve_to_the_migrated_pool() {
+        let mint = Pubkey::new_unique();
+        // A position selling the token for SOL
+        let token_mint = curve_token_mint(&mint, &NATIVE_MINT);
+        // A Raydium swap slice: the AMM sits at index 1
+        let mut accounts: Vec<FixtureAccount> = (0..19).map(|_| unused_account()).collect();
+        let pool = accounts[1].key;
+        let slice = infos(&mut accounts);
+        let route = MigrationRoute { mint, dex_type: DexType::Raydium, pool, bump: 255 };
+
+        let live = BondingCurveInfo::try_from_slice(&curve_data(false)).unwrap();
+        assert_eq!(migration_target(&live, Some(&route), &token_mint, &slice).unwrap().map(|r| r.pool), None);
+        assert_eq!(migration_target(&live, None, &token_mint, &slice).unwrap().map(|r| r.pool), None);
+
+        let complete = BondingCurveInfo::try_from_slice(&curve_data(true)).unwrap();
+        let target = migration_target(&complete, Some(&route), &token_mint, &slice).unwrap().unwrap();
+        assert_eq!((target.dex_type, target.pool), (DexType::Raydium, pool));
+
+        // A graduated token needs its route, for its own mint and pool
+        assert!(migration_target(&complete, None, &token_mint, &slice).is_err());
+        let other_mint = MigrationRoute { mint: Pubkey::new_unique(), dex_type: DexType::Raydium, pool, bump: 255 };
+        assert!(migration_target(&complete, Some(&other_mint), &token_mint, &slice).is_err());
+        let other_pool = MigrationRoute { mint, dex_type: DexType::Raydium, pool: Pubkey::new_unique(), bump: 255 };
+        assert!(migration_target(&complete, Some(&other_pool), &token_mint, &slice).is_err());
+        assert!(migration_target(&complete, Some(&route), &token_mint, &slice[..18]).is_err());
+    }
 }
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke;
-use crate::dex::{DexQuote, DexAdapter};
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
 use crate::state::DexType;
 
 /// Raydium AMM Program ID
@@ -9,65 +10,191 @@ pub const RAYDIUM_AMM_PROGRAM_ID: Pubkey = solana_program::pubkey!("675kPX9MHTjS
 /// Raydium swap instruction discriminator
 const SWAP_INSTRUCTION: u8 = 9;
 
+// Positions in the swap account slice (see `RaydiumAdapter::swap`)
+const AMM_INDEX: usize = 1;
+const OPEN_ORDERS_INDEX: usize = 3;
+const COIN_VAULT_INDEX: usize = 5;
+const PC_VAULT_INDEX: usize = 6;
+const USER_SOURCE_INDEX: usize = 15;
+const USER_DESTINATION_INDEX: usize = 16;
+/// Accounts passed to `swap_base_in` itself
+const SWAP_ACCOUNTS_LEN: usize = 18;
+/// The AMM program follows the instruction's accounts
+const PROGRAM_INDEX: usize = 18;
+
+/// Size of the AMM v4 `AmmInfo` account
+pub const AMM_INFO_LEN: usize = 752;
+
+// `AmmInfo` field offsets: 16 u64 params, then `Fees` (8 u64), `StateData`, then pubkeys
+const STATUS_OFFSET: usize = 0;
+const SWAP_FEE_NUMERATOR_OFFSET: usize = 176;
+const SWAP_FEE_DENOMINATOR_OFFSET: usize = 184;
+const NEED_TAKE_PNL_COIN_OFFSET: usize = 192;
+const NEED_TAKE_PNL_PC_OFFSET: usize = 200;
+const COIN_VAULT_OFFSET: usize = 336;
+const PC_VAULT_OFFSET: usize = 368;
+const COIN_MINT_OFFSET: usize = 400;
+const PC_MINT_OFFSET: usize = 432;
+const OPEN_ORDERS_OFFSET: usize = 496;
+
+// Serum/OpenBook `OpenOrders` totals (after the 5 byte "serum" head padding)
+const OPEN_ORDERS_COIN_TOTAL_OFFSET: usize = 85;
+const OPEN_ORDERS_PC_TOTAL_OFFSET: usize = 101;
+
+/// `AmmStatus` values whose reserves include funds resting on the order book
+const STATUS_INITIALIZED: u64 = 1;
+const STATUS_ORDER_BOOK_ONLY: u64 = 5;
+
+/// The parts of Raydium's `AmmInfo` needed to reproduce `swap_base_in`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaydiumAmmInfo {
+    pub status: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub open_orders: Pubkey,
+}
+
+impl RaydiumAmmInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, RAYDIUM_AMM_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(data.len() == AMM_INFO_LEN, ErrorCode::InvalidPoolAccount);
+        Ok(Self {
+            status: read_u64(data, STATUS_OFFSET),
+            swap_fee_numerator: read_u64(data, SWAP_FEE_NUMERATOR_OFFSET),
+            swap_fee_denominator: read_u64(data, SWAP_FEE_DENOMINATOR_OFFSET),
+            need_take_pnl_coin: read_u64(data, NEED_TAKE_PNL_COIN_OFFSET),
+            need_take_pnl_pc: read_u64(data, NEED_TAKE_PNL_PC_OFFSET),
+            coin_vault: read_pubkey(data, COIN_VAULT_OFFSET),
+            pc_vault: read_pubkey(data, PC_VAULT_OFFSET),
+            coin_mint: read_pubkey(data, COIN_MINT_OFFSET),
+            pc_mint: read_pubkey(data, PC_MINT_OFFSET),
+            open_orders: read_pubkey(data, OPEN_ORDERS_OFFSET),
+        })
+    }
+
+    fn uses_order_book(&self) -> bool {
+        self.status == STATUS_INITIALIZED || self.status == STATUS_ORDER_BOOK_ONLY
+    }
+}
+
 pub struct RaydiumAdapter;
 
 impl DexAdapter for RaydiumAdapter {
-    fn get_quote(
+    fn dex_type(&self) -> DexType {
+        DexType::Raydium
+    }
+
+    fn program_id(&self) -> Pubkey {
+        RAYDIUM_AMM_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, PROGRAM_INDEX, &RAYDIUM_AMM_PROGRAM_ID)?;
+
+        let amm = RaydiumAmmInfo::try_from_account(&accounts[AMM_INDEX])?;
+        require_keys_eq!(*accounts[COIN_VAULT_INDEX].key, amm.coin_vault, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[PC_VAULT_INDEX].key, amm.pc_vault, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[OPEN_ORDERS_INDEX].key, amm.open_orders, ErrorCode::InvalidPoolAccount);
+        require!(*source_mint == amm.coin_mint || *source_mint == amm.pc_mint, ErrorCode::MintMismatch);
+
+        // Raydium infers direction from the account the CPI debits
+        let (user_source_mint, _) = read_token_account(&accounts[USER_SOURCE_INDEX])?;
+        require_keys_eq!(user_source_mint, *source_mint, ErrorCode::MintMismatch);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
         amount_in: u64,
-        _input_decimals: u8,
-        _output_decimals: u8,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
     ) -> Result<DexQuote> {
-        // In production, this would call Raydium's quote calculation
-        // For now, we'll use a simplified version
-        
-        // Raydium typically has 0.25% fee
-        let fee_bps = 25u16;
-        let amount_after_fee = amount_in
-            .checked_mul(10000 - fee_bps as u64)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap();
-        
-        // Simplified quote - in production, calculate from pool reserves
-        let amount_out = amount_after_fee;
-        
+        self.validate_accounts(source_mint, accounts)?;
+
+        let amm = RaydiumAmmInfo::try_from_account(&accounts[AMM_INDEX])?;
+        let (coin_reserve, pc_reserve) = get_raydium_pool_info(
+            &accounts[AMM_INDEX],
+            &accounts[COIN_VAULT_INDEX],
+            &accounts[PC_VAULT_INDEX],
+            &accounts[OPEN_ORDERS_INDEX],
+        )?;
+
+        let (reserve_in, reserve_out) = if *source_mint == amm.coin_mint {
+            (coin_reserve, pc_reserve)
+        } else if *source_mint == amm.pc_mint {
+            (pc_reserve, coin_reserve)
+        } else {
+            return Err(ErrorCode::MintMismatch.into());
+        };
+        require!(
+            reserve_in > 0 && reserve_out > 0,
+            crate::errors::PyroSwapError::InsufficientLiquidity
+        );
+
+        let amount_out = swap_base_in_amount_out(
+            amount_in,
+            amm.swap_fee_numerator,
+            amm.swap_fee_denominator,
+            reserve_in,
+            reserve_out,
+        )?;
+
+        // Output at the current spot price, after fees, with no curve slippage
+        let amount_after_fee = amount_in - swap_fee(amount_in, amm.swap_fee_numerator, amm.swap_fee_denominator)?;
+        let spot_out = (amount_after_fee as u128 * reserve_out as u128 / reserve_in as u128) as u64;
+
         Ok(DexQuote {
             dex_type: DexType::Raydium,
             amount_out,
-            price_impact_bps: 10, // 0.1% estimated impact
-            fee_bps,
+            price_impact_bps: calculate_price_impact(amount_in, amount_out, spot_out),
+            fee_bps: (amm.swap_fee_numerator as u128 * 10000 / amm.swap_fee_denominator as u128) as u16,
         })
     }
 
     fn swap<'info>(
+        &self,
         amount_in: u64,
         min_amount_out: u64,
+        source_mint: &Pubkey,
         accounts: &[AccountInfo<'info>],
         signer_seeds: Option<&[&[&[u8]]]>,
     ) -> Result<u64> {
         // Raydium swap CPI
-        // accounts expected:
+        // accounts expected, in the order of `swap_base_in`:
         // 0. Token program
-        // 1. AMM program
-        // 2. AMM pool
-        // 3. AMM authority
-        // 4. AMM open orders
-        // 5. AMM target orders
-        // 6. Pool coin token account
-        // 7. Pool pc token account
-        // 8. Serum program
-        // 9. Serum market
-        // 10. Serum bids
-        // 11. Serum asks
-        // 12. Serum event queue
-        // 13. Serum coin vault
-        // 14. Serum pc vault
-        // 15. Serum vault signer
-        // 16. User source token account
-        // 17. User destination token account
-        // 18. User owner
-
-        require!(accounts.len() >= 19, ErrorCode::InvalidAccountsLength);
+        // 1. AMM pool
+        // 2. AMM authority
+        // 3. AMM open orders
+        // 4. AMM target orders
+        // 5. Pool coin token account
+        // 6. Pool pc token account
+        // 7. Serum program
+        // 8. Serum market
+        // 9. Serum bids
+        // 10. Serum asks
+        // 11. Serum event queue
+        // 12. Serum coin vault
+        // 13. Serum pc vault
+        // 14. Serum vault signer
+        // 15. User source token account
+        // 16. User destination token account
+        // 17. User owner
+        // 18. AMM program (passed to the CPI, not to the instruction)
+
+        self.validate_accounts(source_mint, accounts)?;
+        let (_, destination_before) = read_token_account(&accounts[USER_DESTINATION_INDEX])?;
 
         // Build Raydium swap instruction
         let mut instruction_data = Vec::with_capacity(17);
@@ -77,7 +204,7 @@ impl DexAdapter for RaydiumAdapter {
 
         let instruction = anchor_lang::solana_program::instruction::Instruction {
             program_id: RAYDIUM_AMM_PROGRAM_ID,
-            accounts: accounts.iter().map(|a| {
+            accounts: accounts[..SWAP_ACCOUNTS_LEN].iter().map(|a| {
                 anchor_lang::solana_program::instruction::AccountMeta {
                     pubkey: *a.key,
                     is_signer: a.is_signer,
@@ -93,22 +220,274 @@ impl DexAdapter for RaydiumAdapter {
             invoke(&instruction, accounts)?;
         }
 
-        // Return the output amount (would be read from account data in production)
-        Ok(min_amount_out)
+        let (_, destination_after) = read_token_account(&accounts[USER_DESTINATION_INDEX])?;
+        let amount_out = destination_after.checked_sub(destination_before).ok_or(PyroSwapError::MathOverflow)?;
+        require!(amount_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+        Ok(amount_out)
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        _source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[USER_DESTINATION_INDEX])
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > PROGRAM_INDEX, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[AMM_INDEX])
     }
 }
 
-/// Helper to get Raydium pool info
+/// Helper to get Raydium pool reserves as `(coin, pc)`.
+///
+/// Mirrors Raydium's `calc_total_without_take_pnl`: vault balances, plus funds
+/// resting on the order book when the pool trades there, minus uncollected PnL.
 pub fn get_raydium_pool_info<'info>(
     pool_account: &AccountInfo<'info>,
+    coin_vault_account: &AccountInfo<'info>,
+    pc_vault_account: &AccountInfo<'info>,
+    open_orders_account: &AccountInfo<'info>,
 ) -> Result<(u64, u64)> {
-    // In production, parse pool account data to get reserves
-    // For now, return placeholder values
-    Ok((1_000_000_000, 100_000_000))
+    let amm = RaydiumAmmInfo::try_from_account(pool_account)?;
+    require_keys_eq!(*coin_vault_account.key, amm.coin_vault, ErrorCode::InvalidPoolAccount);
+    require_keys_eq!(*pc_vault_account.key, amm.pc_vault, ErrorCode::InvalidPoolAccount);
+    require_keys_eq!(*open_orders_account.key, amm.open_orders, ErrorCode::InvalidPoolAccount);
+
+    let (_, mut coin_total) = read_token_account(coin_vault_account)?;
+    let (_, mut pc_total) = read_token_account(pc_vault_account)?;
+
+    if amm.uses_order_book() {
+        let data = open_orders_account.try_borrow_data()?;
+        require!(data.len() >= OPEN_ORDERS_PC_TOTAL_OFFSET + 8, ErrorCode::InvalidPoolAccount);
+        coin_total = coin_total
+            .checked_add(read_u64(&data, OPEN_ORDERS_COIN_TOTAL_OFFSET))
+            .ok_or(ErrorCode::InvalidPoolAccount)?;
+        pc_total = pc_total
+            .checked_add(read_u64(&data, OPEN_ORDERS_PC_TOTAL_OFFSET))
+            .ok_or(ErrorCode::InvalidPoolAccount)?;
+    }
+
+    let coin_reserve = coin_total
+        .checked_sub(amm.need_take_pnl_coin)
+        .ok_or(ErrorCode::InvalidPoolAccount)?;
+    let pc_reserve = pc_total
+        .checked_sub(amm.need_take_pnl_pc)
+        .ok_or(ErrorCode::InvalidPoolAccount)?;
+    Ok((coin_reserve, pc_reserve))
+}
+
+/// Raydium's swap fee: `amount * numerator / denominator` rounded up with
+/// its `checked_ceil_div`, which rounds sub-unit fees half up instead.
+// `is_multiple_of` is newer than the SBF toolchain's rustc
+#[allow(clippy::manual_is_multiple_of)]
+pub fn swap_fee(amount_in: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    require!(denominator > 0, ErrorCode::InvalidPoolAccount);
+    let product = amount_in as u128 * numerator as u128;
+    let denominator = denominator as u128;
+    let quotient = product / denominator;
+    let fee = if quotient == 0 {
+        if product * 2 >= denominator { 1 } else { 0 }
+    } else if product % denominator != 0 {
+        quotient + 1
+    } else {
+        quotient
+    };
+    Ok(fee as u64)
+}
+
+/// Exact output of Raydium's `swap_base_in` for the given reserves
+pub fn swap_base_in_amount_out(
+    amount_in: u64,
+    swap_fee_numerator: u64,
+    swap_fee_denominator: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+) -> Result<u64> {
+    let fee = swap_fee(amount_in, swap_fee_numerator, swap_fee_denominator)?;
+    let amount_after_fee = amount_in.checked_sub(fee).ok_or(ErrorCode::InvalidPoolAccount)? as u128;
+    let denominator = reserve_in as u128 + amount_after_fee;
+    require!(denominator > 0, crate::errors::PyroSwapError::InsufficientLiquidity);
+    Ok((reserve_out as u128 * amount_after_fee / denominator) as u64)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid number of accounts provided")]
     InvalidAccountsLength,
+    #[msg("Account is not a valid Raydium AMM v4 pool account")]
+    InvalidPoolAccount,
+    #[msg("Source token mint is not part of the pool")]
+    MintMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+    use anchor_lang::solana_program::entrypoint::ProgramResult;
+    use anchor_lang::solana_program::instruction::Instruction;
+
+    /// SOL/USDC-style AMM v4 snapshot: 2,500 SOL against 375,000 USDC at 0.25%
+    struct PoolFixture {
+        coin_mint: Pubkey,
+        pc_mint: Pubkey,
+        accounts: Vec<FixtureAccount>,
+    }
+
+    fn pool_fixture(status: u64, source_is_coin: bool) -> PoolFixture {
+        let coin_mint = Pubkey::new_unique();
+        let pc_mint = Pubkey::new_unique();
+        let coin_vault = Pubkey::new_unique();
+        let pc_vault = Pubkey::new_unique();
+        let open_orders = Pubkey::new_unique();
+
+        let mut amm = vec![0u8; AMM_INFO_LEN];
+        write_u64(&mut amm, STATUS_OFFSET, status);
+        write_u64(&mut amm, SWAP_FEE_NUMERATOR_OFFSET, 25);
+        write_u64(&mut amm, SWAP_FEE_DENOMINATOR_OFFSET, 10000);
+        write_u64(&mut amm, NEED_TAKE_PNL_COIN_OFFSET, 1_234_567);
+        write_u64(&mut amm, NEED_TAKE_PNL_PC_OFFSET, 89_000);
+        write_pubkey(&mut amm, COIN_VAULT_OFFSET, &coin_vault);
+        write_pubkey(&mut amm, PC_VAULT_OFFSET, &pc_vault);
+        write_pubkey(&mut amm, COIN_MINT_OFFSET, &coin_mint);
+        write_pubkey(&mut amm, PC_MINT_OFFSET, &pc_mint);
+        write_pubkey(&mut amm, OPEN_ORDERS_OFFSET, &open_orders);
+
+        let mut orders = vec![0u8; 3228];
+        write_u64(&mut orders, OPEN_ORDERS_COIN_TOTAL_OFFSET, 10_000_000_000);
+        write_u64(&mut orders, OPEN_ORDERS_PC_TOTAL_OFFSET, 1_500_000_000);
+
+        let source_mint = if source_is_coin { coin_mint } else { pc_mint };
+        let mut accounts: Vec<FixtureAccount> = (0..=PROGRAM_INDEX).map(|_| unused_account()).collect();
+        accounts[PROGRAM_INDEX] = FixtureAccount::new(RAYDIUM_AMM_PROGRAM_ID, Pubkey::default(), vec![]);
+        accounts[AMM_INDEX] = FixtureAccount::new(Pubkey::new_unique(), RAYDIUM_AMM_PROGRAM_ID, amm);
+        accounts[OPEN_ORDERS_INDEX] = FixtureAccount::new(open_orders, Pubkey::new_unique(), orders);
+        accounts[COIN_VAULT_INDEX] = token_account(coin_vault, coin_mint, 2_500_000_000_000);
+        accounts[PC_VAULT_INDEX] = token_account(pc_vault, pc_mint, 375_000_000_000);
+        accounts[USER_SOURCE_INDEX] = token_account(Pubkey::new_unique(), source_mint, 50_000_000_000);
+        let destination_mint = if source_is_coin { pc_mint } else { coin_mint };
+        accounts[USER_DESTINATION_INDEX] = token_account(Pubkey::new_unique(), destination_mint, 0);
+
+        PoolFixture { coin_mint, pc_mint, accounts }
+    }
+
+    const SWAP_ONLY: u64 = 6;
+
+    /// Stands in for the AMM: debits `amount_in` and pays a little more than
+    /// the caller's minimum, so only a measured output matches
+    fn mock_amm(instruction: &Instruction, account_infos: &[AccountInfo], _signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        let amount_in = u64::from_le_bytes(instruction.data[1..9].try_into().unwrap());
+        let min_amount_out = u64::from_le_bytes(instruction.data[9..17].try_into().unwrap());
+        adjust_token_balance(cpi_account(account_infos, &instruction.accounts[USER_SOURCE_INDEX])?, -(amount_in as i128))?;
+        adjust_token_balance(cpi_account(account_infos, &instruction.accounts[USER_DESTINATION_INDEX])?, min_amount_out as i128 + 7)
+    }
+
+    #[test]
+    fn expects_the_amm_program_after_the_instruction_accounts() {
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        assert!(RaydiumAdapter.validate_accounts(&pool.coin_mint, &infos(&mut pool.accounts)).is_ok());
+
+        pool.accounts[PROGRAM_INDEX] = unused_account();
+        assert!(RaydiumAdapter.validate_accounts(&pool.coin_mint, &infos(&mut pool.accounts)).is_err());
+
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        pool.accounts.pop();
+        assert!(RaydiumAdapter.validate_accounts(&pool.coin_mint, &infos(&mut pool.accounts)).is_err());
+    }
+
+    #[test]
+    fn reports_the_measured_output() {
+        mock_program(RAYDIUM_AMM_PROGRAM_ID, mock_amm);
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        let slice = infos(&mut pool.accounts);
+        assert_eq!(RaydiumAdapter.swap(1_000_000_000, 140_000_000, &pool.coin_mint, &slice, None).unwrap(), 140_000_007);
+        assert_eq!(read_token_account(&slice[USER_SOURCE_INDEX]).unwrap().1, 49_000_000_000);
+    }
+
+    #[test]
+    fn pool_account_is_the_amm() {
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        let amm = pool.accounts[AMM_INDEX].key;
+        let infos = infos(&mut pool.accounts);
+        assert_eq!(*RaydiumAdapter.pool_account(&infos).unwrap().key, amm);
+        assert!(RaydiumAdapter.pool_account(&infos[..PROGRAM_INDEX]).is_err());
+    }
+
+    #[test]
+    fn parses_amm_info() {
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        let amm = RaydiumAmmInfo::try_from_account(&pool.accounts[AMM_INDEX].info()).unwrap();
+        assert_eq!(amm.coin_mint, pool.coin_mint);
+        assert_eq!(amm.pc_mint, pool.pc_mint);
+        assert_eq!((amm.swap_fee_numerator, amm.swap_fee_denominator), (25, 10000));
+
+        let infos = infos(&mut pool.accounts);
+        let reserves = get_raydium_pool_info(
+            &infos[AMM_INDEX],
+            &infos[COIN_VAULT_INDEX],
+            &infos[PC_VAULT_INDEX],
+            &infos[OPEN_ORDERS_INDEX],
+        ).unwrap();
+        // Vault balances minus uncollected PnL
+        assert_eq!(reserves, (2_499_998_765_433, 374_999_911_000));
+    }
+
+    #[test]
+    fn quotes_coin_to_pc() {
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        let quote = RaydiumAdapter.get_quote(5_000_000_000, &pool.coin_mint, &infos(&mut pool.accounts)).unwrap();
+        assert_eq!(quote.amount_out, 746_635_653);
+        assert_eq!(quote.fee_bps, 25);
+    }
+
+    #[test]
+    fn quotes_pc_to_coin() {
+        let mut pool = pool_fixture(SWAP_ONLY, false);
+        let quote = RaydiumAdapter.get_quote(1_000_000_000, &pool.pc_mint, &infos(&mut pool.accounts)).unwrap();
+        assert_eq!(quote.amount_out, 6_632_356_222);
+    }
+
+    #[test]
+    fn includes_order_book_funds_when_trading_there() {
+        let mut pool = pool_fixture(STATUS_INITIALIZED, true);
+        let quote = RaydiumAdapter.get_quote(5_000_000_000, &pool.coin_mint, &infos(&mut pool.accounts)).unwrap();
+        assert_eq!(quote.amount_out, 746_641_574);
+    }
+
+    #[test]
+    fn rejects_foreign_mint_and_wrong_owner() {
+        let foreign_mint = Pubkey::new_unique();
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        pool.accounts[USER_SOURCE_INDEX] = token_account(Pubkey::new_unique(), foreign_mint, 1);
+        assert!(RaydiumAdapter.get_quote(1_000, &foreign_mint, &infos(&mut pool.accounts)).is_err());
+
+        // The source mint must be the one the debited account holds
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        assert!(RaydiumAdapter.get_quote(1_000, &pool.pc_mint, &infos(&mut pool.accounts)).is_err());
+
+        let mut pool = pool_fixture(SWAP_ONLY, true);
+        pool.accounts[AMM_INDEX].owner = Pubkey::new_unique();
+        assert!(RaydiumAdapter.get_quote(1_000, &pool.coin_mint, &infos(&mut pool.accounts)).is_err());
+    }
+
+    #[test]
+    fn fee_rounding_matches_checked_ceil_div() {
+        // Sub-unit fees round half up, larger fees round up
+        let fees: Vec<u64> = [100, 200, 399, 400, 401]
+            .iter()
+            .map(|a| swap_fee(*a, 25, 10000).unwrap())
+            .collect();
+        assert_eq!(fees, vec![0, 1, 1, 1, 2]);
+    }
 }
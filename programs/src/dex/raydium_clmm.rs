@@ -0,0 +1,949 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use crate::dex::math::{mul_div, U256};
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, transfer_fee, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
+use crate::state::DexType;
+
+/// Raydium CLMM Program ID
+pub const RAYDIUM_CLMM_PROGRAM_ID: Pubkey = solana_program::pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+
+/// `swap_v2` instruction discriminator
+const SWAP_V2_INSTRUCTION: [u8; 8] = [0x2b, 0x04, 0xed, 0x0b, 0x1a, 0xc9, 0x1e, 0x62];
+
+/// `PoolState` account discriminator
+const POOL_STATE_DISCRIMINATOR: [u8; 8] = [0xf7, 0xed, 0xe3, 0xf5, 0xd7, 0xc3, 0xde, 0x46];
+
+/// `AmmConfig` account discriminator
+const AMM_CONFIG_DISCRIMINATOR: [u8; 8] = [0xda, 0xf4, 0x21, 0x68, 0xcb, 0xcb, 0x2b, 0x6f];
+
+/// `TickArrayState` account discriminator
+const TICK_ARRAY_DISCRIMINATOR: [u8; 8] = [0xc0, 0x9b, 0x55, 0xcd, 0x31, 0xf9, 0x81, 0x2a];
+
+// Positions in the swap account slice (see `RaydiumClmmAdapter::swap`)
+const AMM_CONFIG_INDEX: usize = 1;
+const POOL_STATE_INDEX: usize = 2;
+const INPUT_TOKEN_ACCOUNT_INDEX: usize = 3;
+const OUTPUT_TOKEN_ACCOUNT_INDEX: usize = 4;
+const INPUT_VAULT_INDEX: usize = 5;
+const OUTPUT_VAULT_INDEX: usize = 6;
+const OBSERVATION_INDEX: usize = 7;
+const INPUT_MINT_INDEX: usize = 11;
+const OUTPUT_MINT_INDEX: usize = 12;
+/// Fixed accounts of the `swap_v2` instruction
+const SWAP_ACCOUNTS_LEN: usize = 13;
+/// The CLMM program sits between the fixed accounts and the tick arrays
+const TICK_ARRAYS_START: usize = 14;
+
+/// Size of the `PoolState` account
+pub const CLMM_POOL_STATE_LEN: usize = 1544;
+
+// `PoolState` field offsets (packed, after the 8 byte Anchor discriminator)
+const AMM_CONFIG_OFFSET: usize = 9;
+const TOKEN_MINT_0_OFFSET: usize = 73;
+const TOKEN_MINT_1_OFFSET: usize = 105;
+const TOKEN_VAULT_0_OFFSET: usize = 137;
+const TOKEN_VAULT_1_OFFSET: usize = 169;
+const OBSERVATION_KEY_OFFSET: usize = 201;
+const TICK_SPACING_OFFSET: usize = 235;
+const LIQUIDITY_OFFSET: usize = 237;
+const SQRT_PRICE_OFFSET: usize = 253;
+const TICK_CURRENT_OFFSET: usize = 269;
+const STATUS_OFFSET: usize = 389;
+const TICK_ARRAY_BITMAP_OFFSET: usize = 904;
+const OPEN_TIME_OFFSET: usize = 1080;
+
+/// Size of the `AmmConfig` account
+pub const CLMM_AMM_CONFIG_LEN: usize = 117;
+const TRADE_FEE_RATE_OFFSET: usize = 47;
+
+/// Size of a `TickArrayState` account
+pub const CLMM_TICK_ARRAY_LEN: usize = 10240;
+/// Ticks stored per tick array
+pub const CLMM_TICK_ARRAY_SIZE: i32 = 60;
+
+// `TickArrayState` layout: owning pool, start index, then 60 ticks of 168 bytes
+const TICK_ARRAY_POOL_OFFSET: usize = 8;
+const START_TICK_INDEX_OFFSET: usize = 40;
+const TICKS_OFFSET: usize = 44;
+const TICK_LEN: usize = 168;
+const TICK_LIQUIDITY_NET_OFFSET: usize = 4;
+const TICK_LIQUIDITY_GROSS_OFFSET: usize = 20;
+
+/// Fee rates are parts per million
+pub const CLMM_FEE_RATE_DENOMINATOR: u32 = 1_000_000;
+
+/// `PoolStatusBitIndex::Swap`: set when swaps are disabled
+const STATUS_SWAP_DISABLED: u8 = 1 << 4;
+
+/// The default bitmap tracks 512 tick arrays either side of zero
+const BITMAP_HALF_RANGE: i32 = 512;
+
+pub const CLMM_MIN_TICK: i32 = -443636;
+pub const CLMM_MAX_TICK: i32 = 443636;
+pub const CLMM_MIN_SQRT_PRICE_X64: u128 = 4295048016;
+pub const CLMM_MAX_SQRT_PRICE_X64: u128 = 79226673521066979257578248091;
+
+/// The parts of a CLMM `PoolState` needed to validate and quote a swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClmmPoolInfo {
+    pub amm_config: Pubkey,
+    pub token_mint_0: Pubkey,
+    pub token_mint_1: Pubkey,
+    pub token_vault_0: Pubkey,
+    pub token_vault_1: Pubkey,
+    pub observation_key: Pubkey,
+    pub tick_spacing: u16,
+    pub liquidity: u128,
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+    pub status: u8,
+    pub tick_array_bitmap: [u64; 16],
+    pub open_time: u64,
+}
+
+impl ClmmPoolInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, RAYDIUM_CLMM_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == CLMM_POOL_STATE_LEN && data[..8] == POOL_STATE_DISCRIMINATOR,
+            ErrorCode::InvalidPoolAccount
+        );
+        let mut tick_array_bitmap = [0u64; 16];
+        for (i, word) in tick_array_bitmap.iter_mut().enumerate() {
+            *word = read_u64(data, TICK_ARRAY_BITMAP_OFFSET + i * 8);
+        }
+        let pool = Self {
+            amm_config: read_pubkey(data, AMM_CONFIG_OFFSET),
+            token_mint_0: read_pubkey(data, TOKEN_MINT_0_OFFSET),
+            token_mint_1: read_pubkey(data, TOKEN_MINT_1_OFFSET),
+            token_vault_0: read_pubkey(data, TOKEN_VAULT_0_OFFSET),
+            token_vault_1: read_pubkey(data, TOKEN_VAULT_1_OFFSET),
+            observation_key: read_pubkey(data, OBSERVATION_KEY_OFFSET),
+            tick_spacing: u16::from_le_bytes(data[TICK_SPACING_OFFSET..TICK_SPACING_OFFSET + 2].try_into().unwrap()),
+            liquidity: read_u128(data, LIQUIDITY_OFFSET),
+            sqrt_price_x64: read_u128(data, SQRT_PRICE_OFFSET),
+            tick_current: read_i32(data, TICK_CURRENT_OFFSET),
+            status: data[STATUS_OFFSET],
+            tick_array_bitmap,
+            open_time: read_u64(data, OPEN_TIME_OFFSET),
+        };
+        require!(pool.tick_spacing > 0, ErrorCode::InvalidPoolAccount);
+        Ok(pool)
+    }
+
+    /// Direction for selling `source_mint`
+    pub fn zero_for_one(&self, source_mint: &Pubkey) -> Result<bool> {
+        if *source_mint == self.token_mint_0 {
+            Ok(true)
+        } else if *source_mint == self.token_mint_1 {
+            Ok(false)
+        } else {
+            Err(ErrorCode::MintMismatch.into())
+        }
+    }
+
+    /// Swaps need the swap status bit clear and the pool past its open time
+    pub fn check_swappable(&self, now: i64) -> Result<()> {
+        require!(self.status & STATUS_SWAP_DISABLED == 0, ErrorCode::PoolNotSwappable);
+        require!(now > self.open_time as i64, ErrorCode::PoolNotSwappable);
+        Ok(())
+    }
+
+    /// Check the swap slice against the pool for a swap in the given direction
+    pub fn validate_accounts(&self, accounts: &[AccountInfo], zero_for_one: bool) -> Result<()> {
+        let (input_vault, output_vault, input_mint, output_mint) = if zero_for_one {
+            (self.token_vault_0, self.token_vault_1, self.token_mint_0, self.token_mint_1)
+        } else {
+            (self.token_vault_1, self.token_vault_0, self.token_mint_1, self.token_mint_0)
+        };
+        require_keys_eq!(*accounts[AMM_CONFIG_INDEX].key, self.amm_config, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[INPUT_VAULT_INDEX].key, input_vault, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[OUTPUT_VAULT_INDEX].key, output_vault, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[OBSERVATION_INDEX].key, self.observation_key, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[INPUT_MINT_INDEX].key, input_mint, ErrorCode::MintMismatch);
+        require_keys_eq!(*accounts[OUTPUT_MINT_INDEX].key, output_mint, ErrorCode::MintMismatch);
+        Ok(())
+    }
+
+    fn tick_array_span(&self) -> i32 {
+        self.tick_spacing as i32 * CLMM_TICK_ARRAY_SIZE
+    }
+
+    /// Start index of the tick array holding `tick`
+    pub fn tick_array_start_index(&self, tick: i32) -> i32 {
+        tick.div_euclid(self.tick_array_span()) * self.tick_array_span()
+    }
+
+    fn bitmap_bit(&self, position: i32) -> bool {
+        let bit = (position + BITMAP_HALF_RANGE) as usize;
+        self.tick_array_bitmap[bit / 64] >> (bit % 64) & 1 == 1
+    }
+
+    /// Whether the default bitmap marks the array starting at `start_index` as
+    /// holding initialized ticks
+    pub fn is_tick_array_initialized(&self, start_index: i32) -> bool {
+        let position = start_index / self.tick_array_span();
+        (-BITMAP_HALF_RANGE..BITMAP_HALF_RANGE).contains(&position) && self.bitmap_bit(position)
+    }
+
+    /// The next initialized tick array past `start_index` in the swap
+    /// direction. Arrays beyond the default bitmap live in the bitmap
+    /// extension and are not followed.
+    pub fn next_initialized_tick_array(&self, start_index: i32, zero_for_one: bool) -> Option<i32> {
+        let step = if zero_for_one { -1 } else { 1 };
+        let mut position = start_index / self.tick_array_span() + step;
+        while (-BITMAP_HALF_RANGE..BITMAP_HALF_RANGE).contains(&position) {
+            if self.bitmap_bit(position) {
+                return Some(position * self.tick_array_span());
+            }
+            position += step;
+        }
+        None
+    }
+}
+
+/// Trade fee rate from a CLMM `AmmConfig`, in parts per million
+pub fn read_clmm_trade_fee_rate(account: &AccountInfo) -> Result<u32> {
+    require_keys_eq!(*account.owner, RAYDIUM_CLMM_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() == CLMM_AMM_CONFIG_LEN && data[..8] == AMM_CONFIG_DISCRIMINATOR,
+        ErrorCode::InvalidPoolAccount
+    );
+    let rate = u32::from_le_bytes(data[TRADE_FEE_RATE_OFFSET..TRADE_FEE_RATE_OFFSET + 4].try_into().unwrap());
+    require!(rate < CLMM_FEE_RATE_DENOMINATOR, ErrorCode::InvalidPoolAccount);
+    Ok(rate)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClmmTick {
+    pub tick: i32,
+    pub liquidity_net: i128,
+    pub liquidity_gross: u128,
+}
+
+impl ClmmTick {
+    pub fn is_initialized(&self) -> bool {
+        self.liquidity_gross != 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClmmTickArray {
+    pub start_tick_index: i32,
+    pub ticks: Vec<ClmmTick>,
+}
+
+impl ClmmTickArray {
+    /// Whether `account` holds a tick array rather than another remaining
+    /// account such as the bitmap extension
+    pub fn is_tick_array(account: &AccountInfo) -> bool {
+        account.try_borrow_data().map(|data| data.len() >= 8 && data[..8] == TICK_ARRAY_DISCRIMINATOR).unwrap_or(false)
+    }
+
+    pub fn try_from_account(account: &AccountInfo, pool: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*account.owner, RAYDIUM_CLMM_PROGRAM_ID, ErrorCode::InvalidTickArray);
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() == CLMM_TICK_ARRAY_LEN && data[..8] == TICK_ARRAY_DISCRIMINATOR,
+            ErrorCode::InvalidTickArray
+        );
+        require_keys_eq!(read_pubkey(&data, TICK_ARRAY_POOL_OFFSET), *pool, ErrorCode::InvalidTickArray);
+        Ok(Self::from_slice(&data))
+    }
+
+    pub fn from_slice(data: &[u8]) -> Self {
+        let ticks = (0..CLMM_TICK_ARRAY_SIZE as usize)
+            .map(|i| {
+                let offset = TICKS_OFFSET + i * TICK_LEN;
+                ClmmTick {
+                    tick: read_i32(data, offset),
+                    liquidity_net: read_u128(data, offset + TICK_LIQUIDITY_NET_OFFSET) as i128,
+                    liquidity_gross: read_u128(data, offset + TICK_LIQUIDITY_GROSS_OFFSET),
+                }
+            })
+            .collect();
+        Self { start_tick_index: read_i32(data, START_TICK_INDEX_OFFSET), ticks }
+    }
+
+    /// `TickArrayState::next_initialized_tick`: searching down includes the
+    /// current tick, searching up starts past it
+    fn next_initialized_tick(&self, tick: i32, tick_spacing: i32, zero_for_one: bool) -> Option<ClmmTick> {
+        let offset = (tick - self.start_tick_index) / tick_spacing;
+        if zero_for_one {
+            (0..=offset).rev().map(|i| self.ticks[i as usize]).find(ClmmTick::is_initialized)
+        } else {
+            (offset + 1..CLMM_TICK_ARRAY_SIZE).map(|i| self.ticks[i as usize]).find(ClmmTick::is_initialized)
+        }
+    }
+
+    /// The first initialized tick met when entering the array
+    fn first_initialized_tick(&self, zero_for_one: bool) -> Option<ClmmTick> {
+        if zero_for_one {
+            self.ticks.iter().rev().copied().find(ClmmTick::is_initialized)
+        } else {
+            self.ticks.iter().copied().find(ClmmTick::is_initialized)
+        }
+    }
+}
+
+/// Result of walking the pool with an exact input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClmmSwapResult {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub end_sqrt_price_x64: u128,
+}
+
+/// Exact-input swap following the CLMM program's `swap_internal`: the pool's
+/// tick array bitmap decides which arrays are visited, and `tick_arrays` must
+/// contain each of them.
+pub fn clmm_swap_base_input(
+    pool: &ClmmPoolInfo,
+    tick_arrays: &[ClmmTickArray],
+    amount_in: u64,
+    trade_fee_rate: u32,
+    zero_for_one: bool,
+) -> Result<ClmmSwapResult> {
+    require!(amount_in > 0, PyroSwapError::InvalidAmount);
+    let tick_spacing = pool.tick_spacing as i32;
+    let sqrt_price_limit = if zero_for_one { CLMM_MIN_SQRT_PRICE_X64 + 1 } else { CLMM_MAX_SQRT_PRICE_X64 - 1 };
+    let find_array = |start_index: i32| {
+        tick_arrays
+            .iter()
+            .find(|array| array.start_tick_index == start_index)
+            .ok_or(error!(ErrorCode::NotEnoughTickArrays))
+    };
+
+    let mut remaining = amount_in;
+    let mut amount_out = 0u64;
+    let mut fee_amount = 0u64;
+    let mut sqrt_price = pool.sqrt_price_x64;
+    let mut tick = pool.tick_current;
+    let mut liquidity = pool.liquidity;
+
+    // Start in the current tick's array if it is initialized, otherwise the next one along
+    let mut array_start = pool.tick_array_start_index(tick);
+    let mut matched_current_array = pool.is_tick_array_initialized(array_start);
+    if !matched_current_array {
+        array_start = pool
+            .next_initialized_tick_array(array_start, zero_for_one)
+            .ok_or(PyroSwapError::InsufficientLiquidity)?;
+    }
+
+    while remaining != 0 && sqrt_price != sqrt_price_limit {
+        let mut array = find_array(array_start)?;
+        let mut next_tick = if pool.tick_array_start_index(tick) == array.start_tick_index {
+            array.next_initialized_tick(tick, tick_spacing, zero_for_one)
+        } else {
+            None
+        };
+        if next_tick.is_none() && !matched_current_array {
+            matched_current_array = true;
+            next_tick = array.first_initialized_tick(zero_for_one);
+        }
+        let next_tick = match next_tick {
+            Some(next_tick) => next_tick,
+            None => {
+                array_start = pool
+                    .next_initialized_tick_array(array_start, zero_for_one)
+                    .ok_or(PyroSwapError::InsufficientLiquidity)?;
+                array = find_array(array_start)?;
+                array.first_initialized_tick(zero_for_one).ok_or(ErrorCode::InvalidTickArray)?
+            }
+        };
+
+        let tick_next = next_tick.tick.clamp(CLMM_MIN_TICK, CLMM_MAX_TICK);
+        let sqrt_price_next = sqrt_price_at_tick(tick_next)?;
+        let target = if zero_for_one { sqrt_price_next.max(sqrt_price_limit) } else { sqrt_price_next.min(sqrt_price_limit) };
+
+        let step = compute_swap_step(sqrt_price, target, liquidity, remaining, trade_fee_rate, zero_for_one)?;
+        sqrt_price = step.sqrt_price_next;
+        remaining -= step.amount_in + step.fee_amount;
+        amount_out = amount_out.checked_add(step.amount_out).ok_or(PyroSwapError::MathOverflow)?;
+        fee_amount += step.fee_amount;
+
+        if sqrt_price == sqrt_price_next {
+            // Crossing a tick moves liquidity in or out of range
+            let liquidity_net = if zero_for_one { -next_tick.liquidity_net } else { next_tick.liquidity_net };
+            liquidity = u128::try_from(liquidity as i128 + liquidity_net).map_err(|_| error!(PyroSwapError::MathOverflow))?;
+            tick = if zero_for_one { tick_next - 1 } else { tick_next };
+        }
+        // A step that stops short of the next tick spends the rest of the input
+    }
+    require!(remaining == 0, PyroSwapError::InsufficientLiquidity);
+
+    Ok(ClmmSwapResult { amount_in, amount_out, fee_amount, end_sqrt_price_x64: sqrt_price })
+}
+
+/// A quoted CLMM trade, including Token-2022 transfer fees on both legs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClmmQuote {
+    pub input_transfer_fee: u64,
+    pub trade_fee: u64,
+    pub output_transfer_fee: u64,
+    /// What arrives in the destination account
+    pub amount_out: u64,
+    pub end_sqrt_price_x64: u128,
+}
+
+/// Quote `swap_v2` from the swap slice as of `clock`, the way the program
+/// charges transfer fees around the swap
+pub fn quote_clmm(amount_in: u64, source_mint: &Pubkey, accounts: &[AccountInfo], clock: &Clock) -> Result<ClmmQuote> {
+    require!(accounts.len() > TICK_ARRAYS_START, ErrorCode::InvalidAccountsLength);
+
+    let pool_account = &accounts[POOL_STATE_INDEX];
+    let pool = ClmmPoolInfo::try_from_account(pool_account)?;
+    pool.check_swappable(clock.unix_timestamp)?;
+    let zero_for_one = pool.zero_for_one(source_mint)?;
+    pool.validate_accounts(accounts, zero_for_one)?;
+    let trade_fee_rate = read_clmm_trade_fee_rate(&accounts[AMM_CONFIG_INDEX])?;
+
+    let tick_arrays = accounts[TICK_ARRAYS_START..]
+        .iter()
+        .filter(|account| ClmmTickArray::is_tick_array(account))
+        .map(|account| ClmmTickArray::try_from_account(account, pool_account.key))
+        .collect::<Result<Vec<_>>>()?;
+
+    let input_transfer_fee = transfer_fee(&accounts[INPUT_MINT_INDEX], amount_in, clock.epoch)?;
+    let result = clmm_swap_base_input(&pool, &tick_arrays, amount_in - input_transfer_fee, trade_fee_rate, zero_for_one)?;
+    let output_transfer_fee = transfer_fee(&accounts[OUTPUT_MINT_INDEX], result.amount_out, clock.epoch)?;
+
+    Ok(ClmmQuote {
+        input_transfer_fee,
+        trade_fee: result.fee_amount,
+        output_transfer_fee,
+        amount_out: result.amount_out - output_transfer_fee,
+        end_sqrt_price_x64: result.end_sqrt_price_x64,
+    })
+}
+
+/// `swap_v2(amount, other_amount_threshold, sqrt_price_limit_x64, is_base_input)`
+/// with a zero limit, which the program reads as "no limit"
+pub fn clmm_swap_instruction_data(amount: u64, other_amount_threshold: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(41);
+    data.extend_from_slice(&SWAP_V2_INSTRUCTION);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&other_amount_threshold.to_le_bytes());
+    data.extend_from_slice(&0u128.to_le_bytes());
+    data.push(1); // is_base_input
+    data
+}
+
+pub struct RaydiumClmmAdapter;
+
+impl DexAdapter for RaydiumClmmAdapter {
+    fn dex_type(&self) -> DexType {
+        DexType::RaydiumClmm
+    }
+
+    fn program_id(&self) -> Pubkey {
+        RAYDIUM_CLMM_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() > TICK_ARRAYS_START, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, SWAP_ACCOUNTS_LEN, &RAYDIUM_CLMM_PROGRAM_ID)?;
+
+        let pool = ClmmPoolInfo::try_from_account(&accounts[POOL_STATE_INDEX])?;
+        pool.validate_accounts(accounts, pool.zero_for_one(source_mint)?)?;
+        let (input_mint, _) = read_token_account(&accounts[INPUT_TOKEN_ACCOUNT_INDEX])?;
+        require_keys_eq!(input_mint, *source_mint, ErrorCode::MintMismatch);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
+        amount_in: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<DexQuote> {
+        self.validate_accounts(source_mint, accounts)?;
+        let quote = quote_clmm(amount_in, source_mint, accounts, &Clock::get()?)?;
+        let pool = ClmmPoolInfo::try_from_account(&accounts[POOL_STATE_INDEX])?;
+        let zero_for_one = pool.zero_for_one(source_mint)?;
+
+        // Output at the starting price after fees, for price impact
+        let net_in = (amount_in - quote.input_transfer_fee - quote.trade_fee) as u128;
+        let price = pool.sqrt_price_x64;
+        let spot_out = if zero_for_one {
+            mul_div(net_in, price, 1 << 64, false).and_then(|x| mul_div(x, price, 1 << 64, false))
+        } else {
+            mul_div(net_in, 1 << 64, price, false).and_then(|x| mul_div(x, 1 << 64, price, false))
+        }
+        .map_or(u64::MAX, |out| out.min(u64::MAX as u128) as u64);
+        let trade_fee_rate = read_clmm_trade_fee_rate(&accounts[AMM_CONFIG_INDEX])?;
+
+        Ok(DexQuote {
+            dex_type: DexType::RaydiumClmm,
+            amount_out: quote.amount_out,
+            price_impact_bps: calculate_price_impact(amount_in, quote.amount_out + quote.output_transfer_fee, spot_out),
+            fee_bps: (trade_fee_rate / 100) as u16,
+        })
+    }
+
+    fn swap<'info>(
+        &self,
+        amount_in: u64,
+        min_amount_out: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        signer_seeds: Option<&[&[&[u8]]]>,
+    ) -> Result<u64> {
+        // Raydium CLMM swap_v2 CPI
+        // accounts expected:
+        // 0. Payer (signer)
+        // 1. AMM config
+        // 2. Pool state
+        // 3. Input token account
+        // 4. Output token account
+        // 5. Input vault
+        // 6. Output vault
+        // 7. Observation state
+        // 8. Token program
+        // 9. Token-2022 program
+        // 10. Memo program
+        // 11. Input vault mint
+        // 12. Output vault mint
+        // 13. CLMM program
+        // 14.. Tick array bitmap extension (optional) and tick arrays
+
+        self.validate_accounts(source_mint, accounts)?;
+        let (_, output_before) = read_token_account(&accounts[OUTPUT_TOKEN_ACCOUNT_INDEX])?;
+
+        let instruction = anchor_lang::solana_program::instruction::Instruction {
+            program_id: RAYDIUM_CLMM_PROGRAM_ID,
+            accounts: accounts[..SWAP_ACCOUNTS_LEN]
+                .iter()
+                .chain(&accounts[TICK_ARRAYS_START..])
+                .map(|a| {
+                    anchor_lang::solana_program::instruction::AccountMeta {
+                        pubkey: *a.key,
+                        is_signer: a.is_signer,
+                        is_writable: a.is_writable,
+                    }
+                })
+                .collect(),
+            data: clmm_swap_instruction_data(amount_in, min_amount_out),
+        };
+
+        if let Some(seeds) = signer_seeds {
+            anchor_lang::solana_program::program::invoke_signed(&instruction, accounts, seeds)?;
+        } else {
+            invoke(&instruction, accounts)?;
+        }
+
+        let (_, output_after) = read_token_account(&accounts[OUTPUT_TOKEN_ACCOUNT_INDEX])?;
+        Ok(output_after.saturating_sub(output_before))
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        _source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > TICK_ARRAYS_START, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[OUTPUT_TOKEN_ACCOUNT_INDEX])
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > TICK_ARRAYS_START, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[POOL_STATE_INDEX])
+    }
+}
+
+struct SwapStep {
+    sqrt_price_next: u128,
+    amount_in: u64,
+    amount_out: u64,
+    fee_amount: u64,
+}
+
+/// `swap_math::compute_swap_step` for exact input
+fn compute_swap_step(
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee_rate: u32,
+    zero_for_one: bool,
+) -> Result<SwapStep> {
+    let fee_rate = fee_rate as u128;
+    let denominator = CLMM_FEE_RATE_DENOMINATOR as u128;
+    let amount_remaining_less_fee = (amount_remaining as u128 * (denominator - fee_rate) / denominator) as u64;
+
+    // `None` when the whole range needs more than a u64 of input
+    let amount_to_target = if zero_for_one {
+        amount_delta_0(sqrt_price_target, sqrt_price_current, liquidity, true).ok()
+    } else {
+        amount_delta_1(sqrt_price_current, sqrt_price_target, liquidity, true).ok()
+    };
+    let sqrt_price_next = match amount_to_target {
+        Some(amount) if amount_remaining_less_fee >= amount => sqrt_price_target,
+        _ => next_sqrt_price_from_input(sqrt_price_current, liquidity, amount_remaining_less_fee, zero_for_one)?,
+    };
+    let reached_target = sqrt_price_next == sqrt_price_target;
+
+    let (amount_in, amount_out) = if zero_for_one {
+        let amount_in = match amount_to_target {
+            Some(amount) if reached_target => amount,
+            _ => amount_delta_0(sqrt_price_next, sqrt_price_current, liquidity, true)?,
+        };
+        (amount_in, amount_delta_1(sqrt_price_next, sqrt_price_current, liquidity, false)?)
+    } else {
+        let amount_in = match amount_to_target {
+            Some(amount) if reached_target => amount,
+            _ => amount_delta_1(sqrt_price_current, sqrt_price_next, liquidity, true)?,
+        };
+        (amount_in, amount_delta_0(sqrt_price_current, sqrt_price_next, liquidity, false)?)
+    };
+
+    let fee_amount = if !reached_target {
+        amount_remaining - amount_in
+    } else {
+        (amount_in as u128 * fee_rate).div_ceil(denominator - fee_rate) as u64
+    };
+
+    Ok(SwapStep { sqrt_price_next, amount_in, amount_out, fee_amount })
+}
+
+/// Token 0 between two prices: `L << 64 * (b - a) / b / a`, dividing twice
+/// like the CLMM program so rounding matches
+fn amount_delta_0(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u64> {
+    let (lower, upper) = if sqrt_price_a > sqrt_price_b { (sqrt_price_b, sqrt_price_a) } else { (sqrt_price_a, sqrt_price_b) };
+    require!(lower > 0, ErrorCode::SqrtPriceOutOfBounds);
+    let numerator = U256::mul_u128(liquidity, upper - lower)
+        .checked_shl_64()
+        .ok_or(PyroSwapError::MathOverflow)?;
+    let divide = |value: U256, divisor: u128| -> Result<U256> {
+        let (quotient, remainder) = value.checked_div_rem(U256::from_u128(divisor)).ok_or(PyroSwapError::MathOverflow)?;
+        if round_up && !remainder.is_zero() {
+            Ok(quotient.checked_add(U256::ONE).ok_or(PyroSwapError::MathOverflow)?)
+        } else {
+            Ok(quotient)
+        }
+    };
+    let amount = divide(divide(numerator, upper)?, lower)?;
+    amount
+        .to_u128()
+        .and_then(|amount| u64::try_from(amount).ok())
+        .ok_or(PyroSwapError::MathOverflow.into())
+}
+
+/// Token 1 between two prices: `L * (b - a) >> 64`
+fn amount_delta_1(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u64> {
+    let (lower, upper) = if sqrt_price_a > sqrt_price_b { (sqrt_price_b, sqrt_price_a) } else { (sqrt_price_a, sqrt_price_b) };
+    mul_div(liquidity, upper - lower, 1 << 64, round_up)
+        .and_then(|amount| u64::try_from(amount).ok())
+        .ok_or(PyroSwapError::MathOverflow.into())
+}
+
+/// Price after adding `amount` of the input token: token 0 rounds the price
+/// up, token 1 rounds it down, both in the pool's favour
+fn next_sqrt_price_from_input(sqrt_price: u128, liquidity: u128, amount: u64, zero_for_one: bool) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price);
+    }
+    require!(liquidity > 0, PyroSwapError::InsufficientLiquidity);
+    if zero_for_one {
+        let liquidity_shifted = U256::from_u128(liquidity).checked_shl_64().ok_or(PyroSwapError::MathOverflow)?;
+        let numerator = U256::mul_u128(liquidity, sqrt_price).checked_shl_64().ok_or(PyroSwapError::MathOverflow)?;
+        let denominator = liquidity_shifted
+            .checked_add(U256::mul_u128(amount as u128, sqrt_price))
+            .ok_or(PyroSwapError::MathOverflow)?;
+        let (quotient, remainder) = numerator.checked_div_rem(denominator).ok_or(PyroSwapError::MathOverflow)?;
+        let quotient = if remainder.is_zero() { quotient } else { quotient.checked_add(U256::ONE).ok_or(PyroSwapError::MathOverflow)? };
+        Ok(quotient.to_u128().ok_or(PyroSwapError::MathOverflow)?)
+    } else {
+        let quotient = ((amount as u128) << 64) / liquidity;
+        Ok(sqrt_price.checked_add(quotient).ok_or(PyroSwapError::MathOverflow)?)
+    }
+}
+
+/// `tick_math::get_sqrt_price_at_tick`: sqrt(1.0001^tick) as Q64.64
+fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    const RATIOS: [u128; 18] = [
+        0xfff97272373d4000, 0xfff2e50f5f657000, 0xffe5caca7e10f000, 0xffcb9843d60f7000,
+        0xff973b41fa98e800, 0xff2ea16466c9b000, 0xfe5dee046a9a3800, 0xfcbe86c7900bb000,
+        0xf987a7253ac65800, 0xf3392b0822bb6000, 0xe7159475a2caf000, 0xd097f3bdfd2f2000,
+        0xa9f746462d9f8000, 0x70d869a156f31c00, 0x31be135f97ed3200, 0x9aa508b5b85a500,
+        0x5d6af8dedc582c, 0x2216e584f5fa,
+    ];
+    let abs_tick = tick.unsigned_abs();
+    require!(abs_tick <= CLMM_MAX_TICK as u32, ErrorCode::SqrtPriceOutOfBounds);
+
+    let mut ratio: u128 = if abs_tick & 1 != 0 { 0xfffcb933bd6fb800 } else { 1 << 64 };
+    for (bit, factor) in RATIOS.iter().enumerate() {
+        if abs_tick & (2 << bit) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+    if tick > 0 {
+        ratio = u128::MAX / ratio;
+    }
+    Ok(ratio)
+}
+
+fn read_i32(data: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid number of accounts provided")]
+    InvalidAccountsLength,
+    #[msg("Account does not match the Raydium CLMM pool")]
+    InvalidPoolAccount,
+    #[msg("Raydium CLMM pool is not open for swaps")]
+    PoolNotSwappable,
+    #[msg("Source mint is not traded by this pool")]
+    MintMismatch,
+    #[msg("Tick array does not belong to the pool")]
+    InvalidTickArray,
+    #[msg("Swap needs a tick array that was not supplied")]
+    NotEnoughTickArrays,
+    #[msg("Sqrt price out of bounds")]
+    SqrtPriceOutOfBounds,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+
+    /// Liquidity positions as `(tick, liquidity_net)` per tick array start:
+    /// 2e12 over [-1000, 500] and 3e12 over [-300, 200]
+    fn positions() -> Vec<(i32, Vec<(i32, i128)>)> {
+        vec![
+            (-1200, vec![(-1000, 2_000_000_000_000)]),
+            (-600, vec![(-300, 3_000_000_000_000)]),
+            (0, vec![(200, -3_000_000_000_000), (500, -2_000_000_000_000)]),
+        ]
+    }
+
+    /// Spacing 10, 0.25% fee, sitting at tick -12 with both positions in range
+    fn pool_info() -> ClmmPoolInfo {
+        let mut pool = ClmmPoolInfo {
+            amm_config: Pubkey::new_unique(),
+            token_mint_0: Pubkey::new_unique(),
+            token_mint_1: Pubkey::new_unique(),
+            token_vault_0: Pubkey::new_unique(),
+            token_vault_1: Pubkey::new_unique(),
+            observation_key: Pubkey::new_unique(),
+            tick_spacing: 10,
+            liquidity: 5_000_000_000_000,
+            sqrt_price_x64: 18_435_679_900_048_813_005,
+            tick_current: -12,
+            status: 0,
+            tick_array_bitmap: [0; 16],
+            open_time: 1_700_000_000,
+        };
+        for (start, _) in positions() {
+            let bit = (start / 600 + BITMAP_HALF_RANGE) as usize;
+            pool.tick_array_bitmap[bit / 64] |= 1 << (bit % 64);
+        }
+        pool
+    }
+
+    fn tick_array_data(pool: &Pubkey, start: i32, ticks: &[(i32, i128)]) -> Vec<u8> {
+        let mut data = vec![0u8; CLMM_TICK_ARRAY_LEN];
+        data[..8].copy_from_slice(&TICK_ARRAY_DISCRIMINATOR);
+        write_pubkey(&mut data, TICK_ARRAY_POOL_OFFSET, pool);
+        data[START_TICK_INDEX_OFFSET..START_TICK_INDEX_OFFSET + 4].copy_from_slice(&start.to_le_bytes());
+        for i in 0..CLMM_TICK_ARRAY_SIZE {
+            let offset = TICKS_OFFSET + i as usize * TICK_LEN;
+            data[offset..offset + 4].copy_from_slice(&(start + i * 10).to_le_bytes());
+        }
+        for (tick, liquidity_net) in ticks {
+            let offset = TICKS_OFFSET + ((tick - start) / 10) as usize * TICK_LEN;
+            data[offset + TICK_LIQUIDITY_NET_OFFSET..offset + TICK_LIQUIDITY_NET_OFFSET + 16]
+                .copy_from_slice(&liquidity_net.to_le_bytes());
+            data[offset + TICK_LIQUIDITY_GROSS_OFFSET..offset + TICK_LIQUIDITY_GROSS_OFFSET + 16]
+                .copy_from_slice(&liquidity_net.unsigned_abs().to_le_bytes());
+        }
+        data
+    }
+
+    fn tick_arrays(pool: &Pubkey) -> Vec<ClmmTickArray> {
+        positions()
+            .iter()
+            .map(|(start, ticks)| ClmmTickArray::from_slice(&tick_array_data(pool, *start, ticks)))
+            .collect()
+    }
+
+    fn pool_data(pool: &ClmmPoolInfo) -> Vec<u8> {
+        let mut data = vec![0u8; CLMM_POOL_STATE_LEN];
+        data[..8].copy_from_slice(&POOL_STATE_DISCRIMINATOR);
+        write_pubkey(&mut data, AMM_CONFIG_OFFSET, &pool.amm_config);
+        write_pubkey(&mut data, TOKEN_MINT_0_OFFSET, &pool.token_mint_0);
+        write_pubkey(&mut data, TOKEN_MINT_1_OFFSET, &pool.token_mint_1);
+        write_pubkey(&mut data, TOKEN_VAULT_0_OFFSET, &pool.token_vault_0);
+        write_pubkey(&mut data, TOKEN_VAULT_1_OFFSET, &pool.token_vault_1);
+        write_pubkey(&mut data, OBSERVATION_KEY_OFFSET, &pool.observation_key);
+        data[TICK_SPACING_OFFSET..TICK_SPACING_OFFSET + 2].copy_from_slice(&pool.tick_spacing.to_le_bytes());
+        data[LIQUIDITY_OFFSET..LIQUIDITY_OFFSET + 16].copy_from_slice(&pool.liquidity.to_le_bytes());
+        data[SQRT_PRICE_OFFSET..SQRT_PRICE_OFFSET + 16].copy_from_slice(&pool.sqrt_price_x64.to_le_bytes());
+        data[TICK_CURRENT_OFFSET..TICK_CURRENT_OFFSET + 4].copy_from_slice(&pool.tick_current.to_le_bytes());
+        data[STATUS_OFFSET] = pool.status;
+        for (i, word) in pool.tick_array_bitmap.iter().enumerate() {
+            write_u64(&mut data, TICK_ARRAY_BITMAP_OFFSET + i * 8, *word);
+        }
+        write_u64(&mut data, OPEN_TIME_OFFSET, pool.open_time);
+        data
+    }
+
+    fn swap(pool: &ClmmPoolInfo, amount_in: u64, zero_for_one: bool) -> Result<ClmmSwapResult> {
+        clmm_swap_base_input(pool, &tick_arrays(&Pubkey::new_unique()), amount_in, 2500, zero_for_one)
+    }
+
+    #[test]
+    fn sqrt_price_matches_tick_bounds() {
+        assert_eq!(sqrt_price_at_tick(CLMM_MIN_TICK).unwrap(), CLMM_MIN_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_at_tick(CLMM_MAX_TICK).unwrap(), CLMM_MAX_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), 1 << 64);
+        assert!(sqrt_price_at_tick(CLMM_MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn finds_initialized_tick_arrays_in_the_bitmap() {
+        let pool = pool_info();
+        assert_eq!(pool.tick_array_start_index(-12), -600);
+        assert_eq!(pool.tick_array_start_index(600), 600);
+        assert!(pool.is_tick_array_initialized(-600));
+        assert!(!pool.is_tick_array_initialized(600));
+        assert_eq!(pool.next_initialized_tick_array(-600, true), Some(-1200));
+        assert_eq!(pool.next_initialized_tick_array(-600, false), Some(0));
+        assert_eq!(pool.next_initialized_tick_array(0, false), None);
+        assert_eq!(pool.next_initialized_tick_array(-1200, true), None);
+    }
+
+    #[test]
+    fn swaps_zero_for_one_across_ticks_and_arrays() {
+        let pool = pool_info();
+        let small = swap(&pool, 1_000_000_000, true).unwrap();
+        assert_eq!(small.amount_out, 996_105_173);
+        assert_eq!(small.fee_amount, 2_500_000);
+        assert_eq!(small.end_sqrt_price_x64, 18_432_004_920_606_138_104);
+
+        // Crosses tick -300, then looks ahead into the -1200 array
+        let large = swap(&pool, 100_000_000_000, true).unwrap();
+        assert_eq!(large.amount_out, 97_474_673_817);
+        assert_eq!(large.fee_amount, 250_000_001);
+        assert_eq!(large.end_sqrt_price_x64, 17_931_972_376_337_180_046);
+
+        // Running out of initialized arrays fails rather than under-quoting
+        assert!(swap(&pool, 300_000_000_000, true).is_err());
+    }
+
+    #[test]
+    fn swaps_one_for_zero_into_the_next_array() {
+        let pool = pool_info();
+        let small = swap(&pool, 1_000_000_000, false).unwrap();
+        assert_eq!(small.amount_out, 998_498_338);
+        assert_eq!(small.end_sqrt_price_x64, 18_439_360_025_491_518_060);
+
+        let large = swap(&pool, 70_000_000_000, false).unwrap();
+        assert_eq!(large.amount_out, 68_866_336_260);
+        assert_eq!(large.fee_amount, 175_000_001);
+
+        assert!(swap(&pool, 100_000_000_000, false).is_err());
+    }
+
+    #[test]
+    fn starts_from_the_next_array_when_the_current_one_is_empty() {
+        // Above every position, with no liquidity in range
+        let pool = ClmmPoolInfo {
+            tick_current: 700,
+            sqrt_price_x64: 19_103_778_296_503_384_316,
+            liquidity: 0,
+            ..pool_info()
+        };
+        let result = swap(&pool, 10_000_000_000, true).unwrap();
+        assert_eq!(result.amount_out, 10_433_050_928);
+        assert_eq!(result.fee_amount, 25_000_000);
+    }
+
+    #[test]
+    fn requires_every_array_the_swap_visits() {
+        let pool = pool_info();
+        let arrays: Vec<_> = tick_arrays(&Pubkey::new_unique())
+            .into_iter()
+            .filter(|array| array.start_tick_index != -1200)
+            .collect();
+        assert!(clmm_swap_base_input(&pool, &arrays, 1_000_000_000, 2500, true).is_ok());
+        assert!(clmm_swap_base_input(&pool, &arrays, 100_000_000_000, 2500, true).is_err());
+    }
+
+    #[test]
+    fn quotes_from_the_swap_slice() {
+        let pool = pool_info();
+        let pool_key = Pubkey::new_unique();
+        let mut config = vec![0u8; CLMM_AMM_CONFIG_LEN];
+        config[..8].copy_from_slice(&AMM_CONFIG_DISCRIMINATOR);
+        config[TRADE_FEE_RATE_OFFSET..TRADE_FEE_RATE_OFFSET + 4].copy_from_slice(&2500u32.to_le_bytes());
+        let spl_mint = |key| FixtureAccount::new(key, anchor_spl::token::ID, vec![0u8; 82]);
+
+        let mut accounts: Vec<FixtureAccount> = (0..TICK_ARRAYS_START).map(|_| unused_account()).collect();
+        accounts[AMM_CONFIG_INDEX] = FixtureAccount::new(pool.amm_config, RAYDIUM_CLMM_PROGRAM_ID, config);
+        accounts[POOL_STATE_INDEX] = FixtureAccount::new(pool_key, RAYDIUM_CLMM_PROGRAM_ID, pool_data(&pool));
+        accounts[INPUT_VAULT_INDEX] = unused_account_with_key(pool.token_vault_0);
+        accounts[OUTPUT_VAULT_INDEX] = unused_account_with_key(pool.token_vault_1);
+        accounts[OBSERVATION_INDEX] = unused_account_with_key(pool.observation_key);
+        accounts[INPUT_MINT_INDEX] = spl_mint(pool.token_mint_0);
+        accounts[OUTPUT_MINT_INDEX] = spl_mint(pool.token_mint_1);
+        accounts[INPUT_TOKEN_ACCOUNT_INDEX] = token_account(Pubkey::new_unique(), pool.token_mint_0, 0);
+        accounts[SWAP_ACCOUNTS_LEN] = unused_account_with_key(RAYDIUM_CLMM_PROGRAM_ID);
+        // A bitmap extension ahead of the tick arrays is passed through untouched
+        accounts.push(FixtureAccount::new(Pubkey::new_unique(), RAYDIUM_CLMM_PROGRAM_ID, vec![0u8; 1832]));
+        for (start, ticks) in positions() {
+            accounts.push(FixtureAccount::new(Pubkey::new_unique(), RAYDIUM_CLMM_PROGRAM_ID, tick_array_data(&pool_key, start, &ticks)));
+        }
+
+        let clock = Clock { unix_timestamp: 1_700_000_100, ..Clock::default() };
+        let quote = quote_clmm(100_000_000_000, &pool.token_mint_0, &infos(&mut accounts), &clock).unwrap();
+        assert_eq!(quote.amount_out, 97_474_673_817);
+        assert_eq!(quote.trade_fee, 250_000_001);
+        assert_eq!(quote.input_transfer_fee + quote.output_transfer_fee, 0);
+        assert!(RaydiumClmmAdapter.validate_accounts(&pool.token_mint_0, &infos(&mut accounts)).is_ok());
+        assert!(RaydiumClmmAdapter.validate_accounts(&pool.token_mint_1, &infos(&mut accounts)).is_err());
+
+        // Tick arrays from another pool are rejected
+        let last = accounts.len() - 1;
+        accounts[last] = FixtureAccount::new(Pubkey::new_unique(), RAYDIUM_CLMM_PROGRAM_ID, tick_array_data(&Pubkey::new_unique(), 0, &[]));
+        assert!(quote_clmm(100_000_000_000, &pool.token_mint_0, &infos(&mut accounts), &clock).is_err());
+    }
+
+    #[test]
+    fn encodes_swap_v2_args() {
+        let data = clmm_swap_instruction_data(1_000, 990);
+        assert_eq!(data.len(), 41);
+        assert_eq!(data[..8], SWAP_V2_INSTRUCTION);
+        assert_eq!(data[8..16], 1_000u64.to_le_bytes());
+        assert_eq!(data[16..24], 990u64.to_le_bytes());
+        assert_eq!(data[24..40], 0u128.to_le_bytes());
+        assert_eq!(data[40], 1);
+    }
+
+    fn unused_account_with_key(key: Pubkey) -> FixtureAccount {
+        FixtureAccount::new(key, Pubkey::default(), vec![])
+    }
+}
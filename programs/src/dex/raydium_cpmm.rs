@@ -0,0 +1,533 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use crate::dex::{calculate_price_impact, check_program_account, read_token_account, transfer_fee, DexQuote, DexAdapter};
+use crate::errors::PyroSwapError;
+use crate::state::DexType;
+
+/// Raydium CPMM (CP-Swap) Program ID
+pub const RAYDIUM_CPMM_PROGRAM_ID: Pubkey = solana_program::pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+/// `swap_base_input` instruction discriminator
+const SWAP_BASE_INPUT_INSTRUCTION: [u8; 8] = [0x8f, 0xbe, 0x5a, 0xda, 0xc4, 0x1e, 0x33, 0xde];
+
+/// `PoolState` account discriminator
+const POOL_STATE_DISCRIMINATOR: [u8; 8] = [0xf7, 0xed, 0xe3, 0xf5, 0xd7, 0xc3, 0xde, 0x46];
+
+/// `AmmConfig` account discriminator
+const AMM_CONFIG_DISCRIMINATOR: [u8; 8] = [0xda, 0xf4, 0x21, 0x68, 0xcb, 0xcb, 0x2b, 0x6f];
+
+// Positions in the swap account slice (see `RaydiumCpmmAdapter::swap`)
+const AMM_CONFIG_INDEX: usize = 2;
+const POOL_STATE_INDEX: usize = 3;
+const INPUT_TOKEN_ACCOUNT_INDEX: usize = 4;
+const OUTPUT_TOKEN_ACCOUNT_INDEX: usize = 5;
+const INPUT_VAULT_INDEX: usize = 6;
+const OUTPUT_VAULT_INDEX: usize = 7;
+const INPUT_MINT_INDEX: usize = 10;
+const OUTPUT_MINT_INDEX: usize = 11;
+const OBSERVATION_INDEX: usize = 12;
+/// Accounts passed to the `swap_base_input` instruction itself
+const SWAP_ACCOUNTS_LEN: usize = 13;
+
+/// Size of the `PoolState` account
+pub const CPMM_POOL_STATE_LEN: usize = 637;
+
+// `PoolState` field offsets (packed, after the 8 byte Anchor discriminator)
+const AMM_CONFIG_OFFSET: usize = 8;
+const TOKEN_0_VAULT_OFFSET: usize = 72;
+const TOKEN_1_VAULT_OFFSET: usize = 104;
+const TOKEN_0_MINT_OFFSET: usize = 168;
+const TOKEN_1_MINT_OFFSET: usize = 200;
+const OBSERVATION_KEY_OFFSET: usize = 296;
+const STATUS_OFFSET: usize = 329;
+const PROTOCOL_FEES_TOKEN_0_OFFSET: usize = 341;
+const PROTOCOL_FEES_TOKEN_1_OFFSET: usize = 349;
+const FUND_FEES_TOKEN_0_OFFSET: usize = 357;
+const FUND_FEES_TOKEN_1_OFFSET: usize = 365;
+const OPEN_TIME_OFFSET: usize = 373;
+
+/// Size of the `AmmConfig` account
+pub const CPMM_AMM_CONFIG_LEN: usize = 236;
+const TRADE_FEE_RATE_OFFSET: usize = 12;
+
+/// Fee rates are parts per million
+pub const CPMM_FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+
+/// `PoolStatusBitIndex::Swap`: set when swaps are disabled
+const STATUS_SWAP_DISABLED: u8 = 1 << 2;
+
+/// The parts of a CP-Swap `PoolState` needed to validate and quote a swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpmmPoolInfo {
+    pub amm_config: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub observation_key: Pubkey,
+    pub status: u8,
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    pub open_time: u64,
+}
+
+impl CpmmPoolInfo {
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, RAYDIUM_CPMM_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+        let data = account.try_borrow_data()?;
+        Self::try_from_slice(&data)
+    }
+
+    pub fn try_from_slice(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == CPMM_POOL_STATE_LEN && data[..8] == POOL_STATE_DISCRIMINATOR,
+            ErrorCode::InvalidPoolAccount
+        );
+        Ok(Self {
+            amm_config: read_pubkey(data, AMM_CONFIG_OFFSET),
+            token_0_vault: read_pubkey(data, TOKEN_0_VAULT_OFFSET),
+            token_1_vault: read_pubkey(data, TOKEN_1_VAULT_OFFSET),
+            token_0_mint: read_pubkey(data, TOKEN_0_MINT_OFFSET),
+            token_1_mint: read_pubkey(data, TOKEN_1_MINT_OFFSET),
+            observation_key: read_pubkey(data, OBSERVATION_KEY_OFFSET),
+            status: data[STATUS_OFFSET],
+            protocol_fees_token_0: read_u64(data, PROTOCOL_FEES_TOKEN_0_OFFSET),
+            protocol_fees_token_1: read_u64(data, PROTOCOL_FEES_TOKEN_1_OFFSET),
+            fund_fees_token_0: read_u64(data, FUND_FEES_TOKEN_0_OFFSET),
+            fund_fees_token_1: read_u64(data, FUND_FEES_TOKEN_1_OFFSET),
+            open_time: read_u64(data, OPEN_TIME_OFFSET),
+        })
+    }
+
+    /// Direction for selling `source_mint`
+    pub fn zero_for_one(&self, source_mint: &Pubkey) -> Result<bool> {
+        if *source_mint == self.token_0_mint {
+            Ok(true)
+        } else if *source_mint == self.token_1_mint {
+            Ok(false)
+        } else {
+            Err(ErrorCode::MintMismatch.into())
+        }
+    }
+
+    /// Swaps need the swap status bit clear and the pool past its open time
+    pub fn check_swappable(&self, now: i64) -> Result<()> {
+        require!(self.status & STATUS_SWAP_DISABLED == 0, ErrorCode::PoolNotSwappable);
+        require!(now >= self.open_time as i64, ErrorCode::PoolNotSwappable);
+        Ok(())
+    }
+
+    /// Vault balances less the protocol and fund fees they still hold
+    pub fn vault_amounts_without_fees(&self, vault_0: u64, vault_1: u64) -> Result<(u64, u64)> {
+        let fees_0 = self.protocol_fees_token_0 + self.fund_fees_token_0;
+        let fees_1 = self.protocol_fees_token_1 + self.fund_fees_token_1;
+        Ok((
+            vault_0.checked_sub(fees_0).ok_or(PyroSwapError::MathOverflow)?,
+            vault_1.checked_sub(fees_1).ok_or(PyroSwapError::MathOverflow)?,
+        ))
+    }
+
+    /// Check the swap slice against the pool for a swap in the given direction
+    pub fn validate_accounts(&self, accounts: &[AccountInfo], zero_for_one: bool) -> Result<()> {
+        let (input_vault, output_vault, input_mint, output_mint) = if zero_for_one {
+            (self.token_0_vault, self.token_1_vault, self.token_0_mint, self.token_1_mint)
+        } else {
+            (self.token_1_vault, self.token_0_vault, self.token_1_mint, self.token_0_mint)
+        };
+        require_keys_eq!(*accounts[AMM_CONFIG_INDEX].key, self.amm_config, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[INPUT_VAULT_INDEX].key, input_vault, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[OUTPUT_VAULT_INDEX].key, output_vault, ErrorCode::InvalidPoolAccount);
+        require_keys_eq!(*accounts[INPUT_MINT_INDEX].key, input_mint, ErrorCode::MintMismatch);
+        require_keys_eq!(*accounts[OUTPUT_MINT_INDEX].key, output_mint, ErrorCode::MintMismatch);
+        require_keys_eq!(*accounts[OBSERVATION_INDEX].key, self.observation_key, ErrorCode::InvalidPoolAccount);
+        Ok(())
+    }
+}
+
+/// Trade fee rate from a CP-Swap `AmmConfig`, in parts per million
+pub fn read_cpmm_trade_fee_rate(account: &AccountInfo) -> Result<u64> {
+    require_keys_eq!(*account.owner, RAYDIUM_CPMM_PROGRAM_ID, ErrorCode::InvalidPoolAccount);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() == CPMM_AMM_CONFIG_LEN && data[..8] == AMM_CONFIG_DISCRIMINATOR,
+        ErrorCode::InvalidPoolAccount
+    );
+    let rate = read_u64(&data, TRADE_FEE_RATE_OFFSET);
+    require!(rate < CPMM_FEE_RATE_DENOMINATOR, ErrorCode::InvalidPoolAccount);
+    Ok(rate)
+}
+
+/// `CurveCalculator::swap_base_input`: the trade fee rounds up, the output down.
+/// Returns `(trade_fee, amount_out)`.
+pub fn cpmm_swap_base_input(amount_in: u64, reserve_in: u64, reserve_out: u64, trade_fee_rate: u64) -> Result<(u64, u64)> {
+    let trade_fee = (amount_in as u128 * trade_fee_rate as u128).div_ceil(CPMM_FEE_RATE_DENOMINATOR as u128) as u64;
+    let amount_less_fee = (amount_in - trade_fee) as u128;
+    let amount_out = amount_less_fee * reserve_out as u128 / (reserve_in as u128 + amount_less_fee);
+    require!(amount_out > 0, PyroSwapError::InsufficientLiquidity);
+    Ok((trade_fee, amount_out as u64))
+}
+
+/// A quoted CP-Swap trade, including Token-2022 transfer fees on both legs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpmmQuote {
+    pub input_transfer_fee: u64,
+    pub trade_fee: u64,
+    pub output_transfer_fee: u64,
+    /// What arrives in the destination account
+    pub amount_out: u64,
+}
+
+/// Quote `swap_base_input` from the swap slice as of `clock`. The pool sees the
+/// input net of its transfer fee, and the output transfer fee comes off what
+/// the pool sends.
+pub fn quote_cpmm(amount_in: u64, source_mint: &Pubkey, accounts: &[AccountInfo], clock: &Clock) -> Result<CpmmQuote> {
+    require!(accounts.len() >= SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+    require!(amount_in > 0, PyroSwapError::InvalidAmount);
+
+    let pool = CpmmPoolInfo::try_from_account(&accounts[POOL_STATE_INDEX])?;
+    pool.check_swappable(clock.unix_timestamp)?;
+    let zero_for_one = pool.zero_for_one(source_mint)?;
+    pool.validate_accounts(accounts, zero_for_one)?;
+    let trade_fee_rate = read_cpmm_trade_fee_rate(&accounts[AMM_CONFIG_INDEX])?;
+
+    let (_, input_vault) = read_token_account(&accounts[INPUT_VAULT_INDEX])?;
+    let (_, output_vault) = read_token_account(&accounts[OUTPUT_VAULT_INDEX])?;
+    let (reserve_in, reserve_out) = if zero_for_one {
+        pool.vault_amounts_without_fees(input_vault, output_vault)?
+    } else {
+        let (reserve_0, reserve_1) = pool.vault_amounts_without_fees(output_vault, input_vault)?;
+        (reserve_1, reserve_0)
+    };
+
+    let input_transfer_fee = transfer_fee(&accounts[INPUT_MINT_INDEX], amount_in, clock.epoch)?;
+    let (trade_fee, swapped) = cpmm_swap_base_input(amount_in - input_transfer_fee, reserve_in, reserve_out, trade_fee_rate)?;
+    let output_transfer_fee = transfer_fee(&accounts[OUTPUT_MINT_INDEX], swapped, clock.epoch)?;
+
+    Ok(CpmmQuote {
+        input_transfer_fee,
+        trade_fee,
+        output_transfer_fee,
+        amount_out: swapped - output_transfer_fee,
+    })
+}
+
+pub struct RaydiumCpmmAdapter;
+
+impl DexAdapter for RaydiumCpmmAdapter {
+    fn dex_type(&self) -> DexType {
+        DexType::RaydiumCpmm
+    }
+
+    fn program_id(&self) -> Pubkey {
+        RAYDIUM_CPMM_PROGRAM_ID
+    }
+
+    fn validate_accounts(&self, source_mint: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        check_program_account(accounts, SWAP_ACCOUNTS_LEN, &RAYDIUM_CPMM_PROGRAM_ID)?;
+
+        let pool = CpmmPoolInfo::try_from_account(&accounts[POOL_STATE_INDEX])?;
+        pool.validate_accounts(accounts, pool.zero_for_one(source_mint)?)?;
+        let (input_mint, _) = read_token_account(&accounts[INPUT_TOKEN_ACCOUNT_INDEX])?;
+        require_keys_eq!(input_mint, *source_mint, ErrorCode::MintMismatch);
+        Ok(())
+    }
+
+    fn get_quote<'info>(
+        &self,
+        amount_in: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<DexQuote> {
+        self.validate_accounts(source_mint, accounts)?;
+        let quote = quote_cpmm(amount_in, source_mint, accounts, &Clock::get()?)?;
+
+        // Output at the current reserve ratio with the same fees, for price impact
+        let (_, reserve_in) = read_token_account(&accounts[INPUT_VAULT_INDEX])?;
+        let (_, reserve_out) = read_token_account(&accounts[OUTPUT_VAULT_INDEX])?;
+        let net_in = amount_in - quote.input_transfer_fee - quote.trade_fee;
+        let spot_out = (net_in as u128 * reserve_out as u128 / reserve_in.max(1) as u128).min(u64::MAX as u128) as u64;
+        let trade_fee_rate = read_cpmm_trade_fee_rate(&accounts[AMM_CONFIG_INDEX])?;
+
+        Ok(DexQuote {
+            dex_type: DexType::RaydiumCpmm,
+            amount_out: quote.amount_out,
+            price_impact_bps: calculate_price_impact(amount_in, quote.amount_out + quote.output_transfer_fee, spot_out),
+            fee_bps: (trade_fee_rate * 10000 / CPMM_FEE_RATE_DENOMINATOR) as u16,
+        })
+    }
+
+    fn swap<'info>(
+        &self,
+        amount_in: u64,
+        min_amount_out: u64,
+        source_mint: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        signer_seeds: Option<&[&[&[u8]]]>,
+    ) -> Result<u64> {
+        // Raydium CPMM swap_base_input CPI
+        // accounts expected:
+        // 0. Payer (signer)
+        // 1. Authority
+        // 2. AMM config
+        // 3. Pool state
+        // 4. Input token account
+        // 5. Output token account
+        // 6. Input vault
+        // 7. Output vault
+        // 8. Input token program
+        // 9. Output token program
+        // 10. Input mint
+        // 11. Output mint
+        // 12. Observation state
+        // 13. CPMM program
+
+        self.validate_accounts(source_mint, accounts)?;
+        let (_, output_before) = read_token_account(&accounts[OUTPUT_TOKEN_ACCOUNT_INDEX])?;
+
+        let mut instruction_data = Vec::with_capacity(24);
+        instruction_data.extend_from_slice(&SWAP_BASE_INPUT_INSTRUCTION);
+        instruction_data.extend_from_slice(&amount_in.to_le_bytes());
+        instruction_data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+        let instruction = anchor_lang::solana_program::instruction::Instruction {
+            program_id: RAYDIUM_CPMM_PROGRAM_ID,
+            accounts: accounts[..SWAP_ACCOUNTS_LEN].iter().map(|a| {
+                anchor_lang::solana_program::instruction::AccountMeta {
+                    pubkey: *a.key,
+                    is_signer: a.is_signer,
+                    is_writable: a.is_writable,
+                }
+            }).collect(),
+            data: instruction_data,
+        };
+
+        if let Some(seeds) = signer_seeds {
+            anchor_lang::solana_program::program::invoke_signed(&instruction, accounts, seeds)?;
+        } else {
+            invoke(&instruction, accounts)?;
+        }
+
+        let (_, output_after) = read_token_account(&accounts[OUTPUT_TOKEN_ACCOUNT_INDEX])?;
+        Ok(output_after.saturating_sub(output_before))
+    }
+
+    fn destination_account<'a, 'info>(
+        &self,
+        _source_mint: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[OUTPUT_TOKEN_ACCOUNT_INDEX])
+    }
+
+    fn pool_account<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> Result<&'a AccountInfo<'info>> {
+        require!(accounts.len() > SWAP_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+        Ok(&accounts[POOL_STATE_INDEX])
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid number of accounts provided")]
+    InvalidAccountsLength,
+    #[msg("Account does not match the Raydium CPMM pool")]
+    InvalidPoolAccount,
+    #[msg("Raydium CPMM pool is not open for swaps")]
+    PoolNotSwappable,
+    #[msg("Source mint is not traded by this pool")]
+    MintMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+
+    struct Pool {
+        info: CpmmPoolInfo,
+        key: Pubkey,
+    }
+
+    /// 1,000,000 token 0 (plus 1.5 in uncollected fees) against 50,000 token 1, 0.25% fee
+    fn pool() -> Pool {
+        Pool {
+            key: Pubkey::new_unique(),
+            info: CpmmPoolInfo {
+                amm_config: Pubkey::new_unique(),
+                token_0_vault: Pubkey::new_unique(),
+                token_1_vault: Pubkey::new_unique(),
+                token_0_mint: Pubkey::new_unique(),
+                token_1_mint: Pubkey::new_unique(),
+                observation_key: Pubkey::new_unique(),
+                status: 0,
+                protocol_fees_token_0: 1_000_000,
+                protocol_fees_token_1: 0,
+                fund_fees_token_0: 500_000,
+                fund_fees_token_1: 0,
+                open_time: 1_700_000_000,
+            },
+        }
+    }
+
+    const VAULT_0: u64 = 1_000_001_500_000;
+    const VAULT_1: u64 = 50_000_000_000;
+
+    fn pool_data(pool: &CpmmPoolInfo) -> Vec<u8> {
+        let mut data = vec![0u8; CPMM_POOL_STATE_LEN];
+        data[..8].copy_from_slice(&POOL_STATE_DISCRIMINATOR);
+        write_pubkey(&mut data, AMM_CONFIG_OFFSET, &pool.amm_config);
+        write_pubkey(&mut data, TOKEN_0_VAULT_OFFSET, &pool.token_0_vault);
+        write_pubkey(&mut data, TOKEN_1_VAULT_OFFSET, &pool.token_1_vault);
+        write_pubkey(&mut data, TOKEN_0_MINT_OFFSET, &pool.token_0_mint);
+        write_pubkey(&mut data, TOKEN_1_MINT_OFFSET, &pool.token_1_mint);
+        write_pubkey(&mut data, OBSERVATION_KEY_OFFSET, &pool.observation_key);
+        data[STATUS_OFFSET] = pool.status;
+        write_u64(&mut data, PROTOCOL_FEES_TOKEN_0_OFFSET, pool.protocol_fees_token_0);
+        write_u64(&mut data, PROTOCOL_FEES_TOKEN_1_OFFSET, pool.protocol_fees_token_1);
+        write_u64(&mut data, FUND_FEES_TOKEN_0_OFFSET, pool.fund_fees_token_0);
+        write_u64(&mut data, FUND_FEES_TOKEN_1_OFFSET, pool.fund_fees_token_1);
+        write_u64(&mut data, OPEN_TIME_OFFSET, pool.open_time);
+        data
+    }
+
+    fn config_data(trade_fee_rate: u64) -> Vec<u8> {
+        let mut data = vec![0u8; CPMM_AMM_CONFIG_LEN];
+        data[..8].copy_from_slice(&AMM_CONFIG_DISCRIMINATOR);
+        write_u64(&mut data, TRADE_FEE_RATE_OFFSET, trade_fee_rate);
+        data
+    }
+
+    fn spl_mint(key: Pubkey) -> FixtureAccount {
+        let mut data = vec![0u8; 82];
+        data[45] = 1;
+        FixtureAccount::new(key, anchor_spl::token::ID, data)
+    }
+
+    /// The swap slice selling token 0 (`zero_for_one`) or token 1, with the
+    /// given mint accounts for token 0 and token 1
+    fn swap_accounts(pool: &Pool, zero_for_one: bool, mint_0: FixtureAccount, mint_1: FixtureAccount) -> Vec<FixtureAccount> {
+        let info = &pool.info;
+        let vault_0 = token_account(info.token_0_vault, info.token_0_mint, VAULT_0);
+        let vault_1 = token_account(info.token_1_vault, info.token_1_mint, VAULT_1);
+        let (input_vault, output_vault, input_mint, output_mint) = if zero_for_one {
+            (vault_0, vault_1, mint_0, mint_1)
+        } else {
+            (vault_1, vault_0, mint_1, mint_0)
+        };
+
+        let mut accounts: Vec<FixtureAccount> = (0..SWAP_ACCOUNTS_LEN + 1).map(|_| unused_account()).collect();
+        accounts[AMM_CONFIG_INDEX] = FixtureAccount::new(info.amm_config, RAYDIUM_CPMM_PROGRAM_ID, config_data(2500));
+        accounts[POOL_STATE_INDEX] = FixtureAccount::new(pool.key, RAYDIUM_CPMM_PROGRAM_ID, pool_data(info));
+        accounts[INPUT_TOKEN_ACCOUNT_INDEX] = token_account(Pubkey::new_unique(), input_mint.key, 0);
+        accounts[INPUT_VAULT_INDEX] = input_vault;
+        accounts[OUTPUT_VAULT_INDEX] = output_vault;
+        accounts[INPUT_MINT_INDEX] = input_mint;
+        accounts[OUTPUT_MINT_INDEX] = output_mint;
+        accounts[OBSERVATION_INDEX] = FixtureAccount::new(info.observation_key, RAYDIUM_CPMM_PROGRAM_ID, vec![]);
+        accounts[SWAP_ACCOUNTS_LEN] = FixtureAccount::new(RAYDIUM_CPMM_PROGRAM_ID, Pubkey::default(), vec![]);
+        accounts
+    }
+
+    fn clock() -> Clock {
+        Clock { unix_timestamp: 1_700_000_100, epoch: 600, ..Clock::default() }
+    }
+
+    #[test]
+    fn parses_pool_and_config() {
+        let pool = pool();
+        assert_eq!(CpmmPoolInfo::try_from_slice(&pool_data(&pool.info)).unwrap(), pool.info);
+        assert_eq!(
+            pool.info.vault_amounts_without_fees(VAULT_0, VAULT_1).unwrap(),
+            (1_000_000_000_000, 50_000_000_000)
+        );
+
+        let mut data = pool_data(&pool.info);
+        data[0] ^= 1;
+        assert!(CpmmPoolInfo::try_from_slice(&data).is_err());
+
+        let mut config = FixtureAccount::new(Pubkey::new_unique(), RAYDIUM_CPMM_PROGRAM_ID, config_data(2500));
+        assert_eq!(read_cpmm_trade_fee_rate(&config.info()).unwrap(), 2500);
+        let mut foreign = FixtureAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), config_data(2500));
+        assert!(read_cpmm_trade_fee_rate(&foreign.info()).is_err());
+    }
+
+    #[test]
+    fn swaps_base_input_on_the_constant_product() {
+        assert_eq!(cpmm_swap_base_input(1_000_000_000, 1_000_000_000_000, 50_000_000_000, 2500).unwrap(), (2_500_000, 49_825_299));
+        // Half the reserve in moves the price a long way
+        assert_eq!(
+            cpmm_swap_base_input(1_000_000_000_000, 1_000_000_000_000, 50_000_000_000, 2500).unwrap(),
+            (2_500_000_000, 24_968_710_888)
+        );
+        assert!(cpmm_swap_base_input(1, 1_000_000_000_000, 50_000_000_000, 2500).is_err());
+    }
+
+    #[test]
+    fn quotes_spl_pools_in_both_directions() {
+        let pool = pool();
+        let (mint_0, mint_1) = (spl_mint(pool.info.token_0_mint), spl_mint(pool.info.token_1_mint));
+        let mut accounts = swap_accounts(&pool, true, mint_0, mint_1);
+        let quote = quote_cpmm(1_000_000_000, &pool.info.token_0_mint, &infos(&mut accounts), &clock()).unwrap();
+        assert_eq!(quote, CpmmQuote { input_transfer_fee: 0, trade_fee: 2_500_000, output_transfer_fee: 0, amount_out: 49_825_299 });
+
+        // The slice is ordered by direction, so it cannot be reused the other way
+        assert!(quote_cpmm(1_000_000_000, &pool.info.token_1_mint, &infos(&mut accounts), &clock()).is_err());
+        assert!(quote_cpmm(1_000_000_000, &Pubkey::new_unique(), &infos(&mut accounts), &clock()).is_err());
+    }
+
+    #[test]
+    fn accounts_for_token_2022_transfer_fees() {
+        let pool = pool();
+        // 1% transfer fee on token 1, capped at 5 tokens
+        let fee_mint = || transfer_fee_mint(pool.info.token_1_mint, 100, 5_000_000);
+
+        let mut accounts = swap_accounts(&pool, false, spl_mint(pool.info.token_0_mint), fee_mint());
+        let quote = quote_cpmm(100_000_000, &pool.info.token_1_mint, &infos(&mut accounts), &clock()).unwrap();
+        assert_eq!(quote.input_transfer_fee, 1_000_000);
+        assert_eq!(quote.trade_fee, 247_500);
+        assert_eq!(quote.amount_out, 1_971_156_866);
+
+        let mut accounts = swap_accounts(&pool, true, spl_mint(pool.info.token_0_mint), fee_mint());
+        let quote = quote_cpmm(1_000_000_000, &pool.info.token_0_mint, &infos(&mut accounts), &clock()).unwrap();
+        assert_eq!(quote.output_transfer_fee, 498_253);
+        assert_eq!(quote.amount_out, 49_327_046);
+    }
+
+    #[test]
+    fn refuses_closed_pools() {
+        let mut pool = pool();
+        let early = Clock { unix_timestamp: 1_600_000_000, ..clock() };
+        let mut accounts = swap_accounts(&pool, true, spl_mint(pool.info.token_0_mint), spl_mint(pool.info.token_1_mint));
+        assert!(quote_cpmm(1_000_000_000, &pool.info.token_0_mint, &infos(&mut accounts), &early).is_err());
+
+        pool.info.status = STATUS_SWAP_DISABLED;
+        let mut accounts = swap_accounts(&pool, true, spl_mint(pool.info.token_0_mint), spl_mint(pool.info.token_1_mint));
+        assert!(quote_cpmm(1_000_000_000, &pool.info.token_0_mint, &infos(&mut accounts), &clock()).is_err());
+    }
+
+    #[test]
+    fn validates_the_program_and_input_account() {
+        let pool = pool();
+        let swap_accounts = || swap_accounts(&pool, true, spl_mint(pool.info.token_0_mint), spl_mint(pool.info.token_1_mint));
+        let mut accounts = swap_accounts();
+        assert!(RaydiumCpmmAdapter.validate_accounts(&pool.info.token_0_mint, &infos(&mut accounts)).is_ok());
+
+        accounts[SWAP_ACCOUNTS_LEN] = unused_account();
+        assert!(RaydiumCpmmAdapter.validate_accounts(&pool.info.token_0_mint, &infos(&mut accounts)).is_err());
+
+        let mut accounts = swap_accounts();
+        accounts[INPUT_TOKEN_ACCOUNT_INDEX] = token_account(Pubkey::new_unique(), pool.info.token_1_mint, 0);
+        assert!(RaydiumCpmmAdapter.validate_accounts(&pool.info.token_0_mint, &infos(&mut accounts)).is_err());
+    }
+}
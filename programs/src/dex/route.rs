@@ -0,0 +1,104 @@
+use std::ops::Range;
+use anchor_lang::prelude::*;
+use crate::constants::MAX_ROUTE_LEGS;
+use crate::dex::{adapter_for, read_token_account};
+use crate::errors::PyroSwapError;
+use crate::state::RouteLeg;
+
+/// Split the remaining accounts into one swap slice per leg. The legs must
+/// account for every remaining account, in order.
+pub fn route_slices(route: &[RouteLeg], accounts_len: usize) -> Result<Vec<Range<usize>>> {
+    require!(!route.is_empty() && route.len() <= MAX_ROUTE_LEGS, PyroSwapError::InvalidRoute);
+    account_slices(route.iter().map(|leg| leg.accounts_len), accounts_len)
+        .ok_or(PyroSwapError::InvalidRoute.into())
+}
+
+/// Consecutive non-empty slices of the given lengths that exactly cover
+/// `accounts_len` accounts
+pub(crate) fn account_slices(lengths: impl Iterator<Item = u8>, accounts_len: usize) -> Option<Vec<Range<usize>>> {
+    let mut start = 0;
+    let mut slices = Vec::new();
+    for len in lengths {
+        if len == 0 {
+            return None;
+        }
+        slices.push(start..start + len as usize);
+        start += len as usize;
+    }
+    (start == accounts_len).then_some(slices)
+}
+
+/// Run `route` hop by hop, selling `source_mint` and delivering to
+/// `destination`. Each leg sells exactly what the previous one delivered,
+/// measured from its output token account, and only the end-to-end output is
+/// held to `min_amount_out`.
+pub fn execute_route<'info>(
+    route: &[RouteLeg],
+    amount_in: u64,
+    min_amount_out: u64,
+    source_mint: &Pubkey,
+    destination: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    let slices = route_slices(route, accounts.len())?;
+
+    let mut mint = *source_mint;
+    let mut amount = amount_in;
+    for (i, (leg, slice)) in route.iter().zip(slices).enumerate() {
+        let leg_accounts = &accounts[slice];
+        let is_last = i + 1 == route.len();
+
+        let adapter = adapter_for(leg.dex_type);
+        let output = adapter.destination_account(&mint, leg_accounts)?;
+        if is_last {
+            require_keys_eq!(*output.key, *destination, PyroSwapError::InvalidRoute);
+        }
+        let (output_mint, output_before) = read_token_account(output)?;
+        require_keys_neq!(output_mint, mint, PyroSwapError::InvalidRoute);
+
+        // Intermediate hops take what the market gives; the route as a whole is checked below
+        let leg_min_out = if is_last { min_amount_out } else { 0 };
+        adapter.swap(amount, leg_min_out, &mint, leg_accounts, signer_seeds)?;
+
+        let (_, output_after) = read_token_account(output)?;
+        amount = output_after.checked_sub(output_before).ok_or(PyroSwapError::MathOverflow)?;
+        msg!("Route leg {} via {:?}: {} {} out", i, leg.dex_type, amount, output_mint);
+        mint = output_mint;
+    }
+
+    require!(amount >= min_amount_out, PyroSwapError::SlippageExceeded);
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::DexType;
+
+    fn leg(dex_type: DexType, accounts_len: u8) -> RouteLeg {
+        RouteLeg { dex_type, accounts_len }
+    }
+
+    #[test]
+    fn splits_accounts_per_leg() {
+        let route = [leg(DexType::PumpFun, 12), leg(DexType::Orca, 12), leg(DexType::RaydiumCpmm, 14)];
+        assert_eq!(route_slices(&route, 38).unwrap(), vec![0..12, 12..24, 24..38]);
+    }
+
+    #[test]
+    fn rejects_routes_that_do_not_cover_the_accounts() {
+        let route = [leg(DexType::Orca, 12), leg(DexType::Meteora, 16)];
+        assert!(route_slices(&route, 27).is_err());
+        assert!(route_slices(&route, 29).is_err());
+        assert!(route_slices(&[leg(DexType::Orca, 0), leg(DexType::Meteora, 16)], 16).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_and_overlong_routes() {
+        assert!(route_slices(&[], 0).is_err());
+        let route = vec![leg(DexType::Lifinity, 14); MAX_ROUTE_LEGS + 1];
+        assert!(route_slices(&route, 14 * (MAX_ROUTE_LEGS + 1)).is_err());
+        assert!(route_slices(&route[..MAX_ROUTE_LEGS], 14 * MAX_ROUTE_LEGS).is_ok());
+    }
+}
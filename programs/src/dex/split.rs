@@ -0,0 +1,235 @@
+use std::ops::Range;
+use anchor_lang::prelude::*;
+use crate::constants::MAX_SPLIT_LEGS;
+use crate::dex::{adapter_for, read_token_account};
+use crate::dex::route::account_slices;
+use crate::errors::PyroSwapError;
+use crate::state::{DexType, SplitLeg};
+
+/// Check a split plan and cut the remaining accounts into one swap slice per
+/// leg. Shares are basis points of the input and must add up to 10000.
+pub fn split_slices(legs: &[SplitLeg], accounts_len: usize) -> Result<Vec<Range<usize>>> {
+    require!(!legs.is_empty() && legs.len() <= MAX_SPLIT_LEGS, PyroSwapError::InvalidSplit);
+    require!(legs.iter().all(|leg| leg.share_bps > 0), PyroSwapError::InvalidSplit);
+    require!(
+        legs.iter().map(|leg| leg.share_bps as u32).sum::<u32>() == 10000,
+        PyroSwapError::InvalidSplit
+    );
+    account_slices(legs.iter().map(|leg| leg.accounts_len), accounts_len)
+        .ok_or(PyroSwapError::InvalidSplit.into())
+}
+
+/// Input for each leg. Shares round down and the last leg takes the
+/// remainder, so the legs always spend exactly `amount_in`.
+pub fn split_amounts(amount_in: u64, legs: &[SplitLeg]) -> Vec<u64> {
+    let mut remaining = amount_in;
+    legs.iter()
+        .enumerate()
+        .map(|(i, leg)| {
+            let amount = if i + 1 == legs.len() {
+                remaining
+            } else {
+                (amount_in as u128 * leg.share_bps as u128 / 10000) as u64
+            };
+            remaining -= amount;
+            amount
+        })
+        .collect()
+}
+
+/// Sell `amount_in` of `source_mint` across every leg of the plan, each
+/// paying into `destination`. Legs are measured one by one and only their
+/// combined output is held to `min_amount_out`.
+pub fn execute_split<'info>(
+    legs: &[SplitLeg],
+    amount_in: u64,
+    min_amount_out: u64,
+    source_mint: &Pubkey,
+    destination: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    let slices = split_slices(legs, accounts.len())?;
+    let amounts = split_amounts(amount_in, legs);
+
+    let mut total_out: u64 = 0;
+    for ((leg, slice), amount) in legs.iter().zip(slices).zip(amounts) {
+        if amount == 0 {
+            continue;
+        }
+        let leg_accounts = &accounts[slice];
+        let adapter = adapter_for(leg.dex_type);
+        let output = adapter.destination_account(source_mint, leg_accounts)?;
+        require_keys_eq!(*output.key, *destination, PyroSwapError::InvalidSplit);
+        let (_, output_before) = read_token_account(output)?;
+
+        adapter.swap(amount, 0, source_mint, leg_accounts, signer_seeds)?;
+
+        let (_, output_after) = read_token_account(output)?;
+        let leg_out = output_after.checked_sub(output_before).ok_or(PyroSwapError::MathOverflow)?;
+        msg!("Split leg via {:?}: {} in, {} out", leg.dex_type, amount, leg_out);
+        total_out = total_out.checked_add(leg_out).ok_or(PyroSwapError::MathOverflow)?;
+    }
+
+    require!(total_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+    Ok(total_out)
+}
+
+/// A venue's output sampled at increasing input sizes, e.g. from repeated
+/// `get_quote` calls off-chain. Interpolated linearly from `(0, 0)` and flat
+/// past the last sample.
+#[derive(Debug, Clone)]
+pub struct QuoteCurve {
+    pub dex_type: DexType,
+    /// `(amount_in, amount_out)` sorted by `amount_in`
+    pub points: Vec<(u64, u64)>,
+}
+
+impl QuoteCurve {
+    pub fn amount_out_at(&self, amount_in: u64) -> u64 {
+        let mut previous = (0u64, 0u64);
+        for &(x, y) in &self.points {
+            if amount_in <= x {
+                if x == previous.0 {
+                    return y;
+                }
+                let slope = (y.saturating_sub(previous.1)) as u128 * (amount_in - previous.0) as u128
+                    / (x - previous.0) as u128;
+                return previous.1 + slope as u64;
+            }
+            previous = (x, y);
+        }
+        previous.1
+    }
+}
+
+/// Split `amount_in` across venues to maximise total output, in
+/// `step_bps` increments. Each increment goes to the venue whose output grows
+/// the most from it, which is optimal when the curves are concave, as AMM and
+/// order book curves are. Returns `(index into curves, share_bps)` for the
+/// curves that receive a share, so two pools on one venue stay apart; empty
+/// if `step_bps` does not evenly divide 10000.
+pub fn optimize_split(curves: &[QuoteCurve], amount_in: u64, step_bps: u16) -> Vec<(usize, u16)> {
+    if curves.is_empty() || step_bps == 0 || 10000 % step_bps != 0 {
+        return Vec::new();
+    }
+    let amount_at = |share_bps: u16| (amount_in as u128 * share_bps as u128 / 10000) as u64;
+
+    let mut shares = vec![0u16; curves.len()];
+    for _ in 0..10000 / step_bps {
+        let (best, _) = curves
+            .iter()
+            .zip(&shares)
+            .enumerate()
+            .map(|(i, (curve, &share))| {
+                let gain = curve.amount_out_at(amount_at(share + step_bps)).saturating_sub(curve.amount_out_at(amount_at(share)));
+                (i, gain)
+            })
+            // Earlier venues win ties
+            .fold((0, None), |(best, best_gain), (i, gain)| match best_gain {
+                Some(best_gain) if best_gain >= gain => (best, Some(best_gain)),
+                _ => (i, Some(gain)),
+            });
+        shares[best] += step_bps;
+    }
+
+    shares
+        .into_iter()
+        .enumerate()
+        .filter(|(_, share)| *share > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(dex_type: DexType, share_bps: u16, accounts_len: u8) -> SplitLeg {
+        SplitLeg { dex_type, share_bps, accounts_len }
+    }
+
+    /// Constant product output sampled every 5% of `amount_in`
+    fn constant_product_curve(dex_type: DexType, reserve_in: u64, reserve_out: u64, amount_in: u64) -> QuoteCurve {
+        let points = (1..=20)
+            .map(|i| {
+                let x = amount_in / 20 * i;
+                let y = (reserve_out as u128 * x as u128 / (reserve_in as u128 + x as u128)) as u64;
+                (x, y)
+            })
+            .collect();
+        QuoteCurve { dex_type, points }
+    }
+
+    #[test]
+    fn validates_split_plans() {
+        let legs = [leg(DexType::Raydium, 6000, 19), leg(DexType::Orca, 4000, 12)];
+        assert_eq!(split_slices(&legs, 31).unwrap(), vec![0..19, 19..31]);
+        assert!(split_slices(&legs, 30).is_err());
+
+        assert!(split_slices(&[leg(DexType::Raydium, 6000, 19), leg(DexType::Orca, 3000, 12)], 31).is_err());
+        assert!(split_slices(&[leg(DexType::Raydium, 10000, 19), leg(DexType::Orca, 0, 12)], 31).is_err());
+        assert!(split_slices(&[], 0).is_err());
+        let too_many = vec![leg(DexType::Orca, 2000, 12); 5];
+        assert!(split_slices(&too_many, 60).is_err());
+    }
+
+    #[test]
+    fn last_leg_takes_the_rounding_remainder() {
+        let legs = [leg(DexType::Raydium, 3333, 19), leg(DexType::Orca, 3333, 12), leg(DexType::Meteora, 3334, 16)];
+        assert_eq!(split_amounts(1001, &legs), vec![333, 333, 335]);
+        assert_eq!(split_amounts(1001, &legs).iter().sum::<u64>(), 1001);
+    }
+
+    #[test]
+    fn interpolates_quote_curves() {
+        let curve = QuoteCurve { dex_type: DexType::Orca, points: vec![(100, 90), (200, 170)] };
+        assert_eq!(curve.amount_out_at(0), 0);
+        assert_eq!(curve.amount_out_at(50), 45);
+        assert_eq!(curve.amount_out_at(150), 130);
+        assert_eq!(curve.amount_out_at(500), 170);
+    }
+
+    #[test]
+    fn splits_in_proportion_to_depth_for_equal_prices() {
+        // Same price, three times the liquidity: the optimum is 25% / 75%
+        let amount_in = 1_000_000_000;
+        let curves = [
+            constant_product_curve(DexType::Raydium, 1_000_000_000, 1_000_000_000, amount_in),
+            constant_product_curve(DexType::Orca, 3_000_000_000, 3_000_000_000, amount_in),
+        ];
+        assert_eq!(
+            optimize_split(&curves, amount_in, 500),
+            vec![(0, 2500), (1, 7500)]
+        );
+    }
+
+    #[test]
+    fn sends_small_orders_to_a_single_venue() {
+        let amount_in = 1_000_000;
+        let curves = [
+            constant_product_curve(DexType::Raydium, 1_000_000_000_000, 990_000_000_000, amount_in),
+            constant_product_curve(DexType::Meteora, 1_000_000_000_000, 1_000_000_000_000, amount_in),
+        ];
+        assert_eq!(optimize_split(&curves, amount_in, 500), vec![(1, 10000)]);
+    }
+
+    #[test]
+    fn keeps_pools_on_the_same_venue_apart() {
+        // Two Orca pools of equal depth share the order evenly
+        let amount_in = 1_000_000_000;
+        let curves = [
+            constant_product_curve(DexType::Orca, 1_000_000_000, 1_000_000_000, amount_in),
+            constant_product_curve(DexType::Raydium, 1_000, 1_000, amount_in),
+            constant_product_curve(DexType::Orca, 1_000_000_000, 1_000_000_000, amount_in),
+        ];
+        assert_eq!(optimize_split(&curves, amount_in, 500), vec![(0, 5000), (2, 5000)]);
+    }
+
+    #[test]
+    fn rejects_uneven_steps() {
+        let curves = [constant_product_curve(DexType::Orca, 1_000, 1_000, 100)];
+        assert!(optimize_split(&curves, 100, 300).is_empty());
+        assert!(optimize_split(&curves, 100, 0).is_empty());
+        assert!(optimize_split(&[], 100, 500).is_empty());
+    }
+}
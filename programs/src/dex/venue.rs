@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_VENUE_CANDIDATES;
+use crate::dex::route::account_slices;
+use crate::dex::{adapter_for, get_best_quote, DexQuote};
+use crate::errors::PyroSwapError;
+use crate::state::VenueCandidate;
+
+/// Outcome of best-venue selection
+#[derive(Debug, Clone)]
+pub struct VenueSelection {
+    /// Index of the winner in the keeper's candidate list
+    pub candidate: usize,
+    pub best: DexQuote,
+    pub losing: Vec<DexQuote>,
+}
+
+/// Pick the best of `quotes`, each tagged with its candidate index. Returns
+/// `None` when nothing quoted a positive output.
+pub fn choose_venue(quotes: Vec<(usize, DexQuote)>) -> Option<VenueSelection> {
+    let best = get_best_quote(quotes.iter().map(|(_, quote)| quote.clone()).collect())?;
+    if best.amount_out == 0 {
+        return None;
+    }
+    let winner = quotes
+        .iter()
+        .position(|(_, quote)| quote.dex_type == best.dex_type && quote.amount_out == best.amount_out)?;
+
+    let mut losing = quotes;
+    let (candidate, best) = losing.remove(winner);
+    Some(VenueSelection {
+        candidate,
+        best,
+        losing: losing.into_iter().map(|(_, quote)| quote).collect(),
+    })
+}
+
+/// Quote every candidate against its live pool state and swap on the best
+/// one. A candidate that fails to quote, such as a drained or closed pool, is
+/// left out rather than failing the whole execution. The winner must pay
+/// into `destination`.
+pub fn execute_best_venue<'info>(
+    candidates: &[VenueCandidate],
+    amount_in: u64,
+    min_amount_out: u64,
+    source_mint: &Pubkey,
+    destination: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<VenueSelection> {
+    require!(
+        !candidates.is_empty() && candidates.len() <= MAX_VENUE_CANDIDATES,
+        PyroSwapError::InvalidRoute
+    );
+    let slices = account_slices(candidates.iter().map(|candidate| candidate.accounts_len), accounts.len())
+        .ok_or(PyroSwapError::InvalidRoute)?;
+
+    let mut quotes = Vec::with_capacity(candidates.len());
+    for (i, (candidate, slice)) in candidates.iter().zip(&slices).enumerate() {
+        match adapter_for(candidate.dex_type).get_quote(amount_in, source_mint, &accounts[slice.clone()]) {
+            Ok(quote) => {
+                require!(quote.dex_type == candidate.dex_type, PyroSwapError::InvalidDexType);
+                quotes.push((i, quote));
+            }
+            Err(err) => msg!("Candidate {} ({:?}) could not quote: {}", i, candidate.dex_type, err),
+        }
+    }
+
+    let selection = choose_venue(quotes).ok_or(PyroSwapError::NoVenueQuoted)?;
+    let winner_accounts = &accounts[slices[selection.candidate].clone()];
+    let adapter = adapter_for(selection.best.dex_type);
+    let output = adapter.destination_account(source_mint, winner_accounts)?;
+    require_keys_eq!(*output.key, *destination, PyroSwapError::InvalidRoute);
+
+    msg!("Best venue {:?} quoting {}", selection.best.dex_type, selection.best.amount_out);
+    adapter.swap(amount_in, min_amount_out, source_mint, winner_accounts, signer_seeds)?;
+    Ok(selection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::DexType;
+
+    fn quote(dex_type: DexType, amount_out: u64) -> DexQuote {
+        DexQuote { dex_type, amount_out, price_impact_bps: 0, fee_bps: 25 }
+    }
+
+    #[test]
+    fn picks_the_largest_output_and_keeps_the_rest() {
+        let selection = choose_venue(vec![
+            (0, quote(DexType::Raydium, 990)),
+            (2, quote(DexType::Orca, 1_010)),
+            (3, quote(DexType::Meteora, 1_000)),
+        ])
+        .unwrap();
+        assert_eq!(selection.candidate, 2);
+        assert_eq!(selection.best.dex_type, DexType::Orca);
+        let losing: Vec<_> = selection.losing.iter().map(|quote| quote.dex_type).collect();
+        assert_eq!(losing, vec![DexType::Raydium, DexType::Meteora]);
+    }
+
+    #[test]
+    fn needs_a_positive_quote() {
+        assert!(choose_venue(vec![]).is_none());
+        assert!(choose_venue(vec![(0, quote(DexType::PumpFun, 0))]).is_none());
+    }
+}
@@ -14,6 +14,9 @@ pub enum PyroSwapError {
     #[msg("Execution fee too low")]
     ExecutionFeeTooLow,
     
+    #[msg("Execution fee escrow does not match its recorded balance")]
+    EscrowMismatch,
+    
     #[msg("Position not ready for execution")]
     PositionNotTriggered,
     
@@ -23,6 +26,18 @@ pub enum PyroSwapError {
     #[msg("Position already cancelled")]
     PositionAlreadyCancelled,
     
+    #[msg("Position status does not allow this action")]
+    InvalidStatusTransition,
+    
+    #[msg("Position has not been executed")]
+    PositionNotExecuted,
+    
+    #[msg("Position has not expired yet")]
+    PositionNotExpired,
+    
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    
     #[msg("Oracle price is stale")]
     StalePriceData,
     
@@ -32,6 +47,9 @@ pub enum PyroSwapError {
     #[msg("Slippage tolerance exceeded")]
     SlippageExceeded,
     
+    #[msg("Vault holds tokens but no destination account was provided")]
+    VaultNotEmpty,
+    
     #[msg("Insufficient liquidity")]
     InsufficientLiquidity,
     
@@ -39,6 +57,8 @@ pub enum PyroSwapError {
     InvalidDexType,
     #[msg("Invalid amount")]
     InvalidAmount,
+    #[msg("Invalid token account")]
+    InvalidTokenAccount,
     #[msg("Invalid oracle account")]
     InvalidOracleAccount,
     #[msg("Invalid oracle price")]
@@ -49,4 +69,46 @@ pub enum PyroSwapError {
     
     #[msg("Math overflow")]
     MathOverflow,
+
+    #[msg("Bonding curve has completed and no migration route is registered")]
+    MigrationRouteMissing,
+
+    #[msg("Migration route does not match the position or the supplied pool")]
+    InvalidMigrationRoute,
+
+    #[msg("Route legs do not match the supplied accounts or mints")]
+    InvalidRoute,
+
+    #[msg("Split shares must be positive and sum to 100%")]
+    InvalidSplit,
+
+    #[msg("No candidate venue could quote the swap")]
+    NoVenueQuoted,
+
+    #[msg("Swap accounts do not include the venue's program where expected")]
+    InvalidDexProgram,
+
+    #[msg("Mint has a Token-2022 extension PyroSwap cannot hold safely")]
+    UnsupportedMintExtension,
+
+    #[msg("This operation is paused")]
+    ProtocolPaused,
+
+    #[msg("This venue is paused")]
+    VenuePaused,
+
+    #[msg("This token pair is paused")]
+    PairPaused,
+
+    #[msg("Paused pair account does not match the pair's address")]
+    InvalidPausedPairAccount,
+
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("Protocol fee account is neither the fee destination's ATA nor the treasury")]
+    InvalidFeeDestination,
+
+    #[msg("Position has expired and can only be refunded")]
+    PositionExpired,
 }
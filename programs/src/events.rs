@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use crate::state::DexType;
+
+/// A candidate venue's quote, as compared during best-venue selection
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VenueQuote {
+    pub dex_type: DexType,
+    pub amount_out: u64,
+    pub price_impact_bps: u16,
+}
+
+/// `execute_position` picked a venue from the keeper's candidates
+#[event]
+pub struct BestVenueSelected {
+    pub position: Pubkey,
+    pub venue: DexType,
+    pub quoted_amount_out: u64,
+    /// Measured in the output vault
+    pub amount_out: u64,
+    pub losing_quotes: Vec<VenueQuote>,
+}
+
+/// `update_config` changed the fee schedule
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub referral_fee_share_bps: u16,
+    pub expiry_keeper_fee_bps: u16,
+}
+
+/// The admin nominated a successor, who still has to accept
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+/// The pending admin accepted and now controls the config
+#[event]
+pub struct AdminTransferred {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+/// Protocol fees now go to a different wallet
+#[event]
+pub struct FeeDestinationUpdated {
+    pub previous_fee_destination: Pubkey,
+    pub fee_destination: Pubkey,
+}
+
+/// The global or per-venue pause flags changed
+#[event]
+pub struct PauseUpdated {
+    pub authority: Pubkey,
+    pub paused: u8,
+    pub paused_venues: u16,
+}
+
+/// A token pair was paused or unpaused
+#[event]
+pub struct PairPauseUpdated {
+    pub authority: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub paused: bool,
+}
+
+/// The admin appointed a new guardian
+#[event]
+pub struct GuardianUpdated {
+    pub previous_guardian: Pubkey,
+    pub guardian: Pubkey,
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+use crate::events::AdminTransferred;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(new_admin.key()) @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub new_admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+    config.admin = ctx.accounts.new_admin.key();
+    config.pending_admin = None;
+
+    msg!("Admin handover complete: {} -> {}", previous_admin, config.admin);
+    emit!(AdminTransferred {
+        previous_admin,
+        new_admin: config.admin,
+    });
+
+    Ok(())
+}
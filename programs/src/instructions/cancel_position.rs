@@ -1,12 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
 use crate::errors::*;
+use crate::utils::{drain_escrow, sweep_and_close_vault};
 
 #[derive(Accounts)]
 pub struct CancelPosition<'info> {
     #[account(
         mut,
+        close = owner,
         seeds = [b"position", owner.key().as_ref(), position.input_mint.as_ref()],
         bump = position.bump,
         has_one = owner,
@@ -19,51 +21,109 @@ pub struct CancelPosition<'info> {
         seeds = [b"vault", position.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"output_vault", position.key().as_ref()],
+        bump
+    )]
+    pub output_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", position.key().as_ref()],
+        bump
+    )]
+    pub escrow: SystemAccount<'info>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    #[account(mut)]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    /// Omit for a wrapped SOL mint to receive it unwrapped as lamports
+    #[account(
+        mut,
+        associated_token::mint = position.input_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Only needed if someone sent output tokens into the output vault
+    #[account(
+        mut,
+        associated_token::mint = position.output_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = output_token_program
+    )]
+    pub owner_output_token: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(address = position.input_mint, mint::token_program = token_program)]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = position.output_mint, mint::token_program = output_token_program)]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token program of the input mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the output mint
+    pub output_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<CancelPosition>) -> Result<()> {
+    let clock = Clock::get()?;
     let position = &mut ctx.accounts.position;
-    
+
     // Mark position as cancelled
-    position.status = PositionStatus::Cancelled;
+    position.transition(PositionStatus::Cancelled, clock.unix_timestamp)?;
 
-    // Refund execution fee to owner
+    // Refund the whole execution fee escrow to the owner
     let execution_fee = position.execution_fee_escrow;
-    if execution_fee > 0 {
-        **position.to_account_info().try_borrow_mut_lamports()? -= execution_fee;
-        **ctx.accounts.owner.to_account_info().try_borrow_mut_lamports()? += execution_fee;
-    }
-
-    // Transfer tokens back from vault to owner
-    let vault_balance = ctx.accounts.vault.amount;
-    if vault_balance > 0 {
-        let position_key = position.key();
-        let seeds = &[
-            b"vault",
-            position_key.as_ref(),
-            &[ctx.bumps.vault],
-        ];
-        let signer = &[&seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.owner_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, vault_balance)?;
-    }
+    let position_key = position.key();
+    let escrow_seeds = &[
+        b"escrow",
+        position_key.as_ref(),
+        &[ctx.bumps.escrow],
+    ];
+    drain_escrow(
+        &ctx.accounts.system_program,
+        &ctx.accounts.escrow,
+        execution_fee,
+        None,
+        &ctx.accounts.owner.to_account_info(),
+        &[&escrow_seeds[..]],
+    )?;
+
+    // Return tokens to the owner and close both vaults
+    let seeds = &[
+        b"vault",
+        position_key.as_ref(),
+        &[ctx.bumps.vault],
+    ];
+    let signer = &[&seeds[..]];
+
+    let vault_authority = ctx.accounts.vault.to_account_info();
+    let owner = ctx.accounts.owner.to_account_info();
+    // The output vault is closed first while the vault PDA still signs as its authority
+    sweep_and_close_vault(
+        &ctx.accounts.output_token_program,
+        &ctx.accounts.output_mint,
+        &ctx.accounts.output_vault,
+        ctx.accounts.owner_output_token.as_ref(),
+        &vault_authority,
+        &owner,
+        signer,
+    )?;
+    let vault_balance = sweep_and_close_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.input_mint,
+        &ctx.accounts.vault,
+        ctx.accounts.owner_token_account.as_ref(),
+        &vault_authority,
+        &owner,
+        signer,
+    )?;
 
     msg!("Position cancelled. Refunded {} lamports execution fee", execution_fee);
     msg!("Returned {} tokens to owner", vault_balance);
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+
+#[derive(Accounts)]
+pub struct CloseMigrationRoute<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"migration_route", migration_route.mint.as_ref()],
+        bump = migration_route.bump
+    )]
+    pub migration_route: Account<'info, MigrationRoute>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CloseMigrationRoute>) -> Result<()> {
+    msg!("Closed migration route for {}", ctx.accounts.migration_route.mint);
+
+    Ok(())
+}
@@ -1,14 +1,21 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
+use crate::errors::PyroSwapError;
+use crate::constants::{MAX_SLIPPAGE_BPS, PAUSE_EXECUTIONS};
+use crate::events::{BestVenueSelected, VenueQuote};
+use crate::dex::DexAdapter;
+use crate::dex::pumpfun::{self, bonding_curve_address, curve_token_mint, BondingCurveInfo};
+use crate::utils::{amount_with_transfer_fee, drain_escrow, sweep_and_close_vault, transfer_tokens};
 
 #[derive(Accounts)]
 pub struct ExecutePosition<'info> {
     #[account(
         mut,
-        close = owner,
         seeds = [b"position", owner.key().as_ref(), position.input_mint.as_ref()],
-        bump = position.bump
+        bump = position.bump,
+        has_one = owner,
+        constraint = position.status == PositionStatus::Active @ PyroSwapError::PositionAlreadyExecuted
     )]
     pub position: Account<'info, Position>,
 
@@ -17,32 +24,108 @@ pub struct ExecutePosition<'info> {
         seeds = [b"vault", position.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: Owner receiving funds after vault closure
+    #[account(
+        mut,
+        seeds = [b"output_vault", position.key().as_ref()],
+        bump
+    )]
+    pub output_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", position.key().as_ref()],
+        bump
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    /// CHECK: Position owner, validated by `has_one`
     #[account(mut)]
     pub owner: AccountInfo<'info>,
 
+    /// Owner ATAs. When both are supplied the output is delivered and the position closed,
+    /// otherwise it stays `Executed` until the owner settles it with `withdraw_manual`.
+    /// A wrapped SOL side needs no ATA, it is unwrapped to the owner.
+    #[account(
+        mut,
+        associated_token::mint = position.input_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_input_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = position.output_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = output_token_program
+    )]
+    pub owner_output_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(mut)]
     pub executor: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
     pub config: Account<'info, GlobalConfig>,
 
+    /// CHECK: The pair's `PausedPair` PDA, which must not exist
+    pub paused_pair: UncheckedAccount<'info>,
+
     /// CHECK: Pyth price feed account
     pub oracle_price_feed: UncheckedAccount<'info>,
 
-    #[account(mut)]
-    pub protocol_fee_destination: Account<'info, TokenAccount>,
+    /// CHECK: pump.fun bonding curve for the position's token, required for
+    /// `PumpFun` positions. Checked against the curve PDA in the handler.
+    pub bonding_curve: Option<UncheckedAccount<'info>>,
+
+    /// Where the token trades after its curve completes. Only read once the
+    /// curve reports `complete`.
+    pub migration_route: Option<Account<'info, MigrationRoute>>,
+
+    /// The fee destination's ATA for the output mint, or the output mint's treasury
+    #[account(
+        mut,
+        constraint = config.is_fee_account(
+            &protocol_fee_destination.key(),
+            &position.output_mint,
+            &output_token_program.key()
+        ) @ PyroSwapError::InvalidFeeDestination
+    )]
+    pub protocol_fee_destination: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(address = position.input_mint, mint::token_program = token_program)]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = position.output_mint, mint::token_program = output_token_program)]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token program of the input mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the output mint
+    pub output_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<ExecutePosition>, _passed_price: u64) -> Result<()> {
+pub fn handler(
+    ctx: Context<ExecutePosition>,
+    _passed_price: u64,
+    route: Option<Vec<RouteLeg>>,
+    split: Option<Vec<SplitLeg>>,
+    candidates: Option<Vec<VenueCandidate>>,
+    jupiter_route: Option<Vec<u8>>,
+) -> Result<()> {
     let position = &ctx.accounts.position;
     let clock = Clock::get()?;
 
+    ctx.accounts.config.check_not_paused(PAUSE_EXECUTIONS)?;
+    PausedPair::check_not_paused(&ctx.accounts.paused_pair, &position.input_mint, &position.output_mint)?;
+    // Past its expiry a position can only be refunded through `expire_position`
+    require!(!position.is_expired(clock.unix_timestamp), PyroSwapError::PositionExpired);
+
     // 1. Get & Validate Oracle Price
     // We ignore the passed-in price and use the on-chain oracle for security
     let oracle_data = crate::oracle::get_pyth_price(
@@ -72,6 +155,23 @@ pub fn handler(ctx: Context<ExecutePosition>, _passed_price: u64) -> Result<()>
     // 3. Perform Swap via Preferred DEX
     let preferred_dex = position.preferred_dex;
     let dex_accounts = ctx.remaining_accounts;
+
+    // A graduated pump.fun token only trades on the pool its liquidity migrated to
+    let mut venue = preferred_dex;
+    let mut rerouted = false;
+    if preferred_dex == DexType::PumpFun {
+        let bonding_curve = ctx.accounts.bonding_curve.as_ref().ok_or(pumpfun::ErrorCode::InvalidCurveAccount)?;
+        let token_mint = curve_token_mint(&position.input_mint, &position.output_mint);
+        require_keys_eq!(bonding_curve.key(), bonding_curve_address(&token_mint), pumpfun::ErrorCode::InvalidCurveAccount);
+
+        let curve = BondingCurveInfo::try_from_account(&bonding_curve.to_account_info())?;
+        let route = ctx.accounts.migration_route.as_deref();
+        if let Some(route) = pumpfun::migration_target(&curve, route, &token_mint, dex_accounts)? {
+            venue = route.dex_type;
+            rerouted = true;
+            msg!("Bonding curve complete, rerouting to {:?} pool {}", venue, route.pool);
+        }
+    }
     
     // Seed for Vault signer
     let position_key = position.key();
@@ -83,63 +183,204 @@ pub fn handler(ctx: Context<ExecutePosition>, _passed_price: u64) -> Result<()>
     ];
     let signer_seeds = &[&seeds[..]];
 
-    // Execute swap using Vault as signer
-    // We use a simplified amount_out expectation for now (1% slippage from oracle price)
-    // In production, this min_amount_out should be calculated more precisely or passed as argument
-    let min_amount_out = 0; // Simplified for MVP execution
-
+    // Execute swap using Vault as signer, held to the oracle price less the
+    // widest slippage we tolerate
     let amount_in = ctx.accounts.vault.amount;
+    let min_amount_out = crate::oracle::min_amount_out(
+        amount_in,
+        &oracle_data,
+        ctx.accounts.input_mint.decimals,
+        ctx.accounts.output_mint.decimals,
+        MAX_SLIPPAGE_BPS,
+    )?;
+    let output_before = ctx.accounts.output_vault.amount;
 
-    let amount_out = match preferred_dex {
-        DexType::Raydium => crate::dex::raydium::RaydiumAdapter::swap(amount_in, min_amount_out, dex_accounts, Some(signer_seeds))?,
-        DexType::Orca => crate::dex::orca::OrcaAdapter::swap(amount_in, min_amount_out, dex_accounts, Some(signer_seeds))?,
-        DexType::Meteora => crate::dex::meteora::MeteoraAdapter::swap(amount_in, min_amount_out, dex_accounts, Some(signer_seeds))?,
-        DexType::PumpFun => crate::dex::pumpfun::PumpFunAdapter::swap(amount_in, min_amount_out, dex_accounts, Some(signer_seeds))?,
-        _ => return Err(ErrorCode::InvalidDexType.into()),
+    // A keeper-supplied route, split, candidate set or Jupiter route replaces the single hop on `venue`
+    let plans = [route.is_some(), split.is_some(), candidates.is_some(), jupiter_route.is_some()];
+    require!(plans.iter().filter(|plan| **plan).count() <= 1, PyroSwapError::InvalidRoute);
+    // The migrated pool is the only venue a graduated token may use
+    require!(!rerouted || !plans.contains(&true), PyroSwapError::InvalidMigrationRoute);
+    // Every venue the plan can touch must be live
+    let venues: Vec<DexType> = if let Some(route) = &route {
+        route.iter().map(|leg| leg.dex_type).collect()
+    } else if let Some(legs) = &split {
+        legs.iter().map(|leg| leg.dex_type).collect()
+    } else if let Some(candidates) = &candidates {
+        candidates.iter().map(|candidate| candidate.dex_type).collect()
+    } else if jupiter_route.is_some() {
+        vec![DexType::Jupiter]
+    } else {
+        vec![venue]
+    };
+    ctx.accounts.config.check_venues(venues)?;
+    let output_vault_key = ctx.accounts.output_vault.key();
+    let mut selection = None;
+    let amount_out = if let Some(route) = &route {
+        crate::dex::execute_route(route, amount_in, min_amount_out, &position.input_mint, &output_vault_key, dex_accounts, Some(signer_seeds))?
+    } else if let Some(legs) = &split {
+        crate::dex::execute_split(legs, amount_in, min_amount_out, &position.input_mint, &output_vault_key, dex_accounts, Some(signer_seeds))?
+    } else if let Some(candidates) = &candidates {
+        let chosen = crate::dex::execute_best_venue(
+            candidates,
+            amount_in,
+            min_amount_out,
+            &position.input_mint,
+            &output_vault_key,
+            dex_accounts,
+            Some(signer_seeds),
+        )?;
+        venue = chosen.best.dex_type;
+        let quoted = chosen.best.amount_out;
+        selection = Some(chosen);
+        quoted
+    } else if let Some(route_data) = &jupiter_route {
+        // Jupiter moves the vault's tokens with the vault PDA as transfer authority
+        let adapter = crate::dex::JupiterAdapter { instruction_data: route_data };
+        require_keys_eq!(
+            adapter.destination_account(&position.input_mint, dex_accounts)?.key(),
+            output_vault_key,
+            PyroSwapError::InvalidRoute
+        );
+        venue = DexType::Jupiter;
+        adapter.swap(amount_in, min_amount_out, &position.input_mint, dex_accounts, Some(signer_seeds))?
+    } else if venue == DexType::PumpFun && position.input_mint != pumpfun::NATIVE_MINT {
+        // Curve sells pay lamports to a signer, so the wSOL output vault takes them
+        let output_vault_seeds = &[
+            b"output_vault",
+            position_key.as_ref(),
+            &[ctx.bumps.output_vault],
+        ];
+        pumpfun::sell_into_output_vault(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.output_token_program.to_account_info(),
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.output_vault.to_account_info(),
+            amount_in,
+            min_amount_out,
+            &position.input_mint,
+            dex_accounts,
+            &[&seeds[..], &output_vault_seeds[..]],
+        )?
+    } else {
+        crate::dex::adapter_for(venue).swap(amount_in, min_amount_out, &position.input_mint, dex_accounts, Some(signer_seeds))?
     };
     
-    msg!("Executed position via {:?}. Out: {}", preferred_dex, amount_out);
+    msg!("Executed position via {:?}. Out: {}", venue, amount_out);
     
     // 3. Calculate Fees (Token) - Protocol Only
-    // Reload vault balance because the swap just happened
-    let vault_balance = ctx.accounts.vault.reload()?; // Ensure we see new balance
-    let vault_balance_amount = ctx.accounts.vault.amount; 
+    // Measure what actually landed in the output vault rather than trusting the adapter
+    ctx.accounts.output_vault.reload()?;
+    let received = ctx.accounts.output_vault.amount
+        .checked_sub(output_before)
+        .ok_or(PyroSwapError::MathOverflow)?;
+    require!(received >= min_amount_out, PyroSwapError::SlippageExceeded);
     
     let config = &ctx.accounts.config;
-    let total_protocol_fee = (vault_balance_amount as u128 * config.protocol_fee_bps as u128 / 10000) as u64;
+    let total_protocol_fee = (received as u128 * config.protocol_fee_bps as u128 / 10000) as u64;
+    // Gross the fee up so the destination nets the full bps under a transfer-fee mint
+    let protocol_fee_sent = if total_protocol_fee > 0 {
+        amount_with_transfer_fee(&ctx.accounts.output_mint.to_account_info(), total_protocol_fee, clock.epoch)?
+    } else {
+        0
+    };
+    require!(protocol_fee_sent <= received, PyroSwapError::MathOverflow);
     
-    msg!("Executed. New Balance: {}. Total Protocol Fee: {}", vault_balance_amount, total_protocol_fee);
+    msg!("Executed. Received: {}. Total Protocol Fee: {}", received, total_protocol_fee);
+
+    if let Some(selection) = selection {
+        emit!(BestVenueSelected {
+            position: position_key,
+            venue,
+            quoted_amount_out: selection.best.amount_out,
+            amount_out: received,
+            losing_quotes: selection
+                .losing
+                .iter()
+                .map(|quote| VenueQuote {
+                    dex_type: quote.dex_type,
+                    amount_out: quote.amount_out,
+                    price_impact_bps: quote.price_impact_bps,
+                })
+                .collect(),
+        });
+    }
 
     // 4. Distribute Fees
     // Send Protocol Fee
-    if total_protocol_fee > 0 {
-         let cpi_accounts = token::Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.protocol_fee_destination.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
-        token::transfer(cpi_ctx, total_protocol_fee)?;
+    if protocol_fee_sent > 0 {
+        transfer_tokens(
+            &ctx.accounts.output_token_program,
+            &ctx.accounts.output_mint,
+            ctx.accounts.output_vault.to_account_info(),
+            ctx.accounts.protocol_fee_destination.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            protocol_fee_sent,
+            signer_seeds,
+        )?;
     }
 
-    // 5. Send Remaining to User
-    // NOTE: The `owner` account passed is an AccountInfo. We need to send tokens to the owner's ATA.
-    // The keeper should pass the Owner's Token Account as a remaining account or we need to derive it.
-    // For MVP safety, we will leave remaining tokens in the Vault for the user to `withdraw_manual`.
-    // OR we transfer out if we can find the account. Use `withdraw_manual` for safety now.
-    
-    // Close position logic remains (transfer SOL execution fee)
-    msg!("Tokens remaining in vault for user withdrawal: {}", vault_balance_amount - total_protocol_fee);
-
-    // 6. SOL Fee Distribution (Executor)
+    // 5. SOL Fee Distribution (Executor)
+    // The executor earns the whole escrow; anything extra in the PDA goes back to the owner
     let executor_fee = position.execution_fee_escrow;
-    
-    // Pay executor
-    **ctx.accounts.position.to_account_info().try_borrow_mut_lamports()? -= executor_fee;
-    **ctx.accounts.executor.to_account_info().try_borrow_mut_lamports()? += executor_fee;
+    let escrow_seeds = &[
+        b"escrow",
+        position_key.as_ref(),
+        &[ctx.bumps.escrow],
+    ];
+    drain_escrow(
+        &ctx.accounts.system_program,
+        &ctx.accounts.escrow,
+        executor_fee,
+        Some((&ctx.accounts.executor.to_account_info(), executor_fee)),
+        &ctx.accounts.owner,
+        &[&escrow_seeds[..]],
+    )?;
+
+    // 6. Record execution
+    let position = &mut ctx.accounts.position;
+    position.execution_fee_escrow = 0;
+    position.execution_price = Some(current_price);
+    position.rerouted = rerouted;
+    position.transition(PositionStatus::Executed, clock.unix_timestamp)?;
+
+    // 7. Deliver to the owner and close everything if the owner ATAs were supplied.
+    // A wSOL vault needs no ATA: closing it unwraps to the owner as lamports.
+    let deliver_input = ctx.accounts.owner_input_token.is_some() || ctx.accounts.vault.is_native();
+    let deliver_output = ctx.accounts.owner_output_token.is_some() || ctx.accounts.output_vault.is_native();
+    if !(deliver_input && deliver_output) {
+        msg!("Tokens remaining in output vault for user withdrawal: {}", received - protocol_fee_sent);
+        return Ok(());
+    }
+
+    // Anything the DEX left unfilled is returned with the output
+    ctx.accounts.vault.reload()?;
+    ctx.accounts.output_vault.reload()?;
+    let vault_authority = ctx.accounts.vault.to_account_info();
+    let owner = ctx.accounts.owner.to_account_info();
+    let delivered = sweep_and_close_vault(
+        &ctx.accounts.output_token_program,
+        &ctx.accounts.output_mint,
+        &ctx.accounts.output_vault,
+        ctx.accounts.owner_output_token.as_ref(),
+        &vault_authority,
+        &owner,
+        signer_seeds,
+    )?;
+    let unfilled = sweep_and_close_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.input_mint,
+        &ctx.accounts.vault,
+        ctx.accounts.owner_input_token.as_ref(),
+        &vault_authority,
+        &owner,
+        signer_seeds,
+    )?;
+
+    ctx.accounts.position.transition(PositionStatus::Settled, clock.unix_timestamp)?;
+    ctx.accounts.position.close(owner)?;
+
+    msg!("Delivered {} output tokens to owner. Unfilled input returned: {}", delivered, unfilled);
 
-    // Remaining lamports go to owner (via close = owner)
-    
     Ok(())
 }
 
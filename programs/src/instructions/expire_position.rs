@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::errors::*;
+use crate::utils::{drain_escrow, sweep_and_close_vault};
+
+#[derive(Accounts)]
+pub struct ExpirePosition<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"position", owner.key().as_ref(), position.input_mint.as_ref()],
+        bump = position.bump,
+        has_one = owner,
+        constraint = position.status == PositionStatus::Active @ PyroSwapError::PositionAlreadyExecuted
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", position.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"output_vault", position.key().as_ref()],
+        bump
+    )]
+    pub output_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", position.key().as_ref()],
+        bump
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    /// CHECK: Position owner receiving the refund, validated by `has_one`
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    /// Omit for a wrapped SOL mint to receive it unwrapped as lamports
+    #[account(
+        mut,
+        associated_token::mint = position.input_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Only needed if someone sent output tokens into the output vault
+    #[account(
+        mut,
+        associated_token::mint = position.output_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = output_token_program
+    )]
+    pub owner_output_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Anyone may expire a stale position and collect the expiry fee
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(address = position.input_mint, mint::token_program = token_program)]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = position.output_mint, mint::token_program = output_token_program)]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token program of the input mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the output mint
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ExpirePosition>) -> Result<()> {
+    let clock = Clock::get()?;
+    let position = &mut ctx.accounts.position;
+
+    require!(position.is_expired(clock.unix_timestamp), PyroSwapError::PositionNotExpired);
+
+    // Mark position as expired
+    position.transition(PositionStatus::Expired, clock.unix_timestamp)?;
+
+    // Pay the keeper its share of the execution fee escrow, the rest goes back to the owner
+    let keeper_fee = (position.execution_fee_escrow as u128
+        * ctx.accounts.config.expiry_keeper_fee_bps as u128
+        / 10000) as u64;
+    let position_key = position.key();
+    let escrow_seeds = &[
+        b"escrow",
+        position_key.as_ref(),
+        &[ctx.bumps.escrow],
+    ];
+    drain_escrow(
+        &ctx.accounts.system_program,
+        &ctx.accounts.escrow,
+        position.execution_fee_escrow,
+        Some((&ctx.accounts.keeper.to_account_info(), keeper_fee)),
+        &ctx.accounts.owner.to_account_info(),
+        &[&escrow_seeds[..]],
+    )?;
+
+    // Return tokens to the owner's ATA and close both vaults
+    let seeds = &[
+        b"vault",
+        position_key.as_ref(),
+        &[ctx.bumps.vault],
+    ];
+    let signer = &[&seeds[..]];
+
+    let vault_authority = ctx.accounts.vault.to_account_info();
+    let owner = ctx.accounts.owner.to_account_info();
+    sweep_and_close_vault(
+        &ctx.accounts.output_token_program,
+        &ctx.accounts.output_mint,
+        &ctx.accounts.output_vault,
+        ctx.accounts.owner_output_token.as_ref(),
+        &vault_authority,
+        &owner,
+        signer,
+    )?;
+    let vault_balance = sweep_and_close_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.input_mint,
+        &ctx.accounts.vault,
+        ctx.accounts.owner_token_account.as_ref(),
+        &vault_authority,
+        &owner,
+        signer,
+    )?;
+
+    msg!("Position expired. Keeper fee: {} lamports", keeper_fee);
+    msg!("Returned {} tokens to owner", vault_balance);
+
+    Ok(())
+}
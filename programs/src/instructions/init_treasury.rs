@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Create the program treasury for a mint. Anyone may pay for it; only
+/// `withdraw_treasury` can move its balance, and only to the fee destination.
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitTreasury>) -> Result<()> {
+    msg!("Treasury for {} created at {}", ctx.accounts.mint.key(), ctx.accounts.treasury.key());
+
+    Ok(())
+}
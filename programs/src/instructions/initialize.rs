@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::constants::*;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -13,14 +14,26 @@ pub struct Initialize<'info> {
     pub config: Account<'info, GlobalConfig>,
     #[account(mut)]
     pub admin: Signer<'info>,
+    /// CHECK: Wallet whose token accounts receive protocol fees
+    pub fee_destination: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<Initialize>, protocol_fee_bps: u16, referral_fee_share_bps: u16) -> Result<()> {
+pub fn handler(
+    ctx: Context<Initialize>,
+    protocol_fee_bps: u16,
+    referral_fee_share_bps: u16,
+    expiry_keeper_fee_bps: u16,
+) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.admin = ctx.accounts.admin.key();
+    config.fee_destination = ctx.accounts.fee_destination.key();
     config.protocol_fee_bps = protocol_fee_bps;
     config.referral_fee_share_bps = referral_fee_share_bps;
+    config.expiry_keeper_fee_bps = expiry_keeper_fee_bps;
+    config.validate_fees()?;
     config.bump = ctx.bumps.config;
+    config.version = GLOBAL_CONFIG_VERSION;
+    config.pending_admin = None;
     Ok(())
 }
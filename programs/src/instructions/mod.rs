@@ -2,12 +2,48 @@ pub mod initialize;
 pub mod open_position;
 pub mod execute_position;
 pub mod cancel_position;
+pub mod expire_position;
 pub mod withdraw_manual;
+pub mod top_up_execution_fee;
 pub mod swap;
+pub mod swap_route;
+pub mod swap_split;
+pub mod swap_generic;
+pub mod swap_jupiter;
+pub mod register_migration_route;
+pub mod update_migration_route;
+pub mod close_migration_route;
+pub mod whitelist_program;
+pub mod remove_whitelisted_program;
+pub mod update_config;
+pub mod propose_admin;
+pub mod accept_admin;
+pub mod set_fee_destination;
+pub mod set_guardian;
+pub mod set_pause;
+pub mod set_venue_pause;
+pub mod pause_pair;
+pub mod unpause_pair;
+pub mod init_treasury;
+pub mod withdraw_treasury;
 
 pub use initialize::*;
 pub use open_position::*;
 pub use execute_position::*;
 pub use cancel_position::*;
+pub use expire_position::*;
 pub use withdraw_manual::*;
+pub use top_up_execution_fee::*;
 pub use swap::*;
+pub use register_migration_route::*;
+pub use update_migration_route::*;
+pub use close_migration_route::*;
+pub use whitelist_program::*;
+pub use remove_whitelisted_program::*;
+pub use update_config::*;
+pub use accept_admin::*;
+pub use set_pause::*;
+pub use pause_pair::*;
+pub use unpause_pair::*;
+pub use init_treasury::*;
+pub use withdraw_treasury::*;
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
 use crate::errors::*;
 use crate::constants::*;
+use crate::utils::{check_mint_extensions, is_native_mint, transfer_tokens, wrap_native};
 
 #[derive(Accounts)]
 pub struct OpenPosition<'info> {
@@ -20,28 +21,65 @@ pub struct OpenPosition<'info> {
         payer = user,
         token::mint = input_mint,
         token::authority = vault,
+        token::token_program = token_program,
         seeds = [b"vault", position.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub input_mint: Account<'info, Mint>,
-    pub output_mint: Account<'info, Mint>,
+    /// Receives the swap output on execution, owned by the vault PDA
+    #[account(
+        init,
+        payer = user,
+        token::mint = output_mint,
+        token::authority = vault,
+        token::token_program = output_token_program,
+        seeds = [b"output_vault", position.key().as_ref()],
+        bump
+    )]
+    pub output_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// System-owned PDA holding the execution fee, kept apart from the position's rent
+    #[account(
+        mut,
+        seeds = [b"escrow", position.key().as_ref()],
+        bump
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = output_token_program)]
+    pub output_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: Pyth price feed account
     pub oracle_price_feed: UncheckedAccount<'info>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// CHECK: The pair's `PausedPair` PDA, which must not exist
+    pub paused_pair: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// Omit to deposit native SOL when the input mint is wrapped SOL
     #[account(mut)]
-    pub user_input_token: Account<'info, TokenAccount>,
+    pub user_input_token: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token program of the input mint, SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the output mint
+    pub output_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<OpenPosition>,
     amount_in: u64,
@@ -50,15 +88,29 @@ pub fn handler(
     entry_price: u64,
     execution_fee: u64,
     preferred_dex: u8,
+    expires_at: Option<i64>,
 ) -> Result<()> {
     // Validate parameters
     require!(sl_bps <= MAX_STOP_LOSS_BPS, PyroSwapError::StopLossTooHigh);
     require!(tp_bps >= MIN_TAKE_PROFIT_BPS && tp_bps <= MAX_TAKE_PROFIT_BPS, PyroSwapError::TakeProfitTooLow);
     require!(execution_fee >= MIN_EXECUTION_FEE, PyroSwapError::ExecutionFeeTooLow);
-    require!(preferred_dex <= 3, PyroSwapError::InvalidDexType);
+    let preferred_dex = DexType::try_from(preferred_dex)?;
+    // Executions carry no instruction data for a generic venue
+    require!(preferred_dex != DexType::Generic, PyroSwapError::InvalidDexType);
+
+    let config = &ctx.accounts.config;
+    config.check_not_paused(PAUSE_OPENS)?;
+    config.check_venues([preferred_dex])?;
+    PausedPair::check_not_paused(&ctx.accounts.paused_pair, &ctx.accounts.input_mint.key(), &ctx.accounts.output_mint.key())?;
+
+    check_mint_extensions(&ctx.accounts.input_mint.to_account_info())?;
+    check_mint_extensions(&ctx.accounts.output_mint.to_account_info())?;
 
     // Validate entry price with Oracle
     let clock = Clock::get()?;
+    if let Some(expires_at) = expires_at {
+        require!(expires_at > clock.unix_timestamp, PyroSwapError::InvalidExpiry);
+    }
     let oracle_price = crate::oracle::get_pyth_price(
         &ctx.accounts.oracle_price_feed,
         clock.slot,
@@ -75,59 +127,79 @@ pub fn handler(
     // Initialize position
     position.owner = ctx.accounts.user.key();
     position.vault = ctx.accounts.vault.key();
+    position.output_vault = ctx.accounts.output_vault.key();
     position.input_mint = ctx.accounts.input_mint.key();
     position.output_mint = ctx.accounts.output_mint.key();
-    position.amount_in = amount_in;
     position.sl_bps = sl_bps;
     position.tp_bps = tp_bps;
     position.entry_price = entry_price;
     position.execution_fee_escrow = execution_fee;
     position.oracle_price_feed = ctx.accounts.oracle_price_feed.key();
-    position.preferred_dex = match preferred_dex {
-        0 => DexType::Raydium,
-        1 => DexType::Orca,
-        2 => DexType::Meteora,
-        _ => return Err(PyroSwapError::InvalidDexType.into()),
-    };
+    position.preferred_dex = preferred_dex;
     position.status = PositionStatus::Active;
     position.created_at = clock.unix_timestamp;
     position.executed_at = None;
     position.referrer = None;
     position.bump = ctx.bumps.position;
+    position.expires_at = expires_at;
+    position.rerouted = false;
 
-    // Transfer execution fee from user to position account
+    // Transfer execution fee from user to the escrow PDA
     let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
         &ctx.accounts.user.key(),
-        &position.key(),
+        &ctx.accounts.escrow.key(),
         execution_fee,
     );
     anchor_lang::solana_program::program::invoke(
         &transfer_ix,
         &[
             ctx.accounts.user.to_account_info(),
-            position.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
         ],
     )?;
 
-    // Transfer input tokens from user to vault
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.user_input_token.to_account_info(),
-        to: ctx.accounts.vault.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, amount_in)?;
+    // Transfer input tokens from user to vault, wrapping native SOL straight into it
+    match &ctx.accounts.user_input_token {
+        Some(user_input_token) => transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.input_mint,
+            user_input_token.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            amount_in,
+            &[],
+        )?,
+        None => {
+            require!(is_native_mint(&ctx.accounts.input_mint.key()), PyroSwapError::InvalidTokenAccount);
+            wrap_native(
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                amount_in,
+            )?;
+        }
+    }
+
+    // The vault was just created, so its balance is the deposit net of any transfer fee
+    ctx.accounts.vault.reload()?;
+    let deposited = ctx.accounts.vault.amount;
+    require!(deposited > 0, PyroSwapError::InvalidAmount);
+    let position = &mut ctx.accounts.position;
+    position.amount_in = deposited;
 
     msg!("Position created:");
     msg!("  Owner: {}", position.owner);
-    msg!("  Input: {} {}", amount_in, ctx.accounts.input_mint.key());
+    msg!("  Input: {} {} ({} deposited)", amount_in, ctx.accounts.input_mint.key(), deposited);
     msg!("  Stop Loss: {}%", sl_bps as f64 / 100.0);
     msg!("  Take Profit: {}%", tp_bps as f64 / 100.0);
     msg!("  Entry Price: {}", entry_price);
     msg!("  Execution Fee: {} lamports", execution_fee);
     msg!("  Preferred DEX: {:?}", position.preferred_dex);
+    if let Some(expires_at) = expires_at {
+        msg!("  Expires At: {}", expires_at);
+    }
 
     Ok(())
 }
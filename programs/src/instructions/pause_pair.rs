@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+use crate::events::PairPauseUpdated;
+
+#[derive(Accounts)]
+#[instruction(mint_a: Pubkey, mint_b: Pubkey)]
+pub struct PausePair<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PausedPair::SIZE,
+        seeds = [b"paused_pair", mint_a.as_ref(), mint_b.as_ref()],
+        bump
+    )]
+    pub paused_pair: Account<'info, PausedPair>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.can_pause(&authority.key()) @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Pause trading between two mints in both directions. `mint_a` must sort
+/// before `mint_b` so each pair has a single address.
+pub fn handler(ctx: Context<PausePair>, mint_a: Pubkey, mint_b: Pubkey) -> Result<()> {
+    require!(mint_a < mint_b, PyroSwapError::InvalidPausedPairAccount);
+
+    let pair = &mut ctx.accounts.paused_pair;
+    pair.mint_a = mint_a;
+    pair.mint_b = mint_b;
+    pair.bump = ctx.bumps.paused_pair;
+
+    msg!("Paused pair {} / {}", mint_a, mint_b);
+    emit!(PairPauseUpdated {
+        authority: ctx.accounts.authority.key(),
+        mint_a,
+        mint_b,
+        paused: true,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+use crate::events::AdminProposed;
+use crate::instructions::update_config::UpdateConfig;
+
+/// Same accounts as `update_config`. The current admin stays in control until
+/// `new_admin` signs `accept_admin`; proposing again replaces the nominee.
+pub fn handler(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_admin = Some(new_admin);
+
+    msg!("Admin handover proposed: {} -> {}", config.admin, new_admin);
+    emit!(AdminProposed {
+        admin: config.admin,
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
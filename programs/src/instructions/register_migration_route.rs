@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+
+#[derive(Accounts)]
+pub struct RegisterMigrationRoute<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = MigrationRoute::SIZE,
+        seeds = [b"migration_route", mint.key().as_ref()],
+        bump
+    )]
+    pub migration_route: Account<'info, MigrationRoute>,
+
+    /// The pump.fun token whose liquidity migrates
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<RegisterMigrationRoute>,
+    dex_type: u8, // 0 = Raydium, 1 = Orca, 2 = Meteora, 7 = RaydiumCpmm
+    pool: Pubkey,
+) -> Result<()> {
+    let dex_type = MigrationRoute::venue(dex_type)?;
+
+    let route = &mut ctx.accounts.migration_route;
+    route.mint = ctx.accounts.mint.key();
+    route.dex_type = dex_type;
+    route.pool = pool;
+    route.bump = ctx.bumps.migration_route;
+
+    msg!("Registered migration route for {}: {:?} pool {}", route.mint, dex_type, pool);
+
+    Ok(())
+}
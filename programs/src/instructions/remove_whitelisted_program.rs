@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+
+#[derive(Accounts)]
+pub struct RemoveWhitelistedProgram<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"whitelisted_program", whitelisted_program.program.as_ref()],
+        bump = whitelisted_program.bump
+    )]
+    pub whitelisted_program: Account<'info, WhitelistedProgram>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RemoveWhitelistedProgram>) -> Result<()> {
+    msg!("Removed {} from the generic swap whitelist", ctx.accounts.whitelisted_program.program);
+
+    Ok(())
+}
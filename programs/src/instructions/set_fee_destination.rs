@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use crate::events::FeeDestinationUpdated;
+use crate::instructions::update_config::UpdateConfig;
+
+/// Same accounts as `update_config`. `fee_destination` is the wallet whose
+/// token accounts receive protocol fees.
+pub fn handler(ctx: Context<UpdateConfig>, fee_destination: Pubkey) -> Result<()> {
+    require_keys_neq!(fee_destination, Pubkey::default());
+
+    let config = &mut ctx.accounts.config;
+    let previous_fee_destination = config.fee_destination;
+    config.fee_destination = fee_destination;
+
+    msg!("Fee destination updated: {} -> {}", previous_fee_destination, fee_destination);
+    emit!(FeeDestinationUpdated {
+        previous_fee_destination,
+        fee_destination,
+    });
+
+    Ok(())
+}
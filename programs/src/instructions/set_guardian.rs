@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use crate::events::GuardianUpdated;
+use crate::instructions::update_config::UpdateConfig;
+
+/// Same accounts as `update_config`. The guardian can only pause and unpause;
+/// `Pubkey::default()` removes it.
+pub fn handler(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_guardian = config.guardian;
+    config.guardian = guardian;
+
+    msg!("Guardian updated: {} -> {}", previous_guardian, guardian);
+    emit!(GuardianUpdated {
+        previous_guardian,
+        guardian,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::PyroSwapError;
+use crate::events::PauseUpdated;
+
+/// Pause flag write access for the admin or the guardian
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.can_pause(&authority.key()) @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+impl SetPause<'_> {
+    pub fn emit_update(&self) {
+        emit!(PauseUpdated {
+            authority: self.authority.key(),
+            paused: self.config.paused,
+            paused_venues: self.config.paused_venues,
+        });
+    }
+}
+
+/// Replace the global `PAUSE_*` bitmask. Cancels, expiries and withdrawals
+/// ignore it so users can always exit.
+pub fn handler(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+    require!(paused & !PAUSE_ALL == 0, PyroSwapError::InvalidPauseFlags);
+
+    ctx.accounts.config.paused = paused;
+
+    msg!(
+        "Pause flags set by {}: opens {}, swaps {}, executions {}",
+        ctx.accounts.authority.key(),
+        paused & PAUSE_OPENS != 0,
+        paused & PAUSE_SWAPS != 0,
+        paused & PAUSE_EXECUTIONS != 0
+    );
+    ctx.accounts.emit_update();
+
+    Ok(())
+}
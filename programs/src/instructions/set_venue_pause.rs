@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::instructions::set_pause::SetPause;
+
+/// Same accounts as `set_pause`. A paused venue is refused by swaps and
+/// executions, including as one leg of a route, split or candidate set.
+pub fn handler(ctx: Context<SetPause>, dex_type: u8, paused: bool) -> Result<()> {
+    let dex_type = DexType::try_from(dex_type)?;
+
+    let config = &mut ctx.accounts.config;
+    let bit = 1u16 << dex_type as u8;
+    if paused {
+        config.paused_venues |= bit;
+    } else {
+        config.paused_venues &= !bit;
+    }
+
+    msg!("{:?} {} by {}", dex_type, if paused { "paused" } else { "unpaused" }, ctx.accounts.authority.key());
+    ctx.accounts.emit_update();
+
+    Ok(())
+}
@@ -1,26 +1,50 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, CloseAccount, InitializeAccount3, Mint, TokenAccount, TokenInterface};
 use crate::state::*;
-use crate::dex::DexAdapter;
+use crate::constants::{PAUSE_SWAPS, UNWRAP_SEED};
+use crate::errors::PyroSwapError;
+use crate::utils::{amount_with_transfer_fee, is_native_mint, transfer_tokens, wrap_native};
+use crate::dex::adapter_for;
+
+/// Size of a plain SPL Token account
+const TOKEN_ACCOUNT_LEN: usize = 165;
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
-    /// CHECK: This is the user or aggregator calling the swap
+    /// CHECK: This is the user or aggregator calling the swap. Pays for
+    /// wrapping SOL in and receives unwrapped SOL out.
+    #[account(mut)]
     pub authority: Signer<'info>,
 
-    #[account(mut)]
-    pub user_source_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = source_mint,
+        token::token_program = token_program
+    )]
+    pub user_source_token: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
-    pub user_destination_token: Account<'info, TokenAccount>,
+    pub user_destination_token: InterfaceAccount<'info, TokenAccount>,
 
-    /// The Protocol's fee collection wallet (ATA)
-    #[account(mut)]
-    pub protocol_fee_destination: Account<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub source_mint: InterfaceAccount<'info, Mint>,
+
+    /// The Protocol's fee collection wallet (ATA) for the source mint, or the
+    /// source mint's treasury
+    #[account(
+        mut,
+        constraint = config.is_fee_account(
+            &protocol_fee_destination.key(),
+            &source_mint.key(),
+            &token_program.key()
+        ) @ PyroSwapError::InvalidFeeDestination
+    )]
+    pub protocol_fee_destination: InterfaceAccount<'info, TokenAccount>,
 
     /// Optional: The Referrer's fee collection wallet (ATA)
     #[account(mut)]
-    pub referrer_fee_account: Option<Account<'info, TokenAccount>>,
+    pub referrer_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         seeds = [b"config"],
@@ -28,74 +52,238 @@ pub struct Swap<'info> {
     )]
     pub config: Account<'info, GlobalConfig>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: The source and destination mints' `PausedPair` PDA, which must not exist
+    pub paused_pair: UncheckedAccount<'info>,
+
+    /// Token program of the source mint, SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Wraps native SOL into a wSOL source account
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The authority's `unwrap` PDA. Supplied with `native_mint` to
+    /// take a wSOL output as lamports: this instruction opens it, moves the
+    /// output in and closes it, so it never outlives the swap.
+    #[account(mut)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+
+    /// The wSOL mint, for the temporary unwrap account
+    #[account(mint::token_program = token_program)]
+    pub native_mint: Option<InterfaceAccount<'info, Mint>>,
 }
 
-pub fn handler(
-    ctx: Context<Swap>, 
-    amount: u64, 
-    min_amount_out: u64,
-    preferred_dex: u8 // 0=Raydium, 1=Orca, 2=Meteora, 3=PumpFun
-) -> Result<()> {
-    // 1. Validation
-    require!(amount > 0, ErrorCode::InvalidAmount);
+impl<'info> Swap<'info> {
+    /// Refuse the swap while swaps, its pair or any of `venues` are paused
+    pub fn check_not_paused(&self, venues: impl IntoIterator<Item = DexType>) -> Result<()> {
+        self.config.check_not_paused(PAUSE_SWAPS)?;
+        PausedPair::check_not_paused(&self.paused_pair, &self.source_mint.key(), &self.user_destination_token.mint)?;
+        self.config.check_venues(venues)
+    }
+
+    /// Take the protocol and referral fees out of `amount` from the user's
+    /// source account, returning what is left to swap. A wSOL source short
+    /// of `amount` is topped up from the authority's lamports first.
+    pub fn collect_fees(&self, amount: u64) -> Result<u64> {
+        // 1. Validation
+        require!(amount > 0, ErrorCode::InvalidAmount);
 
-    let config = &ctx.accounts.config;
+        if is_native_mint(&self.source_mint.key()) && self.user_source_token.amount < amount {
+            wrap_native(
+                &self.system_program,
+                &self.token_program,
+                self.authority.to_account_info(),
+                self.user_source_token.to_account_info(),
+                amount - self.user_source_token.amount,
+            )?;
+        }
+
+        let config = &self.config;
     
-    // 2. Calculate Fees
-    // Protocol Fee (Base)
-    let protocol_fee = (amount as u128 * config.protocol_fee_bps as u128 / 10000) as u64;
+        // 2. Calculate Fees
+        // Protocol Fee (Base)
+        let protocol_fee = (amount as u128 * config.protocol_fee_bps as u128 / 10000) as u64;
     
-    // Referral Fee (Variable)
-    let referral_fee = if ctx.accounts.referrer_fee_account.is_some() {
-        (amount as u128 * config.referral_fee_share_bps as u128 / 10000) as u64
-    } else {
-        0
-    };
-
-    let total_fee = protocol_fee + referral_fee;
-    let swap_amount = amount.checked_sub(total_fee).ok_or(ErrorCode::NotEnoughFunds)?;
-
-    msg!("Swapping: {}. Protocol Fee: {}. Referral Fee: {}", swap_amount, protocol_fee, referral_fee);
-
-    // 3. Transfer Protocol Fee
-    if protocol_fee > 0 {
-        let cpi_accounts = token::Transfer {
-            from: ctx.accounts.user_source_token.to_account_info(),
-            to: ctx.accounts.protocol_fee_destination.to_account_info(),
-            authority: ctx.accounts.authority.to_account_info(),
+        // Referral Fee (Variable)
+        let referral_fee = if self.referrer_fee_account.is_some() {
+            (amount as u128 * config.referral_fee_share_bps as u128 / 10000) as u64
+        } else {
+            0
+        };
+
+        // Gross the fees up so each recipient nets its full bps under a transfer-fee mint
+        let epoch = Clock::get()?.epoch;
+        let mint = self.source_mint.to_account_info();
+        let protocol_fee_sent = if protocol_fee > 0 {
+            amount_with_transfer_fee(&mint, protocol_fee, epoch)?
+        } else {
+            0
+        };
+        let referral_fee_sent = if referral_fee > 0 {
+            amount_with_transfer_fee(&mint, referral_fee, epoch)?
+        } else {
+            0
+        };
+
+        let total_fee = protocol_fee_sent
+            .checked_add(referral_fee_sent)
+            .ok_or(ErrorCode::NotEnoughFunds)?;
+        let swap_amount = amount.checked_sub(total_fee).ok_or(ErrorCode::NotEnoughFunds)?;
+
+        msg!("Swapping: {}. Protocol Fee: {}. Referral Fee: {}", swap_amount, protocol_fee, referral_fee);
+
+        // 3. Transfer Protocol Fee
+        if protocol_fee_sent > 0 {
+            transfer_tokens(
+                &self.token_program,
+                &self.source_mint,
+                self.user_source_token.to_account_info(),
+                self.protocol_fee_destination.to_account_info(),
+                self.authority.to_account_info(),
+                protocol_fee_sent,
+                &[],
+            )?;
+        }
+
+        // 4. Transfer Referral Fee (if applicable)
+        if referral_fee_sent > 0 {
+            if let Some(referrer_account) = &self.referrer_fee_account {
+                transfer_tokens(
+                    &self.token_program,
+                    &self.source_mint,
+                    self.user_source_token.to_account_info(),
+                    referrer_account.to_account_info(),
+                    self.authority.to_account_info(),
+                    referral_fee_sent,
+                    &[],
+                )?;
+            }
+        }
+
+        Ok(swap_amount)
+    }
+
+    /// Pay a wSOL output out to the authority as lamports. Only `received`,
+    /// what the swap delivered, is unwrapped, through a temporary account this
+    /// instruction opens and closes; the authority's own wSOL accounts stay open.
+    pub fn unwrap_native(&self, received: u64) -> Result<()> {
+        let (Some(unwrap_account), Some(native_mint)) = (&self.unwrap_account, &self.native_mint) else {
+            return Ok(());
         };
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-        token::transfer(cpi_ctx, protocol_fee)?;
+        let destination = &self.user_destination_token;
+        require!(is_native_mint(&native_mint.key()), PyroSwapError::InvalidTokenAccount);
+        require_keys_eq!(destination.mint, native_mint.key(), PyroSwapError::InvalidTokenAccount);
+        require_keys_eq!(destination.owner, self.authority.key(), PyroSwapError::InvalidTokenAccount);
+
+        if received == 0 {
+            return Ok(());
+        }
+
+        let authority_key = self.authority.key();
+        let (address, bump) = Pubkey::find_program_address(&[UNWRAP_SEED, authority_key.as_ref()], &crate::ID);
+        require_keys_eq!(unwrap_account.key(), address, PyroSwapError::InvalidTokenAccount);
+        let seeds: &[&[u8]] = &[UNWRAP_SEED, authority_key.as_ref(), &[bump]];
+        self.open_unwrap_account(unwrap_account, seeds)?;
+
+        let unwrap_info = unwrap_account.to_account_info();
+        let token_program = self.token_program.to_account_info();
+        token_interface::initialize_account3(CpiContext::new(
+            token_program.clone(),
+            InitializeAccount3 {
+                account: unwrap_info.clone(),
+                mint: native_mint.to_account_info(),
+                authority: self.authority.to_account_info(),
+            },
+        ))?;
+        transfer_tokens(
+            &self.token_program,
+            native_mint,
+            destination.to_account_info(),
+            unwrap_info.clone(),
+            self.authority.to_account_info(),
+            received,
+            &[],
+        )?;
+        // Rent and output both go back to the authority
+        token_interface::close_account(CpiContext::new(
+            token_program,
+            CloseAccount {
+                account: unwrap_info,
+                destination: self.authority.to_account_info(),
+                authority: self.authority.to_account_info(),
+            },
+        ))
     }
 
-    // 4. Transfer Referral Fee (if applicable)
-    if referral_fee > 0 {
-        if let Some(referrer_account) = &ctx.accounts.referrer_fee_account {
-             let cpi_accounts = token::Transfer {
-                from: ctx.accounts.user_source_token.to_account_info(),
-                to: referrer_account.to_account_info(),
-                authority: ctx.accounts.authority.to_account_info(),
-            };
-            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-            token::transfer(cpi_ctx, referral_fee)?;
+    /// Fund, allocate and assign the unwrap PDA to the token program. Lamports
+    /// someone already sent to the address count towards its rent.
+    fn open_unwrap_account(&self, unwrap_account: &UncheckedAccount<'info>, seeds: &[&[u8]]) -> Result<()> {
+        let system_program = self.system_program.to_account_info();
+        let account = unwrap_account.to_account_info();
+        let rent = Rent::get()?.minimum_balance(TOKEN_ACCOUNT_LEN);
+        let shortfall = rent.saturating_sub(account.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer { from: self.authority.to_account_info(), to: account.clone() },
+                ),
+                shortfall,
+            )?;
         }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate { account_to_allocate: account.clone() },
+                &[seeds],
+            ),
+            TOKEN_ACCOUNT_LEN as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program,
+                system_program::Assign { account_to_assign: account },
+                &[seeds],
+            ),
+            &self.token_program.key(),
+        )
     }
+}
+
+pub fn handler(
+    ctx: Context<Swap>, 
+    amount: u64, 
+    min_amount_out: u64,
+    preferred_dex: u8 // 0=Raydium, 1=Orca, 2=Meteora, 3=PumpFun, 4=Phoenix, 5=Lifinity, 6=RaydiumClmm, 7=RaydiumCpmm
+) -> Result<()> {
+    let dex_type = DexType::try_from(preferred_dex)?;
+    ctx.accounts.check_not_paused([dex_type])?;
+
+    let swap_amount = ctx.accounts.collect_fees(amount)?;
 
     // 5. Execute Swap on preferred DEX
     // The DEX accounts are passed as remaining_accounts
     let dex_accounts = ctx.remaining_accounts;
+    let source_mint = ctx.accounts.user_source_token.mint;
+    let adapter = adapter_for(dex_type);
+    require_keys_eq!(
+        adapter.destination_account(&source_mint, dex_accounts)?.key(),
+        ctx.accounts.user_destination_token.key(),
+        PyroSwapError::InvalidTokenAccount
+    );
+
+    let destination_before = ctx.accounts.user_destination_token.amount;
+    adapter.swap(swap_amount, min_amount_out, &source_mint, dex_accounts, None)?;
+
+    // Report what reached the user's account rather than what the adapter claims
+    ctx.accounts.user_destination_token.reload()?;
+    let amount_out = ctx.accounts.user_destination_token.amount
+        .checked_sub(destination_before)
+        .ok_or(PyroSwapError::MathOverflow)?;
+    require!(amount_out >= min_amount_out, PyroSwapError::SlippageExceeded);
+
+    msg!("Swap executed via {:?}. Amount out: {}", dex_type, amount_out);
     
-    let amount_out = match preferred_dex {
-        0 => crate::dex::raydium::RaydiumAdapter::swap(swap_amount, min_amount_out, dex_accounts, None)?,
-        1 => crate::dex::orca::OrcaAdapter::swap(swap_amount, min_amount_out, dex_accounts, None)?,
-        2 => crate::dex::meteora::MeteoraAdapter::swap(swap_amount, min_amount_out, dex_accounts, None)?,
-        3 => crate::dex::pumpfun::PumpFunAdapter::swap(swap_amount, min_amount_out, dex_accounts, None)?,
-        _ => return Err(ErrorCode::InvalidDexType.into()),
-    };
-
-    msg!("Swap executed via DEX {}. Amount out: {}", preferred_dex, amount_out);
-    
+    ctx.accounts.unwrap_native(amount_out)?;
+
     Ok(())
 }
 
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::DexType;
+use crate::dex::{DexAdapter, GenericAdapter};
+use crate::errors::PyroSwapError;
+use crate::instructions::swap::Swap;
+
+/// Same accounts as `swap`. The remaining accounts are a generic swap slice
+/// (see `GenericAdapter::swap`) whose source and destination are the user's
+/// token accounts; `instruction_data` goes to the whitelisted program as is.
+pub fn handler(
+    ctx: Context<Swap>,
+    amount: u64,
+    min_amount_out: u64,
+    instruction_data: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts.check_not_paused([DexType::Generic])?;
+
+    let swap_amount = ctx.accounts.collect_fees(amount)?;
+
+    let adapter = GenericAdapter { instruction_data: &instruction_data };
+    let source_mint = ctx.accounts.user_source_token.mint;
+    let dex_accounts = ctx.remaining_accounts;
+    require_keys_eq!(
+        GenericAdapter::source_account(dex_accounts)?.key(),
+        ctx.accounts.user_source_token.key(),
+        PyroSwapError::InvalidTokenAccount
+    );
+    require_keys_eq!(
+        adapter.destination_account(&source_mint, dex_accounts)?.key(),
+        ctx.accounts.user_destination_token.key(),
+        PyroSwapError::InvalidTokenAccount
+    );
+
+    let amount_out = adapter.swap(swap_amount, min_amount_out, &source_mint, dex_accounts, None)?;
+
+    msg!("Swap executed via {}. Amount out: {}", dex_accounts[1].key(), amount_out);
+
+    ctx.accounts.unwrap_native(amount_out)?;
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::DexType;
+use crate::dex::{DexAdapter, JupiterAdapter};
+use crate::errors::PyroSwapError;
+use crate::instructions::swap::Swap;
+
+/// Same accounts as `swap`. The remaining accounts are the Jupiter program
+/// followed by the route's accounts, with `authority` as the transfer
+/// authority and the user's token accounts as source and destination.
+/// `route_data` is the serialized `route` or `shared_accounts_route`
+/// instruction, built for the amount left after PyroSwap's fees.
+pub fn handler(
+    ctx: Context<Swap>,
+    amount: u64,
+    min_amount_out: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts.check_not_paused([DexType::Jupiter])?;
+
+    let swap_amount = ctx.accounts.collect_fees(amount)?;
+
+    let adapter = JupiterAdapter { instruction_data: &route_data };
+    let source_mint = ctx.accounts.user_source_token.mint;
+    let dex_accounts = ctx.remaining_accounts;
+    require_keys_eq!(
+        adapter.destination_account(&source_mint, dex_accounts)?.key(),
+        ctx.accounts.user_destination_token.key(),
+        PyroSwapError::InvalidTokenAccount
+    );
+
+    let amount_out = adapter.swap(swap_amount, min_amount_out, &source_mint, dex_accounts, None)?;
+
+    msg!("Swap executed via Jupiter. Amount out: {}", amount_out);
+
+    ctx.accounts.unwrap_native(amount_out)?;
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::RouteLeg;
+use crate::instructions::swap::Swap;
+
+/// Same accounts as `swap`. The remaining accounts are the legs' swap slices
+/// back to back, in route order, with the last leg paying into
+/// `user_destination_token`.
+pub fn handler(
+    ctx: Context<Swap>,
+    amount: u64,
+    min_amount_out: u64,
+    route: Vec<RouteLeg>,
+) -> Result<()> {
+    ctx.accounts.check_not_paused(route.iter().map(|leg| leg.dex_type))?;
+
+    let swap_amount = ctx.accounts.collect_fees(amount)?;
+
+    let amount_out = crate::dex::execute_route(
+        &route,
+        swap_amount,
+        min_amount_out,
+        &ctx.accounts.user_source_token.mint,
+        &ctx.accounts.user_destination_token.key(),
+        ctx.remaining_accounts,
+        None,
+    )?;
+
+    msg!("Swap executed via {} hop route. Amount out: {}", route.len(), amount_out);
+
+    ctx.accounts.unwrap_native(amount_out)?;
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::SplitLeg;
+use crate::instructions::swap::Swap;
+
+/// Same accounts as `swap`. The remaining accounts are each venue's swap
+/// slice back to back, in plan order, all paying into `user_destination_token`.
+pub fn handler(
+    ctx: Context<Swap>,
+    amount: u64,
+    min_amount_out: u64,
+    legs: Vec<SplitLeg>,
+) -> Result<()> {
+    ctx.accounts.check_not_paused(legs.iter().map(|leg| leg.dex_type))?;
+
+    let swap_amount = ctx.accounts.collect_fees(amount)?;
+
+    let amount_out = crate::dex::execute_split(
+        &legs,
+        swap_amount,
+        min_amount_out,
+        &ctx.accounts.user_source_token.mint,
+        &ctx.accounts.user_destination_token.key(),
+        ctx.remaining_accounts,
+        None,
+    )?;
+
+    msg!("Swap executed across {} venues. Amount out: {}", legs.len(), amount_out);
+
+    ctx.accounts.unwrap_native(amount_out)?;
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct TopUpExecutionFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"position", owner.key().as_ref(), position.input_mint.as_ref()],
+        bump = position.bump,
+        has_one = owner,
+        constraint = position.status == PositionStatus::Active @ PyroSwapError::PositionAlreadyExecuted
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", position.key().as_ref()],
+        bump
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Raise the keeper bounty on an active position, e.g. during network congestion
+pub fn handler(ctx: Context<TopUpExecutionFee>, amount: u64) -> Result<()> {
+    require!(amount > 0, PyroSwapError::InvalidAmount);
+
+    let escrow_before = ctx.accounts.escrow.lamports();
+    require!(
+        escrow_before >= ctx.accounts.position.execution_fee_escrow,
+        PyroSwapError::EscrowMismatch
+    );
+
+    let cpi_accounts = system_program::Transfer {
+        from: ctx.accounts.owner.to_account_info(),
+        to: ctx.accounts.escrow.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+    system_program::transfer(cpi_ctx, amount)?;
+
+    let position = &mut ctx.accounts.position;
+    position.execution_fee_escrow = position.execution_fee_escrow
+        .checked_add(amount)
+        .ok_or(PyroSwapError::MathOverflow)?;
+
+    // Reconcile: the PDA grew by exactly the top-up
+    require!(
+        ctx.accounts.escrow.lamports() == escrow_before.checked_add(amount).ok_or(PyroSwapError::MathOverflow)?,
+        PyroSwapError::EscrowMismatch
+    );
+
+    msg!("Execution fee topped up by {} lamports. New escrow: {}", amount, position.execution_fee_escrow);
+
+    Ok(())
+}
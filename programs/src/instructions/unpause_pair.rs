@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+use crate::events::PairPauseUpdated;
+
+#[derive(Accounts)]
+pub struct UnpausePair<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"paused_pair", paused_pair.mint_a.as_ref(), paused_pair.mint_b.as_ref()],
+        bump = paused_pair.bump
+    )]
+    pub paused_pair: Account<'info, PausedPair>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.can_pause(&authority.key()) @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UnpausePair>) -> Result<()> {
+    let pair = &ctx.accounts.paused_pair;
+
+    msg!("Unpaused pair {} / {}", pair.mint_a, pair.mint_b);
+    emit!(PairPauseUpdated {
+        authority: ctx.accounts.authority.key(),
+        mint_a: pair.mint_a,
+        mint_b: pair.mint_b,
+        paused: false,
+    });
+
+    Ok(())
+}
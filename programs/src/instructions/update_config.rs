@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+use crate::events::ConfigUpdated;
+
+/// Admin-signed write access to the config, shared by the admin instructions
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Change any of the fees; `None` keeps the current value
+pub fn handler(
+    ctx: Context<UpdateConfig>,
+    protocol_fee_bps: Option<u16>,
+    referral_fee_share_bps: Option<u16>,
+    expiry_keeper_fee_bps: Option<u16>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    if let Some(protocol_fee_bps) = protocol_fee_bps {
+        config.protocol_fee_bps = protocol_fee_bps;
+    }
+    if let Some(referral_fee_share_bps) = referral_fee_share_bps {
+        config.referral_fee_share_bps = referral_fee_share_bps;
+    }
+    if let Some(expiry_keeper_fee_bps) = expiry_keeper_fee_bps {
+        config.expiry_keeper_fee_bps = expiry_keeper_fee_bps;
+    }
+    config.validate_fees()?;
+
+    msg!(
+        "Config updated. Protocol fee: {} bps. Referral share: {} bps. Expiry keeper fee: {} bps",
+        config.protocol_fee_bps,
+        config.referral_fee_share_bps,
+        config.expiry_keeper_fee_bps
+    );
+    emit!(ConfigUpdated {
+        admin: config.admin,
+        protocol_fee_bps: config.protocol_fee_bps,
+        referral_fee_share_bps: config.referral_fee_share_bps,
+        expiry_keeper_fee_bps: config.expiry_keeper_fee_bps,
+    });
+
+    Ok(())
+}
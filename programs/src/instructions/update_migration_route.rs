@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+
+#[derive(Accounts)]
+pub struct UpdateMigrationRoute<'info> {
+    #[account(
+        mut,
+        seeds = [b"migration_route", migration_route.mint.as_ref()],
+        bump = migration_route.bump
+    )]
+    pub migration_route: Account<'info, MigrationRoute>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateMigrationRoute>,
+    dex_type: u8, // 0 = Raydium, 1 = Orca, 2 = Meteora, 7 = RaydiumCpmm
+    pool: Pubkey,
+) -> Result<()> {
+    let dex_type = MigrationRoute::venue(dex_type)?;
+
+    let route = &mut ctx.accounts.migration_route;
+    route.dex_type = dex_type;
+    route.pool = pool;
+
+    msg!("Updated migration route for {}: {:?} pool {}", route.mint, dex_type, pool);
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::PyroSwapError;
+
+#[derive(Accounts)]
+#[instruction(program: Pubkey)]
+pub struct WhitelistProgram<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = WhitelistedProgram::SIZE,
+        seeds = [b"whitelisted_program", program.as_ref()],
+        bump
+    )]
+    pub whitelisted_program: Account<'info, WhitelistedProgram>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<WhitelistProgram>, program: Pubkey) -> Result<()> {
+    // Calling back into ourselves would let the target act with our signatures
+    require_keys_neq!(program, crate::ID, PyroSwapError::InvalidDexProgram);
+
+    let entry = &mut ctx.accounts.whitelisted_program;
+    entry.program = program;
+    entry.bump = ctx.bumps.whitelisted_program;
+
+    msg!("Whitelisted {} for generic swaps", program);
+
+    Ok(())
+}
@@ -1,14 +1,20 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
+use crate::errors::*;
+use crate::utils::sweep_and_close_vault;
 
+/// Settlement path for positions that executed but whose output was not delivered.
+/// Sweeps both vaults to the owner's ATAs, unwrapping wrapped SOL, and closes everything.
 #[derive(Accounts)]
 pub struct WithdrawManual<'info> {
     #[account(
         mut,
         close = owner,
         seeds = [b"position", owner.key().as_ref(), position.input_mint.as_ref()],
-        bump = position.bump
+        bump = position.bump,
+        has_one = owner,
+        constraint = position.status == PositionStatus::Executed @ PyroSwapError::PositionNotExecuted
     )]
     pub position: Account<'info, Position>,
 
@@ -17,33 +23,83 @@ pub struct WithdrawManual<'info> {
         seeds = [b"vault", position.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"output_vault", position.key().as_ref()],
+        bump
+    )]
+    pub output_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// Omit for a wrapped SOL mint to receive it unwrapped as lamports
+    #[account(
+        mut,
+        associated_token::mint = position.input_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_input_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Omit for a wrapped SOL mint to receive it unwrapped as lamports
+    #[account(
+        mut,
+        associated_token::mint = position.output_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = output_token_program
+    )]
+    pub owner_output_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = position.input_mint, mint::token_program = token_program)]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = position.output_mint, mint::token_program = output_token_program)]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token program of the input mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the output mint
+    pub output_token_program: Interface<'info, TokenInterface>,
 }
 
 pub fn handler(ctx: Context<WithdrawManual>) -> Result<()> {
+    let clock = Clock::get()?;
+    ctx.accounts.position.transition(PositionStatus::Settled, clock.unix_timestamp)?;
+
+    let position_key = ctx.accounts.position.key();
     let seeds = &[
         b"vault",
-        ctx.accounts.position.to_account_info().key.as_ref(),
+        position_key.as_ref(),
         &[ctx.bumps.vault],
     ];
     let signer = &[&seeds[..]];
 
-    let cpi_accounts = token::Transfer {
-        from: ctx.accounts.vault.to_account_info(),
-        to: ctx.accounts.owner.to_account_info(), // For MVP, simple transfer
-        authority: ctx.accounts.vault.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
+    // Both vaults are owned by the vault PDA; the output vault goes first while it can still sign
+    let vault_authority = ctx.accounts.vault.to_account_info();
+    let owner = ctx.accounts.owner.to_account_info();
+    let output_delivered = sweep_and_close_vault(
+        &ctx.accounts.output_token_program,
+        &ctx.accounts.output_mint,
+        &ctx.accounts.output_vault,
+        ctx.accounts.owner_output_token.as_ref(),
+        &vault_authority,
+        &owner,
         signer,
-    );
-    token::transfer(cpi_ctx, ctx.accounts.vault.amount)?;
+    )?;
+    let input_returned = sweep_and_close_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.input_mint,
+        &ctx.accounts.vault,
+        ctx.accounts.owner_input_token.as_ref(),
+        &vault_authority,
+        &owner,
+        signer,
+    )?;
+
+    msg!("Position settled. Input returned: {}. Output delivered: {}", input_returned, output_delivered);
 
     Ok(())
 }
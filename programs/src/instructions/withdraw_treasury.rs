@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::errors::PyroSwapError;
+use crate::utils::transfer_tokens;
+
+/// Forward a mint's treasury balance to the fee destination's ATA
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = fee_destination,
+        associated_token::token_program = token_program
+    )]
+    pub fee_destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The configured fee wallet, only used to derive its ATA
+    #[account(address = config.fee_destination @ PyroSwapError::InvalidFeeDestination)]
+    pub fee_destination: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PyroSwapError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler(ctx: Context<WithdrawTreasury>) -> Result<()> {
+    let amount = ctx.accounts.treasury.amount;
+    require!(amount > 0, PyroSwapError::InvalidAmount);
+
+    let mint_key = ctx.accounts.mint.key();
+    let seeds = &[
+        b"treasury",
+        mint_key.as_ref(),
+        &[ctx.bumps.treasury],
+    ];
+    transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        ctx.accounts.treasury.to_account_info(),
+        ctx.accounts.fee_destination_token.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+        amount,
+        &[&seeds[..]],
+    )?;
+
+    msg!("Forwarded {} {} from the treasury to {}", amount, mint_key, ctx.accounts.fee_destination.key());
+
+    Ok(())
+}
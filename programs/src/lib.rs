@@ -3,9 +3,11 @@ use anchor_lang::prelude::*;
 pub mod state;
 pub mod instructions;
 pub mod errors;
+pub mod events;
 pub mod constants;
 pub mod dex;
 pub mod oracle;
+pub mod utils;
 
 use instructions::*;
 use errors::*;
@@ -20,10 +22,17 @@ pub mod pyro_swap {
         ctx: Context<Initialize>,
         protocol_fee_bps: u16,
         referral_fee_share_bps: u16,
+        expiry_keeper_fee_bps: u16,
     ) -> Result<()> {
-        instructions::initialize::handler(ctx, protocol_fee_bps, referral_fee_share_bps)
+        instructions::initialize::handler(
+            ctx,
+            protocol_fee_bps,
+            referral_fee_share_bps,
+            expiry_keeper_fee_bps,
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn open_position(
         ctx: Context<OpenPosition>,
         amount_in: u64,
@@ -31,7 +40,8 @@ pub mod pyro_swap {
         tp_bps: u16,
         entry_price: u64,
         execution_fee: u64,
-        preferred_dex: u8, // 0 = Raydium, 1 = Orca, 2 = Meteora
+        preferred_dex: u8, // 0 = Raydium, 1 = Orca, 2 = Meteora, 3 = PumpFun, 4 = Phoenix, 5 = Lifinity, 6 = RaydiumClmm, 7 = RaydiumCpmm, 9 = Jupiter
+        expires_at: Option<i64>,
     ) -> Result<()> {
         instructions::open_position::handler(
             ctx,
@@ -41,20 +51,33 @@ pub mod pyro_swap {
             entry_price,
             execution_fee,
             preferred_dex,
+            expires_at,
         )
     }
 
     pub fn execute_position(
         ctx: Context<ExecutePosition>,
         current_price: u64,
+        route: Option<Vec<state::RouteLeg>>,
+        split: Option<Vec<state::SplitLeg>>,
+        candidates: Option<Vec<state::VenueCandidate>>,
+        jupiter_route: Option<Vec<u8>>,
     ) -> Result<()> {
-        instructions::execute_position::handler(ctx, current_price)
+        instructions::execute_position::handler(ctx, current_price, route, split, candidates, jupiter_route)
     }
 
     pub fn cancel_position(ctx: Context<CancelPosition>) -> Result<()> {
         instructions::cancel_position::handler(ctx)
     }
 
+    pub fn expire_position(ctx: Context<ExpirePosition>) -> Result<()> {
+        instructions::expire_position::handler(ctx)
+    }
+
+    pub fn top_up_execution_fee(ctx: Context<TopUpExecutionFee>, amount: u64) -> Result<()> {
+        instructions::top_up_execution_fee::handler(ctx, amount)
+    }
+
     pub fn withdraw_manual(ctx: Context<WithdrawManual>) -> Result<()> {
         instructions::withdraw_manual::handler(ctx)
     }
@@ -67,4 +90,122 @@ pub mod pyro_swap {
     ) -> Result<()> {
         instructions::swap::handler(ctx, amount, min_amount_out, preferred_dex)
     }
+
+    pub fn swap_route(
+        ctx: Context<Swap>,
+        amount: u64,
+        min_amount_out: u64,
+        route: Vec<state::RouteLeg>,
+    ) -> Result<()> {
+        instructions::swap_route::handler(ctx, amount, min_amount_out, route)
+    }
+
+    pub fn swap_split(
+        ctx: Context<Swap>,
+        amount: u64,
+        min_amount_out: u64,
+        legs: Vec<state::SplitLeg>,
+    ) -> Result<()> {
+        instructions::swap_split::handler(ctx, amount, min_amount_out, legs)
+    }
+
+    pub fn swap_generic(
+        ctx: Context<Swap>,
+        amount: u64,
+        min_amount_out: u64,
+        instruction_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::swap_generic::handler(ctx, amount, min_amount_out, instruction_data)
+    }
+
+    pub fn swap_jupiter(
+        ctx: Context<Swap>,
+        amount: u64,
+        min_amount_out: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::swap_jupiter::handler(ctx, amount, min_amount_out, route_data)
+    }
+
+    pub fn register_migration_route(
+        ctx: Context<RegisterMigrationRoute>,
+        dex_type: u8,
+        pool: Pubkey,
+    ) -> Result<()> {
+        instructions::register_migration_route::handler(ctx, dex_type, pool)
+    }
+
+    pub fn update_migration_route(
+        ctx: Context<UpdateMigrationRoute>,
+        dex_type: u8,
+        pool: Pubkey,
+    ) -> Result<()> {
+        instructions::update_migration_route::handler(ctx, dex_type, pool)
+    }
+
+    pub fn close_migration_route(ctx: Context<CloseMigrationRoute>) -> Result<()> {
+        instructions::close_migration_route::handler(ctx)
+    }
+
+    pub fn whitelist_program(ctx: Context<WhitelistProgram>, program: Pubkey) -> Result<()> {
+        instructions::whitelist_program::handler(ctx, program)
+    }
+
+    pub fn remove_whitelisted_program(ctx: Context<RemoveWhitelistedProgram>) -> Result<()> {
+        instructions::remove_whitelisted_program::handler(ctx)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        protocol_fee_bps: Option<u16>,
+        referral_fee_share_bps: Option<u16>,
+        expiry_keeper_fee_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::update_config::handler(
+            ctx,
+            protocol_fee_bps,
+            referral_fee_share_bps,
+            expiry_keeper_fee_bps,
+        )
+    }
+
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin::handler(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin::handler(ctx)
+    }
+
+    pub fn set_fee_destination(ctx: Context<UpdateConfig>, fee_destination: Pubkey) -> Result<()> {
+        instructions::set_fee_destination::handler(ctx, fee_destination)
+    }
+
+    pub fn set_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+        instructions::set_guardian::handler(ctx, guardian)
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+        instructions::set_pause::handler(ctx, paused)
+    }
+
+    pub fn set_venue_pause(ctx: Context<SetPause>, dex_type: u8, paused: bool) -> Result<()> {
+        instructions::set_venue_pause::handler(ctx, dex_type, paused)
+    }
+
+    pub fn pause_pair(ctx: Context<PausePair>, mint_a: Pubkey, mint_b: Pubkey) -> Result<()> {
+        instructions::pause_pair::handler(ctx, mint_a, mint_b)
+    }
+
+    pub fn unpause_pair(ctx: Context<UnpausePair>) -> Result<()> {
+        instructions::unpause_pair::handler(ctx)
+    }
+
+    pub fn init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+        instructions::init_treasury::handler(ctx)
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>) -> Result<()> {
+        instructions::withdraw_treasury::handler(ctx)
+    }
 }
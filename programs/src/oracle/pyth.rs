@@ -63,3 +63,58 @@ pub fn validate_execution_price(
 
     Ok(())
 }
+
+/// Least output a swap of `amount_in` may return at the oracle price, less
+/// `max_slippage_bps` of tolerance. The price quotes the input mint in the
+/// output mint, so the mints' decimals fold into its exponent.
+pub fn min_amount_out(
+    amount_in: u64,
+    oracle_price: &OraclePrice,
+    input_decimals: u8,
+    output_decimals: u8,
+    max_slippage_bps: u16,
+) -> Result<u64> {
+    require!(max_slippage_bps <= 10000, PyroSwapError::InvalidBasisPoints);
+    let scale = oracle_price.exponent + output_decimals as i32 - input_decimals as i32;
+    let factor = 10u128
+        .checked_pow(scale.unsigned_abs())
+        .ok_or(PyroSwapError::MathOverflow)?;
+
+    let value = (amount_in as u128 * oracle_price.price as u128)
+        .checked_mul(10000 - max_slippage_bps as u128)
+        .ok_or(PyroSwapError::MathOverflow)?
+        / 10000;
+    let min_out = if scale >= 0 {
+        value.checked_mul(factor).ok_or(PyroSwapError::MathOverflow)?
+    } else {
+        value / factor
+    };
+    Ok(u64::try_from(min_out).map_err(|_| PyroSwapError::MathOverflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(price: u64, exponent: i32) -> OraclePrice {
+        OraclePrice { price, exponent, confidence: 0, publish_time: 0 }
+    }
+
+    #[test]
+    fn min_out_converts_decimals() {
+        // 10 SOL at $100 into USDC: 1,000 USDC, 900 after 10% slippage
+        let sol_usd = price(100_000_000, -6);
+        assert_eq!(min_amount_out(10_000_000_000, &sol_usd, 9, 6, 1000).unwrap(), 900_000_000);
+        assert_eq!(min_amount_out(10_000_000_000, &sol_usd, 9, 6, 0).unwrap(), 1_000_000_000);
+
+        // A 6 decimal token at 5.0 into a 9 decimal one scales up
+        assert_eq!(min_amount_out(1_000_000, &price(50, -1), 6, 9, 0).unwrap(), 5_000_000_000);
+    }
+
+    #[test]
+    fn min_out_rejects_overflow_and_bad_bps() {
+        let huge = price(u64::MAX, 0);
+        assert!(min_amount_out(u64::MAX, &huge, 0, 0, 0).is_err());
+        assert!(min_amount_out(1, &price(1, 0), 0, 0, 10001).is_err());
+    }
+}
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use crate::constants::*;
+use crate::errors::PyroSwapError;
 
 #[account]
 pub struct GlobalConfig {
@@ -6,13 +9,64 @@ pub struct GlobalConfig {
     pub fee_destination: Pubkey,
     pub protocol_fee_bps: u16,
     pub referral_fee_share_bps: u16,
+    pub expiry_keeper_fee_bps: u16, // Share of execution fee escrow paid to whoever expires a position
     pub total_positions_created: u64,
     pub total_volume: u64,
     pub bump: u8,
+    /// Layout version, bumped when a field carved out of `_reserved` needs
+    /// existing accounts rewritten rather than read as zero
+    pub version: u8,
+    /// Set by `propose_admin`; becomes `admin` once it signs `accept_admin`
+    pub pending_admin: Option<Pubkey>,
+    /// May set pause flags alongside the admin, and nothing else
+    pub guardian: Pubkey,
+    /// `PAUSE_*` bits
+    pub paused: u8,
+    /// One bit per `DexType`, by code
+    pub paused_venues: u16,
+    pub _reserved: [u8; 93],
 }
 
 impl GlobalConfig {
-    pub const SIZE: usize = 8 + 32 + 32 + 2 + 2 + 8 + 8 + 1;
+    pub const SIZE: usize = 8 + 32 + 32 + 2 + 2 + 2 + 8 + 8 + 1 + 1 + 33 + 32 + 1 + 2 + 93;
+
+    /// The admin and the guardian can both pause and unpause
+    pub fn can_pause(&self, authority: &Pubkey) -> bool {
+        *authority == self.admin || (*authority == self.guardian && self.guardian != Pubkey::default())
+    }
+
+    /// Fails if any of the `PAUSE_*` bits in `flags` is set
+    pub fn check_not_paused(&self, flags: u8) -> Result<()> {
+        require!(self.paused & flags == 0, PyroSwapError::ProtocolPaused);
+        Ok(())
+    }
+
+    pub fn is_venue_paused(&self, dex_type: DexType) -> bool {
+        self.paused_venues & (1 << dex_type as u8) != 0
+    }
+
+    /// Protocol fees in `mint` may only go to `fee_destination`'s ATA or to
+    /// the program's treasury for the mint
+    pub fn is_fee_account(&self, fee_account: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> bool {
+        let ata = get_associated_token_address_with_program_id(&self.fee_destination, mint, token_program);
+        *fee_account == ata || *fee_account == treasury_address(mint).0
+    }
+
+    /// Fails if any of `venues` is paused
+    pub fn check_venues(&self, venues: impl IntoIterator<Item = DexType>) -> Result<()> {
+        for venue in venues {
+            require!(!self.is_venue_paused(venue), PyroSwapError::VenuePaused);
+        }
+        Ok(())
+    }
+
+    /// Fee bounds, checked whenever fees are written
+    pub fn validate_fees(&self) -> Result<()> {
+        require!(self.protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS, PyroSwapError::InvalidBasisPoints);
+        require!(self.referral_fee_share_bps <= MAX_REFERRAL_SHARE_BPS, PyroSwapError::InvalidBasisPoints);
+        require!(self.expiry_keeper_fee_bps <= MAX_EXPIRY_KEEPER_FEE_BPS, PyroSwapError::InvalidBasisPoints);
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
@@ -23,13 +77,157 @@ pub enum DexType {
     PumpFun,      // Pump.fun
     Phoenix,      // Phoenix DEX
     Lifinity,     // Lifinity
+    RaydiumClmm,  // Raydium concentrated liquidity
+    RaydiumCpmm,  // Raydium CP-Swap (Token-2022 capable constant product)
+    Generic,      // Any whitelisted program, checked by balance deltas
+    Jupiter,      // Jupiter v6 route built off-chain
+}
+
+/// Instruction arguments carry venues as `u8`; this is the only place the
+/// codes are decoded
+impl TryFrom<u8> for DexType {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        Ok(match code {
+            0 => DexType::Raydium,
+            1 => DexType::Orca,
+            2 => DexType::Meteora,
+            3 => DexType::PumpFun,
+            4 => DexType::Phoenix,
+            5 => DexType::Lifinity,
+            6 => DexType::RaydiumClmm,
+            7 => DexType::RaydiumCpmm,
+            8 => DexType::Generic,
+            9 => DexType::Jupiter,
+            _ => return Err(PyroSwapError::InvalidDexType.into()),
+        })
+    }
+}
+
+/// One hop of a multi-hop route: the venue and how many of the remaining
+/// accounts make up its swap slice
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RouteLeg {
+    pub dex_type: DexType,
+    pub accounts_len: u8,
+}
+
+/// A venue the keeper offers for best-venue selection and how many of the
+/// remaining accounts make up its swap slice
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VenueCandidate {
+    pub dex_type: DexType,
+    pub accounts_len: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+/// One venue of a split swap: its share of the input in basis points and how
+/// many of the remaining accounts make up its swap slice
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SplitLeg {
+    pub dex_type: DexType,
+    pub share_bps: u16,
+    pub accounts_len: u8,
+}
+
+/// Where a pump.fun token trades once its bonding curve completes and the
+/// liquidity migrates. Registered by the admin, one per mint.
+#[account]
+pub struct MigrationRoute {
+    pub mint: Pubkey,
+    pub dex_type: DexType,
+    pub pool: Pubkey,
+    pub bump: u8,
+}
+
+impl MigrationRoute {
+    pub const SIZE: usize = 8 + 32 + 1 + 32 + 1;
+
+    /// Parse a route's venue. A graduated curve no longer trades, so only
+    /// the pools pump.fun migrates to qualify.
+    pub fn venue(dex_type: u8) -> Result<DexType> {
+        let dex_type = DexType::try_from(dex_type)?;
+        require!(
+            matches!(dex_type, DexType::Raydium | DexType::Orca | DexType::Meteora | DexType::RaydiumCpmm),
+            PyroSwapError::InvalidDexType
+        );
+        Ok(dex_type)
+    }
+}
+
+/// A program the generic adapter may invoke. Added and removed by the
+/// admin, one per program.
+#[account]
+pub struct WhitelistedProgram {
+    pub program: Pubkey,
+    pub bump: u8,
+}
+
+impl WhitelistedProgram {
+    pub const SIZE: usize = 8 + 32 + 1;
+}
+
+/// The program-owned token account collecting protocol fees in `mint` when
+/// the fee destination has no ATA for it. Its own PDA is its authority.
+pub fn treasury_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, mint.as_ref()], &crate::ID)
+}
+
+/// A paused token pair, in either direction. Exists only while the pair is
+/// paused; `mint_a` sorts before `mint_b`.
+#[account]
+pub struct PausedPair {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub bump: u8,
+}
+
+impl PausedPair {
+    pub const SIZE: usize = 8 + 32 + 32 + 1;
+
+    /// The pair's PDA, the same for both directions
+    pub fn address(mint_x: &Pubkey, mint_y: &Pubkey) -> (Pubkey, u8) {
+        let (mint_a, mint_b) = if mint_x < mint_y { (mint_x, mint_y) } else { (mint_y, mint_x) };
+        Pubkey::find_program_address(&[PAUSED_PAIR_SEED, mint_a.as_ref(), mint_b.as_ref()], &crate::ID)
+    }
+
+    /// Instructions pass the pair's PDA whether or not it exists; an
+    /// initialized account means the pair is paused
+    pub fn check_not_paused(account: &AccountInfo, mint_x: &Pubkey, mint_y: &Pubkey) -> Result<()> {
+        require_keys_eq!(account.key(), Self::address(mint_x, mint_y).0, PyroSwapError::InvalidPausedPairAccount);
+        require!(account.data_is_empty(), PyroSwapError::PairPaused);
+        Ok(())
+    }
+}
+
+/// Position lifecycle:
+///
+/// ```text
+/// Active ──execute──▶ Executed ──withdraw_manual──▶ Settled
+///   ├────cancel─────▶ Cancelled
+///   └────expire─────▶ Expired
+/// ```
+///
+/// All status changes go through `Position::transition`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PositionStatus {
     Active,
     Executed,
     Cancelled,
+    Expired,
+    Settled,
+}
+
+impl PositionStatus {
+    pub fn can_transition_to(&self, next: PositionStatus) -> bool {
+        matches!(
+            (self, next),
+            (PositionStatus::Active, PositionStatus::Executed)
+                | (PositionStatus::Active, PositionStatus::Cancelled)
+                | (PositionStatus::Active, PositionStatus::Expired)
+                | (PositionStatus::Executed, PositionStatus::Settled)
+        )
+    }
 }
 
 #[account]
@@ -43,20 +241,44 @@ pub struct Position {
     pub sl_bps: u16,  // Stop loss in basis points (e.g., 500 = 5%)
     pub tp_bps: u16,  // Take profit in basis points (e.g., 1000 = 10%)
     pub entry_price: u64,
-    pub execution_fee_escrow: u64,
+    pub execution_fee_escrow: u64,  // Lamports held for the keeper in the escrow PDA
     pub oracle_price_feed: Pubkey,  // Pyth price feed account
     pub preferred_dex: DexType,
     pub status: PositionStatus,
     pub created_at: i64,
     pub executed_at: Option<i64>,
     pub bump: u8,
+    pub expires_at: Option<i64>,  // Keepers may refund the position after this time
+    pub execution_price: Option<u64>,  // Oracle price the position executed at
+    pub output_vault: Pubkey,  // Holds swap output until it is delivered to the owner
+    pub rerouted: bool,  // Executed on the post-migration venue because the bonding curve had completed
 }
 
 impl Position {
     // 8 (disc) + 32 (owner) + 32 (vault) + 32 (input) + 32 (output) 
     // + 33 (Option<Pubkey>) + 8 (amount) + 2 (sl) + 2 (tp) + 8 (price) 
     // + 8 (fee) + 32 (oracle) + 1 (dex) + 1 (status) + 8 (created) + 9 (executed) + 1 (bump)
-    pub const SIZE: usize = 8 + 32 + 32 + 32 + 32 + 33 + 8 + 2 + 2 + 8 + 8 + 32 + 1 + 1 + 8 + 9 + 1;
+    // + 9 (expires) + 9 (execution price) + 32 (output vault) + 1 (rerouted)
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 32 + 33 + 8 + 2 + 2 + 8 + 8 + 32 + 1 + 1 + 8 + 9 + 1 + 9 + 9 + 32 + 1;
+
+    /// Move the position to `next`, recording the execution time when it executes.
+    /// Fails without touching the position if the transition is not allowed.
+    pub fn transition(&mut self, next: PositionStatus, now: i64) -> Result<()> {
+        if !self.status.can_transition_to(next) {
+            return Err(match self.status {
+                PositionStatus::Executed => PyroSwapError::PositionAlreadyExecuted,
+                PositionStatus::Cancelled => PyroSwapError::PositionAlreadyCancelled,
+                _ => PyroSwapError::InvalidStatusTransition,
+            }
+            .into());
+        }
+
+        if next == PositionStatus::Executed {
+            self.executed_at = Some(now);
+        }
+        self.status = next;
+        Ok(())
+    }
 
     pub fn is_take_profit_triggered(&self, current_price: u64) -> bool {
         if self.status != PositionStatus::Active {
@@ -82,7 +304,217 @@ impl Position {
         current_price <= sl_price
     }
 
+    pub fn is_expired(&self, now: i64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now >= expires_at,
+            None => false,
+        }
+    }
+
     pub fn should_execute(&self, current_price: u64) -> bool {
         self.is_take_profit_triggered(current_price) || self.is_stop_loss_triggered(current_price)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_STATUSES: [PositionStatus; 5] = [
+        PositionStatus::Active,
+        PositionStatus::Executed,
+        PositionStatus::Cancelled,
+        PositionStatus::Expired,
+        PositionStatus::Settled,
+    ];
+
+    fn position(status: PositionStatus) -> Position {
+        Position {
+            owner: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            referrer: None,
+            amount_in: 1_000_000,
+            sl_bps: 500,
+            tp_bps: 1000,
+            entry_price: 100_000_000,
+            execution_fee_escrow: 5_000_000,
+            oracle_price_feed: Pubkey::new_unique(),
+            preferred_dex: DexType::Raydium,
+            status,
+            created_at: 1_700_000_000,
+            executed_at: None,
+            bump: 255,
+            expires_at: None,
+            execution_price: None,
+            output_vault: Pubkey::new_unique(),
+            rerouted: false,
+        }
+    }
+
+    #[test]
+    fn legal_transitions() {
+        let mut p = position(PositionStatus::Active);
+        p.transition(PositionStatus::Executed, 42).unwrap();
+        assert_eq!(p.status, PositionStatus::Executed);
+        assert_eq!(p.executed_at, Some(42));
+        p.transition(PositionStatus::Settled, 43).unwrap();
+        assert_eq!(p.status, PositionStatus::Settled);
+        assert_eq!(p.executed_at, Some(42));
+
+        for next in [PositionStatus::Cancelled, PositionStatus::Expired] {
+            let mut p = position(PositionStatus::Active);
+            p.transition(next, 42).unwrap();
+            assert_eq!(p.status, next);
+            assert_eq!(p.executed_at, None);
+        }
+    }
+
+    #[test]
+    fn every_illegal_transition_is_rejected() {
+        let mut rejected = 0;
+        for from in ALL_STATUSES {
+            for next in ALL_STATUSES {
+                if from.can_transition_to(next) {
+                    continue;
+                }
+                let mut p = position(from);
+                assert!(p.transition(next, 42).is_err(), "{:?} -> {:?} must fail", from, next);
+                assert_eq!(p.status, from);
+                assert_eq!(p.executed_at, None);
+                rejected += 1;
+            }
+        }
+        // 25 pairs minus the 4 legal edges
+        assert_eq!(rejected, 21);
+    }
+
+    #[test]
+    fn triggers_only_fire_while_active() {
+        let mut p = position(PositionStatus::Active);
+        assert!(p.should_execute(110_000_000));
+        assert!(p.should_execute(95_000_000));
+        assert!(!p.should_execute(100_000_000));
+
+        p.transition(PositionStatus::Executed, 42).unwrap();
+        assert!(!p.should_execute(110_000_000));
+        assert!(!p.should_execute(95_000_000));
+    }
+
+    fn config() -> GlobalConfig {
+        GlobalConfig {
+            admin: Pubkey::new_unique(),
+            fee_destination: Pubkey::new_unique(),
+            protocol_fee_bps: 50,
+            referral_fee_share_bps: 5000,
+            expiry_keeper_fee_bps: 1000,
+            total_positions_created: 0,
+            total_volume: 0,
+            bump: 255,
+            version: GLOBAL_CONFIG_VERSION,
+            pending_admin: None,
+            guardian: Pubkey::default(),
+            paused: 0,
+            paused_venues: 0,
+            _reserved: [0; 93],
+        }
+    }
+
+    #[test]
+    fn fee_bounds_are_inclusive() {
+        let mut c = config();
+        c.validate_fees().unwrap();
+
+        c.protocol_fee_bps = MAX_PROTOCOL_FEE_BPS;
+        c.referral_fee_share_bps = MAX_REFERRAL_SHARE_BPS;
+        c.expiry_keeper_fee_bps = MAX_EXPIRY_KEEPER_FEE_BPS;
+        c.validate_fees().unwrap();
+
+        for over in [
+            GlobalConfig { protocol_fee_bps: MAX_PROTOCOL_FEE_BPS + 1, ..config() },
+            GlobalConfig { referral_fee_share_bps: MAX_REFERRAL_SHARE_BPS + 1, ..config() },
+            GlobalConfig { expiry_keeper_fee_bps: MAX_EXPIRY_KEEPER_FEE_BPS + 1, ..config() },
+        ] {
+            assert!(over.validate_fees().is_err());
+        }
+    }
+
+    #[test]
+    fn config_size_matches_serialized_layout() {
+        // A pending admin is the largest form of the layout
+        let c = GlobalConfig { pending_admin: Some(Pubkey::new_unique()), ..config() };
+        assert_eq!(8 + c.try_to_vec().unwrap().len(), GlobalConfig::SIZE);
+    }
+
+    #[test]
+    fn pause_flags_and_venues() {
+        let mut c = config();
+        c.check_not_paused(PAUSE_OPENS | PAUSE_SWAPS | PAUSE_EXECUTIONS).unwrap();
+
+        c.paused = PAUSE_EXECUTIONS;
+        assert!(c.check_not_paused(PAUSE_EXECUTIONS).is_err());
+        c.check_not_paused(PAUSE_OPENS | PAUSE_SWAPS).unwrap();
+
+        c.paused_venues = 1 << DexType::Jupiter as u8;
+        assert!(c.is_venue_paused(DexType::Jupiter));
+        c.check_venues([DexType::Raydium, DexType::Orca]).unwrap();
+        assert!(c.check_venues([DexType::Raydium, DexType::Jupiter]).is_err());
+    }
+
+    #[test]
+    fn only_a_set_guardian_shares_pause_rights() {
+        let mut c = config();
+        assert!(c.can_pause(&c.admin.clone()));
+        assert!(!c.can_pause(&Pubkey::default()));
+        assert!(!c.can_pause(&Pubkey::new_unique()));
+
+        c.guardian = Pubkey::new_unique();
+        assert!(c.can_pause(&c.guardian.clone()));
+    }
+
+    #[test]
+    fn paused_pair_address_ignores_direction() {
+        let (x, y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(PausedPair::address(&x, &y), PausedPair::address(&y, &x));
+
+        let (address, _) = PausedPair::address(&x, &y);
+        let mut unpaused = crate::dex::fixtures::FixtureAccount::new(address, Pubkey::default(), vec![]);
+        PausedPair::check_not_paused(&unpaused.info(), &y, &x).unwrap();
+
+        let mut paused = crate::dex::fixtures::FixtureAccount::new(address, crate::ID, vec![0; PausedPair::SIZE]);
+        assert!(PausedPair::check_not_paused(&paused.info(), &x, &y).is_err());
+
+        let mut elsewhere = crate::dex::fixtures::unused_account();
+        assert!(PausedPair::check_not_paused(&elsewhere.info(), &x, &y).is_err());
+    }
+
+    #[test]
+    fn fee_account_is_the_destination_ata_or_the_treasury() {
+        let c = config();
+        let mint = Pubkey::new_unique();
+        for token_program in [anchor_spl::token::ID, anchor_spl::token_2022::ID] {
+            let ata = get_associated_token_address_with_program_id(&c.fee_destination, &mint, &token_program);
+            assert!(c.is_fee_account(&ata, &mint, &token_program));
+            assert!(c.is_fee_account(&treasury_address(&mint).0, &mint, &token_program));
+        }
+
+        let callers_ata = get_associated_token_address_with_program_id(&Pubkey::new_unique(), &mint, &anchor_spl::token::ID);
+        assert!(!c.is_fee_account(&callers_ata, &mint, &anchor_spl::token::ID));
+        // Another mint's treasury or ATA does not count
+        let other = Pubkey::new_unique();
+        assert!(!c.is_fee_account(&treasury_address(&other).0, &mint, &anchor_spl::token::ID));
+        let other_ata = get_associated_token_address_with_program_id(&c.fee_destination, &other, &anchor_spl::token::ID);
+        assert!(!c.is_fee_account(&other_ata, &mint, &anchor_spl::token::ID));
+    }
+
+    #[test]
+    fn migration_routes_only_target_migration_pools() {
+        assert_eq!(MigrationRoute::venue(0).unwrap(), DexType::Raydium);
+        assert_eq!(MigrationRoute::venue(7).unwrap(), DexType::RaydiumCpmm);
+        // The curve itself, order books and aggregators are never a destination
+        for dex_type in [3, 4, 5, 6, 8, 9, 200] {
+            assert!(MigrationRoute::venue(dex_type).is_err());
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    non_transferable::NonTransferable, permanent_delegate::PermanentDelegate,
+    transfer_fee::{TransferFeeAmount, TransferFeeConfig}, transfer_hook::TransferHook,
+    BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::{Account as SplAccount, Mint as SplMint};
+use anchor_spl::token_2022_extensions::{harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint};
+use anchor_spl::token_interface::{self, CloseAccount, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked};
+use crate::errors::PyroSwapError;
+
+/// `transfer_checked` of `amount` from `from` to `to`. Under a Token-2022
+/// transfer fee `to` receives less than `amount`.
+pub fn transfer_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi_accounts = TransferChecked {
+        from,
+        mint: mint.to_account_info(),
+        to,
+        authority,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Whether `mint` is the wrapped SOL mint of SPL Token or Token-2022
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == anchor_spl::token::spl_token::native_mint::ID
+        || *mint == anchor_spl::token_2022::spl_token_2022::native_mint::ID
+}
+
+/// Wrap `amount` lamports from the system-owned `from` into the wSOL account
+/// `to`: send the lamports, then `sync_native` so the token balance follows.
+pub fn wrap_native<'info>(
+    system_program: &Program<'info, System>,
+    token_program: &Interface<'info, TokenInterface>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer { from, to: to.clone() },
+        ),
+        amount,
+    )?;
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), SyncNative { account: to });
+    token_interface::sync_native(cpi_ctx)
+}
+
+/// What to send so that the recipient nets `net_amount` after the mint's
+/// transfer fee in `epoch`. Mints without the extension charge nothing.
+pub fn amount_with_transfer_fee(mint: &AccountInfo, net_amount: u64, epoch: u64) -> Result<u64> {
+    if *mint.owner != anchor_spl::token_2022::ID || net_amount == 0 {
+        return Ok(net_amount);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<SplMint>::unpack(&data)
+        .map_err(|_| PyroSwapError::InvalidTokenAccount)?;
+    let Ok(config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(net_amount);
+    };
+    let fee = config
+        .calculate_inverse_epoch_fee(epoch, net_amount)
+        .ok_or(PyroSwapError::MathOverflow)?;
+    net_amount.checked_add(fee).ok_or(PyroSwapError::MathOverflow.into())
+}
+
+/// Reject Token-2022 mints whose extensions would let tokens be stuck in or
+/// taken out of a vault: non-transferable mints, a permanent delegate, or a
+/// transfer hook program, which could refuse the vault's transfers and needs
+/// accounts no instruction here passes. Transfer fees are accounted for and
+/// allowed, as are unset delegates and hooks.
+pub fn check_mint_extensions(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<SplMint>::unpack(&data)
+        .map_err(|_| PyroSwapError::InvalidTokenAccount)?;
+
+    require!(state.get_extension::<NonTransferable>().is_err(), PyroSwapError::UnsupportedMintExtension);
+    if let Ok(delegate) = state.get_extension::<PermanentDelegate>() {
+        require!(Option::<Pubkey>::from(delegate.delegate).is_none(), PyroSwapError::UnsupportedMintExtension);
+    }
+    if let Ok(hook) = state.get_extension::<TransferHook>() {
+        require!(Option::<Pubkey>::from(hook.program_id).is_none(), PyroSwapError::UnsupportedMintExtension);
+    }
+    Ok(())
+}
+
+/// Empty a vault-PDA-owned token account into `destination` and close it,
+/// returning its rent to `rent_destination`.
+///
+/// Anyone can send tokens into a vault, so a non-empty vault without a
+/// destination is an error rather than a silent loss, except for a wSOL vault:
+/// closing it unwraps its balance to `rent_destination` as lamports, so no
+/// token account is needed on the way out. Transfer fees withheld
+/// in the vault are harvested to the mint first, since Token-2022 will not
+/// close an account holding them. Returns the amount swept.
+pub fn sweep_and_close_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    destination: Option<&InterfaceAccount<'info, TokenAccount>>,
+    authority: &AccountInfo<'info>,
+    rent_destination: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let amount = vault.amount;
+    let unwrap = destination.is_none() && vault.is_native();
+    if amount > 0 && !unwrap {
+        let destination = destination.ok_or(PyroSwapError::VaultNotEmpty)?;
+        transfer_tokens(
+            token_program,
+            mint,
+            vault.to_account_info(),
+            destination.to_account_info(),
+            authority.clone(),
+            amount,
+            signer_seeds,
+        )?;
+    }
+
+    if withheld_transfer_fees(&vault.to_account_info())? > 0 {
+        let cpi_accounts = HarvestWithheldTokensToMint {
+            token_program_id: token_program.to_account_info(),
+            mint: mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+        harvest_withheld_tokens_to_mint(cpi_ctx, vec![vault.to_account_info()])?;
+    }
+
+    let cpi_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: rent_destination.clone(),
+        authority: authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    token_interface::close_account(cpi_ctx)?;
+
+    Ok(amount)
+}
+
+/// Transfer fees withheld in a Token-2022 account, zero for SPL Token accounts
+fn withheld_transfer_fees(account: &AccountInfo) -> Result<u64> {
+    if *account.owner != anchor_spl::token_2022::ID {
+        return Ok(0);
+    }
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<SplAccount>::unpack(&data)
+        .map_err(|_| PyroSwapError::InvalidTokenAccount)?;
+    Ok(state
+        .get_extension::<TransferFeeAmount>()
+        .map_or(0, |fees| u64::from(fees.withheld_amount)))
+}
+
+/// Pay out the execution fee escrow PDA and leave it empty.
+///
+/// `keeper_amount` goes to the keeper, everything else (the unpaid escrow plus
+/// anything donated to the PDA) goes to the owner. The PDA must hold at least
+/// the `recorded` escrow, and `keeper_amount` can never exceed it.
+pub fn drain_escrow<'info>(
+    system_program: &Program<'info, System>,
+    escrow: &SystemAccount<'info>,
+    recorded: u64,
+    keeper: Option<(&AccountInfo<'info>, u64)>,
+    owner: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let held = escrow.lamports();
+    require!(held >= recorded, PyroSwapError::EscrowMismatch);
+
+    let mut remaining = held;
+    if let Some((keeper, keeper_amount)) = keeper {
+        require!(keeper_amount <= recorded, PyroSwapError::EscrowMismatch);
+        transfer_from_escrow(system_program, escrow, keeper, keeper_amount, signer_seeds)?;
+        remaining = remaining
+            .checked_sub(keeper_amount)
+            .ok_or(PyroSwapError::MathOverflow)?;
+    }
+    transfer_from_escrow(system_program, escrow, owner, remaining, signer_seeds)?;
+
+    require!(escrow.lamports() == 0, PyroSwapError::EscrowMismatch);
+    Ok(())
+}
+
+fn transfer_from_escrow<'info>(
+    system_program: &Program<'info, System>,
+    escrow: &SystemAccount<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let cpi_accounts = system_program::Transfer {
+        from: escrow.to_account_info(),
+        to: to.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    system_program::transfer(cpi_ctx, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::fixtures::*;
+
+    const NON_TRANSFERABLE: u16 = 9;
+    const PERMANENT_DELEGATE: u16 = 12;
+    const TRANSFER_HOOK: u16 = 14;
+
+    #[test]
+    fn grosses_up_transfer_fees() {
+        let mut mint = transfer_fee_mint(Pubkey::new_unique(), 100, 1_000);
+        // 1% of 9_900 is 99, so sending 10_000 nets exactly 9_900
+        assert_eq!(amount_with_transfer_fee(&mint.info(), 9_900, 0).unwrap(), 10_000);
+        // The fee cap applies to the gross amount as well
+        assert_eq!(amount_with_transfer_fee(&mint.info(), 1_000_000, 0).unwrap(), 1_001_000);
+
+        let mut plain = FixtureAccount::new(Pubkey::new_unique(), anchor_spl::token::ID, vec![0u8; 82]);
+        assert_eq!(amount_with_transfer_fee(&plain.info(), 9_900, 0).unwrap(), 9_900);
+    }
+
+    #[test]
+    fn rejects_mints_that_can_trap_or_take_vault_tokens() {
+        let mut non_transferable = extension_mint(Pubkey::new_unique(), NON_TRANSFERABLE, &[]);
+        assert!(check_mint_extensions(&non_transferable.info()).is_err());
+
+        let delegate = Pubkey::new_unique();
+        let mut permanent_delegate = extension_mint(Pubkey::new_unique(), PERMANENT_DELEGATE, delegate.as_ref());
+        assert!(check_mint_extensions(&permanent_delegate.info()).is_err());
+
+        let mut hook = [0u8; 64];
+        write_pubkey(&mut hook, 32, &Pubkey::new_unique());
+        let mut transfer_hook = extension_mint(Pubkey::new_unique(), TRANSFER_HOOK, &hook);
+        assert!(check_mint_extensions(&transfer_hook.info()).is_err());
+    }
+
+    #[test]
+    fn accepts_transfer_fees_and_unset_extensions() {
+        let mut fee_mint = transfer_fee_mint(Pubkey::new_unique(), 50, u64::MAX);
+        assert!(check_mint_extensions(&fee_mint.info()).is_ok());
+
+        let mut no_delegate = extension_mint(Pubkey::new_unique(), PERMANENT_DELEGATE, &[0u8; 32]);
+        assert!(check_mint_extensions(&no_delegate.info()).is_ok());
+
+        let mut no_hook = extension_mint(Pubkey::new_unique(), TRANSFER_HOOK, &[0u8; 64]);
+        assert!(check_mint_extensions(&no_hook.info()).is_ok());
+    }
+
+    #[test]
+    fn recognizes_both_wrapped_sol_mints() {
+        assert!(is_native_mint(&crate::dex::pumpfun::NATIVE_MINT));
+        assert!(is_native_mint(&anchor_spl::token_2022::spl_token_2022::native_mint::ID));
+        assert!(!is_native_mint(&Pubkey::new_unique()));
+    }
+}
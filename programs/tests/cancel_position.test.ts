@@ -1,24 +1,31 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { PyroSwap } from "../../target/types/pyro_swap";
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
 import { TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo, getAccount } from "@solana/spl-token";
 import { assert } from "chai";
+import { MOCK_PRICE_FEED, ensureConfig, pausedPairAddress } from "./setup";
 
 describe("cancel-position", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
   const program = anchor.workspace.PyroSwap as Program<PyroSwap>;
-  
+
   const user = Keypair.generate();
   let inputMint: PublicKey;
   let outputMint: PublicKey;
   let userTokenAccount: PublicKey;
-  
-  // Mock Oracle
-  const mockOracle = Keypair.generate();
+
+  const txFee = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return tx!.meta!.fee;
+  };
 
   before(async () => {
+    await ensureConfig(program);
     await provider.connection.confirmTransaction(
       await provider.connection.requestAirdrop(user.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL)
     );
@@ -29,9 +36,78 @@ describe("cancel-position", () => {
   });
 
   it("Cancels a position and refunds funds", async () => {
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), user.publicKey.toBuffer(), inputMint.toBuffer()],
+      program.programId
+    );
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [outputVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("output_vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), positionPda.toBuffer()],
+      program.programId
+    );
+    const pausedPairPda = pausedPairAddress(program, inputMint, outputMint);
+
+    const lamportsBefore = await provider.connection.getBalance(user.publicKey);
+
     // 1. Open Position
-    // We recycle logic from open_position test or create a helper
+    const openSig = await program.methods
+      .openPosition(new anchor.BN(10 * 10**9), 500, 1000, new anchor.BN(100 * 10**6), new anchor.BN(5_000_000), 0, null)
+      .accounts({
+        position: positionPda,
+        vault: vaultPda,
+        outputVault: outputVaultPda,
+        escrow: escrowPda,
+        inputMint,
+        outputMint,
+        oraclePriceFeed: MOCK_PRICE_FEED,
+        pausedPair: pausedPairPda,
+        user: user.publicKey,
+        userInputToken: userTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
     // 2. Call cancel_position
-    // 3. Assert: Vault empty, User refunded, Position status Cancelled
+    const cancelSig = await program.methods
+      .cancelPosition()
+      .accounts({
+        position: positionPda,
+        vault: vaultPda,
+        outputVault: outputVaultPda,
+        escrow: escrowPda,
+        owner: user.publicKey,
+        ownerTokenAccount: userTokenAccount,
+        ownerOutputToken: null,
+        inputMint,
+        outputMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    // 3. Assert: tokens refunded, every account closed
+    const userToken = await getAccount(provider.connection, userTokenAccount);
+    assert.equal(userToken.amount.toString(), (1000 * 10**9).toString());
+    for (const closed of [positionPda, vaultPda, outputVaultPda, escrowPda]) {
+      assert.isNull(await provider.connection.getAccountInfo(closed));
+    }
+
+    // Escrow and all rent came back: the only lamports spent are transaction fees
+    const lamportsAfter = await provider.connection.getBalance(user.publicKey);
+    const fees = (await txFee(openSig)) + (await txFee(cancelSig));
+    assert.equal(lamportsBefore - lamportsAfter, fees);
   });
 });
@@ -0,0 +1,177 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PyroSwap } from "../../target/types/pyro_swap";
+import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo, getAccount } from "@solana/spl-token";
+import { assert } from "chai";
+import { MOCK_PRICE_FEED, ensureConfig, feeDestination, pausedPairAddress } from "./setup";
+
+describe("expire-position", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.PyroSwap as Program<PyroSwap>;
+
+  const user = Keypair.generate();
+  const keeper = Keypair.generate();
+  let inputMint: PublicKey;
+  let outputMint: PublicKey;
+  let userTokenAccount: PublicKey;
+  let feeTokenAccount: PublicKey;
+
+  const txFee = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return tx!.meta!.fee;
+  };
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  before(async () => {
+    await ensureConfig(program);
+    for (const kp of [user, keeper]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(kp.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL)
+      );
+    }
+    inputMint = await createMint(provider.connection, user, user.publicKey, null, 9);
+    outputMint = await createMint(provider.connection, user, user.publicKey, null, 6);
+    userTokenAccount = await createAssociatedTokenAccount(provider.connection, user, inputMint, user.publicKey);
+    await mintTo(provider.connection, user, inputMint, userTokenAccount, user.publicKey, 1000 * 10**9);
+    feeTokenAccount = await createAssociatedTokenAccount(provider.connection, user, outputMint, feeDestination.publicKey);
+  });
+
+  it("Refunds an expired position and pays the keeper", async () => {
+    const amountIn = new anchor.BN(10 * 10**9);
+    const executionFee = new anchor.BN(5_000_000);
+    const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), user.publicKey.toBuffer(), inputMint.toBuffer()],
+      program.programId
+    );
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [outputVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("output_vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), positionPda.toBuffer()],
+      program.programId
+    );
+    const pausedPairPda = pausedPairAddress(program, inputMint, outputMint);
+
+    const expireAccounts = {
+      position: positionPda,
+      vault: vaultPda,
+      outputVault: outputVaultPda,
+      escrow: escrowPda,
+      owner: user.publicKey,
+      ownerTokenAccount: userTokenAccount,
+      ownerOutputToken: null,
+      keeper: keeper.publicKey,
+      config: configPda,
+      inputMint,
+      outputMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      outputTokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const userBefore = await provider.connection.getBalance(user.publicKey);
+
+    const openSig = await program.methods
+      .openPosition(amountIn, 500, 1000, new anchor.BN(100 * 10**6), executionFee, 0, expiresAt)
+      .accounts({
+        position: positionPda,
+        vault: vaultPda,
+        outputVault: outputVaultPda,
+        escrow: escrowPda,
+        inputMint,
+        outputMint,
+        oraclePriceFeed: MOCK_PRICE_FEED,
+        pausedPair: pausedPairPda,
+        user: user.publicKey,
+        userInputToken: userTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    // Too early: the position has not reached its expiry yet
+    try {
+      await program.methods.expirePosition().accounts(expireAccounts).signers([keeper]).rpc();
+      assert.fail("expire_position should fail before expires_at");
+    } catch (e: any) {
+      assert.include(e.message, "PositionNotExpired");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    // Expired: a keeper can no longer swap it, only refund it
+    try {
+      await program.methods
+        .executePosition(new anchor.BN(0), null, null, null, null)
+        .accounts({
+          position: positionPda,
+          vault: vaultPda,
+          outputVault: outputVaultPda,
+          escrow: escrowPda,
+          owner: user.publicKey,
+          ownerInputToken: null,
+          ownerOutputToken: null,
+          executor: keeper.publicKey,
+          config: configPda,
+          pausedPair: pausedPairPda,
+          oraclePriceFeed: MOCK_PRICE_FEED,
+          bondingCurve: null,
+          migrationRoute: null,
+          protocolFeeDestination: feeTokenAccount,
+          inputMint,
+          outputMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([keeper])
+        .rpc();
+      assert.fail("execute_position should fail after expires_at");
+    } catch (e: any) {
+      assert.include(e.message, "PositionExpired");
+    }
+
+    const keeperBefore = await provider.connection.getBalance(keeper.publicKey);
+    const expireSig = await program.methods
+      .expirePosition()
+      .accounts(expireAccounts)
+      .signers([keeper])
+      .rpc({ commitment: "confirmed" });
+
+    const config = await program.account.globalConfig.fetch(configPda);
+    const keeperFee = Math.floor(executionFee.toNumber() * config.expiryKeeperFeeBps / 10000);
+
+    // Keeper earns its share of the escrow minus the transaction fee it paid
+    const keeperAfter = await provider.connection.getBalance(keeper.publicKey);
+    assert.equal(keeperAfter - keeperBefore, keeperFee - (await txFee(expireSig)));
+
+    // Owner gets every lamport of rent and escrow back except the keeper's share
+    const userAfter = await provider.connection.getBalance(user.publicKey);
+    assert.equal(userBefore - userAfter, keeperFee + (await txFee(openSig)));
+
+    const userToken = await getAccount(provider.connection, userTokenAccount);
+    assert.equal(userToken.amount.toString(), (1000 * 10**9).toString());
+    for (const closed of [positionPda, vaultPda, outputVaultPda, escrowPda]) {
+      assert.isNull(await provider.connection.getAccountInfo(closed));
+    }
+  });
+});
@@ -1,86 +1,128 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { PyroSwap } from "../../target/types/pyro_swap";
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo } from "@solana/spl-token";
+import { Keypair, PublicKey } from "@solana/web3.js";
 import { assert } from "chai";
+import {
+  admin,
+  feeDestination,
+  ensureConfig,
+  PROTOCOL_FEE_BPS,
+  REFERRAL_FEE_SHARE_BPS,
+  EXPIRY_KEEPER_FEE_BPS,
+} from "./setup";
 
 describe("pyro-swap", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.PyroSwap as Program<PyroSwap>;
-  
-  // Test accounts
-  const admin = Keypair.generate();
-  const feeDestination = Keypair.generate();
-  let inputMint: PublicKey;
-  let outputMint: PublicKey;
-  
+
   // PDAs
   let configPda: PublicKey;
-  let configBump: number;
 
   before(async () => {
-    // Airdrop SOL to admin
-    await provider.connection.confirmTransaction(
-      await provider.connection.requestAirdrop(admin.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL)
-    );
-
-    // Create Mints
-    inputMint = await createMint(
-      provider.connection,
-      admin,
-      admin.publicKey,
-      null,
-      9
-    );
-    
-    outputMint = await createMint(
-      provider.connection,
-      admin,
-      admin.publicKey,
-      null,
-      6
-    );
-
-    // Find Config PDA
-    [configPda, configBump] = PublicKey.findProgramAddressSync(
-      [Buffer.from("global_config")],
-      program.programId
-    );
+    // Another file may already have initialized the shared config
+    configPda = await ensureConfig(program);
   });
 
   it("Is initialized!", async () => {
-    const protocolFeeBps = 50; // 0.5%
-    const referralFeeShareBps = 5000; // 50%
-
-    // Create fee destination token account (mock)
-    // Actually config stores the fee destination WALLET/ATA depending on implementation
-    // In state.rs: pub fee_destination: Pubkey (Protocol fee ATA or Wallet?)
-    // Usually a wallet implementation is more flexible for SOL, but for tokens needs ATA
-    // Let's check state.rs struct... it's just a Pubkey.
-    
+    const config = await program.account.globalConfig.fetch(configPda);
+
+    assert.ok(config.admin.equals(admin.publicKey));
+    assert.ok(config.feeDestination.equals(feeDestination.publicKey));
+    assert.equal(config.protocolFeeBps, PROTOCOL_FEE_BPS);
+    assert.equal(config.referralFeeShareBps, REFERRAL_FEE_SHARE_BPS);
+    assert.equal(config.expiryKeeperFeeBps, EXPIRY_KEEPER_FEE_BPS);
+    assert.equal(config.version, 1);
+    assert.isNull(config.pendingAdmin);
+  });
+
+  it("Updates config", async () => {
     await program.methods
-      .initialize(protocolFeeBps, referralFeeShareBps)
-      .accounts({
-        admin: admin.publicKey,
-        config: configPda,
-        feeDestination: feeDestination.publicKey,
-        systemProgram: SystemProgram.programId,
-      })
+      .updateConfig(100, null, null)
+      .accounts({ config: configPda, admin: admin.publicKey })
       .signers([admin])
       .rpc();
 
-    const config = await program.account.globalConfig.fetch(configPda);
-    
-    assert.ok(config.admin.equals(admin.publicKey));
-    assert.ok(config.feeDestination.equals(feeDestination.publicKey));
-    assert.equal(config.protocolFeeBps, protocolFeeBps);
-    assert.equal(config.referralFeeShareBps, referralFeeShareBps);
+    let config = await program.account.globalConfig.fetch(configPda);
+    assert.equal(config.protocolFeeBps, 100);
+    assert.equal(config.referralFeeShareBps, 5000);
+
+    // MAX_PROTOCOL_FEE_BPS is 500
+    try {
+      await program.methods
+        .updateConfig(501, null, null)
+        .accounts({ config: configPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      assert.fail("update_config should reject a fee above the cap");
+    } catch (e: any) {
+      assert.include(e.message, "InvalidBasisPoints");
+    }
+
+    const newFeeDestination = Keypair.generate().publicKey;
+    await program.methods
+      .setFeeDestination(newFeeDestination)
+      .accounts({ config: configPda, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+    config = await program.account.globalConfig.fetch(configPda);
+    assert.ok(config.feeDestination.equals(newFeeDestination));
+
+    await program.methods
+      .setFeeDestination(feeDestination.publicKey)
+      .accounts({ config: configPda, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
   });
 
-  it("Updates config (if updated instruction exists)", async () => {
-    // Add logic if update instruction exists
+  it("Hands over the admin in two steps", async () => {
+    const newAdmin = Keypair.generate();
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ config: configPda, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+    let config = await program.account.globalConfig.fetch(configPda);
+    assert.ok(config.admin.equals(admin.publicKey));
+    assert.ok(config.pendingAdmin.equals(newAdmin.publicKey));
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({ config: configPda, newAdmin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+
+    config = await program.account.globalConfig.fetch(configPda);
+    assert.ok(config.admin.equals(newAdmin.publicKey));
+    assert.isNull(config.pendingAdmin);
+
+    // The previous admin lost access
+    try {
+      await program.methods
+        .updateConfig(50, null, null)
+        .accounts({ config: configPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      assert.fail("the previous admin should no longer update the config");
+    } catch (e: any) {
+      assert.include(e.message, "Unauthorized");
+    }
+
+    // Hand it back so the shared admin keeps working for later files
+    await program.methods
+      .proposeAdmin(admin.publicKey)
+      .accounts({ config: configPda, admin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accounts({ config: configPda, newAdmin: admin.publicKey })
+      .signers([admin])
+      .rpc();
   });
 });
@@ -2,8 +2,9 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { PyroSwap } from "../../target/types/pyro_swap";
 import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, createMint, getAssociatedTokenAddress, createAssociatedTokenAccount, mintTo } from "@solana/spl-token";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createMint, getAssociatedTokenAddress, createAssociatedTokenAccount, mintTo } from "@solana/spl-token";
 import { assert } from "chai";
+import { MOCK_PRICE_FEED, ensureConfig, pausedPairAddress } from "./setup";
 
 describe("open-position", () => {
   const provider = anchor.AnchorProvider.env();
@@ -16,16 +17,8 @@ describe("open-position", () => {
   let outputMint: PublicKey;
   let userParamTokenAccount: PublicKey;
   
-  // Mock Oracle
-  // In localnet tests, we can't easily interface with real Pyth. 
-  // We'd typically mock the oracle account structure or fork mainnet.
-  // For basic unit test structure, we'll try to use a generated keypair as oracle 
-  // and accept that it might fail validation if we don't write Pyth data to it.
-  // **Ideally**, we use `banks-client` or similar to write mock account data.
-  // For now, we will create a placeholder test.
-  const mockOracle = Keypair.generate();
-
   before(async () => {
+    await ensureConfig(program);
     // Setup User
     await provider.connection.confirmTransaction(
       await provider.connection.requestAirdrop(user.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL)
@@ -72,44 +65,100 @@ describe("open-position", () => {
       program.programId
     );
 
-    // NOTE: This test will fail on-chain without valid Pyth data in mockOracle
-    // unless we disable oracle validation or mock the account data properly.
-    // In a real test suite, you'd use `program.provider.connection` to write bytes to `mockOracle`.
-    
-    try {
-        await program.methods
-        .openPosition(
-            amountIn,
-            slBps,
-            tpBps,
-            entryPrice,
-            executionFee,
-            preferredDex
-        )
-        .accounts({
-            position: positionPda,
-            vault: vaultPda,
-            inputMint: inputMint,
-            outputMint: outputMint,
-            oraclePriceFeed: mockOracle.publicKey,
-            user: user.publicKey,
-            userInputToken: userParamTokenAccount,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-            rent: SYSVAR_RENT_PUBKEY,
-        })
-        .signers([user])
-        .rpc();
-
-        const position = await program.account.position.fetch(positionPda);
-        assert.equal(position.amountIn.toString(), amountIn.toString());
-        assert.equal(position.slBps, slBps);
-        assert.equal(position.tpBps, tpBps);
-        
-    } catch (e: any) {
-        // Expected failure due to Invalid Oracle Account data (we didn't write Pyth headers)
-        // In a full implementation, we add a `mock_oracle` utility.
-        console.log("Test expectedly failed due to empty oracle account (mocking needed):", e.message);
-    }
+    const [outputVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("output_vault"), positionPda.toBuffer()],
+      program.programId
+    );
+
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), positionPda.toBuffer()],
+      program.programId
+    );
+    const pausedPairPda = pausedPairAddress(program, inputMint, outputMint);
+
+    await program.methods
+      .openPosition(
+        amountIn,
+        slBps,
+        tpBps,
+        entryPrice,
+        executionFee,
+        preferredDex,
+        null // no expiry
+      )
+      .accounts({
+        position: positionPda,
+        vault: vaultPda,
+        outputVault: outputVaultPda,
+        escrow: escrowPda,
+        inputMint: inputMint,
+        outputMint: outputMint,
+        oraclePriceFeed: MOCK_PRICE_FEED,
+        pausedPair: pausedPairPda,
+        user: user.publicKey,
+        userInputToken: userParamTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    const position = await program.account.position.fetch(positionPda);
+    assert.equal(position.amountIn.toString(), amountIn.toString());
+    assert.equal(position.slBps, slBps);
+    assert.equal(position.tpBps, tpBps);
+    assert.ok(position.oraclePriceFeed.equals(MOCK_PRICE_FEED));
+  });
+
+  it("Opens a PumpFun position paying out SOL", async () => {
+    // A fresh input mint, since positions are keyed by owner and input mint
+    const tokenMint = await createMint(provider.connection, user, user.publicKey, null, 6);
+    const userTokenAccount = await createAssociatedTokenAccount(provider.connection, user, tokenMint, user.publicKey);
+    await mintTo(provider.connection, user, tokenMint, userTokenAccount, user.publicKey, 1000 * 10**6);
+
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), user.publicKey.toBuffer(), tokenMint.toBuffer()],
+      program.programId
+    );
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [outputVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("output_vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), positionPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .openPosition(new anchor.BN(10 * 10**6), 500, 1000, new anchor.BN(100 * 10**6), new anchor.BN(5_000_000), 3, null)
+      .accounts({
+        position: positionPda,
+        vault: vaultPda,
+        outputVault: outputVaultPda,
+        escrow: escrowPda,
+        inputMint: tokenMint,
+        outputMint: NATIVE_MINT,
+        oraclePriceFeed: MOCK_PRICE_FEED,
+        pausedPair: pausedPairAddress(program, tokenMint, NATIVE_MINT),
+        user: user.publicKey,
+        userInputToken: userTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    // The sell's lamports are wrapped into the output vault at execution
+    const position = await program.account.position.fetch(positionPda);
+    assert.ok(position.outputMint.equals(NATIVE_MINT));
+    assert.deepEqual(position.preferredDex, { pumpFun: {} });
   });
 });
@@ -0,0 +1,64 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PyroSwap } from "../../target/types/pyro_swap";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+
+// Shared by every test file: mocha loads this module once, so all files see
+// the same admin and the config is initialized by whichever file runs first.
+
+// Pyth price account preloaded by Anchor.toml from tests/fixtures/pyth_price.json
+export const MOCK_PRICE_FEED = new PublicKey("HeDhDqdUuYcnMFJshCq8yeh6nER6BxHfQVStjJYJXthN");
+// The mock feed's price, $100 at expo -6
+export const MOCK_PRICE = 100 * 10 ** 6;
+
+export const admin = Keypair.generate();
+export const feeDestination = Keypair.generate();
+
+export const PROTOCOL_FEE_BPS = 50; // 0.5%
+export const REFERRAL_FEE_SHARE_BPS = 5000; // 50%
+export const EXPIRY_KEEPER_FEE_BPS = 1000; // 10% of the execution fee escrow
+
+export function configAddress(program: Program<PyroSwap>): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  return pda;
+}
+
+// Pair pauses live at one address per pair, mints in byte order
+export function pausedPairAddress(program: Program<PyroSwap>, mintX: PublicKey, mintY: PublicKey): PublicKey {
+  const [mintA, mintB] = [mintX, mintY].sort((a, b) => a.toBuffer().compare(b.toBuffer()));
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("paused_pair"), mintA.toBuffer(), mintB.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+let initialized: Promise<PublicKey> | null = null;
+
+// Initialize the global config once per test run and return its address
+export function ensureConfig(program: Program<PyroSwap>): Promise<PublicKey> {
+  if (!initialized) {
+    initialized = (async () => {
+      const provider = program.provider as anchor.AnchorProvider;
+      const configPda = configAddress(program);
+      if (await program.account.globalConfig.fetchNullable(configPda)) {
+        return configPda;
+      }
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(admin.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL)
+      );
+      await program.methods
+        .initialize(PROTOCOL_FEE_BPS, REFERRAL_FEE_SHARE_BPS, EXPIRY_KEEPER_FEE_BPS)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          feeDestination: feeDestination.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc({ commitment: "confirmed" });
+      return configPda;
+    })();
+  }
+  return initialized;
+}
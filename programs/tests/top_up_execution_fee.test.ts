@@ -0,0 +1,93 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PyroSwap } from "../../target/types/pyro_swap";
+import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo } from "@solana/spl-token";
+import { assert } from "chai";
+import { MOCK_PRICE_FEED, ensureConfig, pausedPairAddress } from "./setup";
+
+describe("top-up-execution-fee", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.PyroSwap as Program<PyroSwap>;
+
+  const user = Keypair.generate();
+  let inputMint: PublicKey;
+  let outputMint: PublicKey;
+  let userTokenAccount: PublicKey;
+
+  before(async () => {
+    await ensureConfig(program);
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(user.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL)
+    );
+    inputMint = await createMint(provider.connection, user, user.publicKey, null, 9);
+    outputMint = await createMint(provider.connection, user, user.publicKey, null, 6);
+    userTokenAccount = await createAssociatedTokenAccount(provider.connection, user, inputMint, user.publicKey);
+    await mintTo(provider.connection, user, inputMint, userTokenAccount, user.publicKey, 1000 * 10**9);
+  });
+
+  it("Raises the keeper bounty held in the escrow PDA", async () => {
+    const executionFee = new anchor.BN(5_000_000);
+    const topUp = new anchor.BN(2_500_000);
+
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), user.publicKey.toBuffer(), inputMint.toBuffer()],
+      program.programId
+    );
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [outputVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("output_vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), positionPda.toBuffer()],
+      program.programId
+    );
+    const pausedPairPda = pausedPairAddress(program, inputMint, outputMint);
+
+    await program.methods
+      .openPosition(new anchor.BN(10 * 10**9), 500, 1000, new anchor.BN(100 * 10**6), executionFee, 0, null)
+      .accounts({
+        position: positionPda,
+        vault: vaultPda,
+        outputVault: outputVaultPda,
+        escrow: escrowPda,
+        inputMint,
+        outputMint,
+        oraclePriceFeed: MOCK_PRICE_FEED,
+        pausedPair: pausedPairPda,
+        user: user.publicKey,
+        userInputToken: userTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    const positionRentBefore = await provider.connection.getBalance(positionPda);
+
+    await program.methods
+      .topUpExecutionFee(topUp)
+      .accounts({
+        position: positionPda,
+        escrow: escrowPda,
+        owner: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const position = await program.account.position.fetch(positionPda);
+    const expected = executionFee.add(topUp);
+    assert.equal(position.executionFeeEscrow.toString(), expected.toString());
+    assert.equal(await provider.connection.getBalance(escrowPda), expected.toNumber());
+    // The position account only ever holds its rent
+    assert.equal(await provider.connection.getBalance(positionPda), positionRentBefore);
+  });
+});
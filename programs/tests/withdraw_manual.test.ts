@@ -0,0 +1,97 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PyroSwap } from "../../target/types/pyro_swap";
+import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo } from "@solana/spl-token";
+import { assert } from "chai";
+import { MOCK_PRICE_FEED, ensureConfig, pausedPairAddress } from "./setup";
+
+describe("withdraw-manual", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.PyroSwap as Program<PyroSwap>;
+
+  const user = Keypair.generate();
+  let inputMint: PublicKey;
+  let outputMint: PublicKey;
+  let userInputToken: PublicKey;
+  let userOutputToken: PublicKey;
+
+  before(async () => {
+    await ensureConfig(program);
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(user.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL)
+    );
+    inputMint = await createMint(provider.connection, user, user.publicKey, null, 9);
+    outputMint = await createMint(provider.connection, user, user.publicKey, null, 6);
+    userInputToken = await createAssociatedTokenAccount(provider.connection, user, inputMint, user.publicKey);
+    userOutputToken = await createAssociatedTokenAccount(provider.connection, user, outputMint, user.publicKey);
+    await mintTo(provider.connection, user, inputMint, userInputToken, user.publicKey, 1000 * 10**9);
+  });
+
+  it("Refuses to settle a position that is still Active", async () => {
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), user.publicKey.toBuffer(), inputMint.toBuffer()],
+      program.programId
+    );
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [outputVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("output_vault"), positionPda.toBuffer()],
+      program.programId
+    );
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), positionPda.toBuffer()],
+      program.programId
+    );
+    const pausedPairPda = pausedPairAddress(program, inputMint, outputMint);
+
+    await program.methods
+      .openPosition(new anchor.BN(10 * 10**9), 500, 1000, new anchor.BN(100 * 10**6), new anchor.BN(5_000_000), 0, null)
+      .accounts({
+        position: positionPda,
+        vault: vaultPda,
+        outputVault: outputVaultPda,
+        escrow: escrowPda,
+        inputMint,
+        outputMint,
+        oraclePriceFeed: MOCK_PRICE_FEED,
+        pausedPair: pausedPairPda,
+        user: user.publicKey,
+        userInputToken,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    try {
+      await program.methods
+        .withdrawManual()
+        .accounts({
+          position: positionPda,
+          vault: vaultPda,
+          outputVault: outputVaultPda,
+          owner: user.publicKey,
+          ownerInputToken: userInputToken,
+          ownerOutputToken: userOutputToken,
+          inputMint,
+          outputMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("withdraw_manual should refuse an Active position");
+    } catch (e: any) {
+      assert.include(e.message, "PositionNotExecuted");
+    }
+
+    const position = await program.account.position.fetch(positionPda);
+    assert.deepEqual(position.status, { active: {} });
+  });
+});